@@ -0,0 +1,151 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Applications started with the session, from the [Desktop Application
+//! Autostart spec](https://specifications.freedesktop.org/autostart-spec/latest/).
+//!
+//! Example:
+//! ```no_run
+//! use freedesktop_entry_parser::{autostart, parse_entry};
+//!
+//! for path in autostart::autostart_files() {
+//!     let entry = parse_entry(path)?;
+//!     if autostart::should_start(&entry.desktop_entry()) {
+//!         println!("{:?}", entry.desktop_entry().exec());
+//!     }
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! A file in an earlier directory hides the files with the same name in the
+//! later ones, which [`autostart_files`](fn.autostart_files.html) takes care
+//! of.  That's how a user turns off an application with a file that has
+//! `Hidden=true`.
+use crate::{DesktopEntry, VisibilityChecks};
+use std::{
+    collections::BTreeMap,
+    env,
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// The `autostart` directories, from the most to the least important: the
+/// one in `XDG_CONFIG_HOME` followed by the ones in `XDG_CONFIG_DIRS`.
+pub fn autostart_dirs() -> Vec<PathBuf> {
+    let config_home = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            env::var_os("HOME").map(|home| Path::new(&home).join(".config"))
+        });
+    let config_dirs = env::var_os("XDG_CONFIG_DIRS")
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or_else(|| "/etc/xdg".into());
+    config_home
+        .into_iter()
+        .chain(env::split_paths(&config_dirs))
+        .map(|dir| dir.join("autostart"))
+        .collect()
+}
+
+/// The `.desktop` files in the [`autostart_dirs`](fn.autostart_dirs.html).
+/// See [`autostart_files_in`](fn.autostart_files_in.html).
+pub fn autostart_files() -> Vec<PathBuf> {
+    autostart_files_in(&autostart_dirs())
+}
+
+/// The `.desktop` files in `dirs`, sorted by file name.  Of the files with
+/// the same name only the one in the earliest directory is returned, the
+/// others are hidden by it.
+pub fn autostart_files_in<P: AsRef<Path>>(dirs: &[P]) -> Vec<PathBuf> {
+    let mut files = BTreeMap::<OsString, PathBuf>::new();
+    for dir in dirs {
+        let dir = match fs::read_dir(dir) {
+            Ok(dir) => dir,
+            Err(_) => continue,
+        };
+        for file in dir.flatten() {
+            let path = file.path();
+            let is_entry = path.extension() == Some("desktop".as_ref());
+            if is_entry && path.is_file() {
+                files.entry(file.file_name()).or_insert(path);
+            }
+        }
+    }
+    files.into_values().collect()
+}
+
+/// Whether `entry` should be started in the current desktop environments,
+/// from `XDG_CURRENT_DESKTOP`.  See [`should_start_in`](fn.should_start_in.html).
+pub fn should_start(entry: &DesktopEntry<'_>) -> bool {
+    let current = env::var("XDG_CURRENT_DESKTOP").unwrap_or_default();
+    let desktops: Vec<&str> =
+        current.split(':').filter(|d| !d.is_empty()).collect();
+    should_start_in(entry, &desktops)
+}
+
+/// Whether `entry` should be started in a session of the desktop
+/// environments `desktops`.
+///
+/// It isn't started if it's `Hidden`, if `OnlyShowIn` or `NotShowIn`
+/// exclude all of `desktops`, if its `TryExec` isn't installed or if
+/// `X-GNOME-Autostart-enabled` is `false`.  `NoDisplay` doesn't matter.
+pub fn should_start_in(entry: &DesktopEntry<'_>, desktops: &[&str]) -> bool {
+    let checks = VisibilityChecks::default()
+        .no_display(false)
+        .desktops(false);
+    entry.shown_in(desktops)
+        && entry.should_show_with(&checks)
+        && entry.attr_bool("X-GNOME-Autostart-enabled") != Some(false)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Entry;
+
+    #[test]
+    fn should_start() {
+        let should_start = |input: &str| {
+            let input = format!("[Desktop Entry]\nExec=foo\n{}", input);
+            let entry = Entry::parse(input.as_bytes()).unwrap();
+            let start = should_start_in(&entry.desktop_entry(), &["GNOME"]);
+            start
+        };
+        assert!(should_start(""));
+        assert!(should_start("NoDisplay=true"));
+        assert!(should_start("X-GNOME-Autostart-enabled=true"));
+        assert!(!should_start("X-GNOME-Autostart-enabled=false"));
+        assert!(!should_start("Hidden=true"));
+        assert!(!should_start("OnlyShowIn=KDE;"));
+        assert!(!should_start("NotShowIn=GNOME;"));
+        assert!(!should_start("TryExec=/no/such/program"));
+    }
+
+    #[test]
+    fn files() {
+        let dirs = [
+            "./test_data/autostart/user",
+            "./test_data/autostart/missing",
+            "./test_data/autostart/system",
+        ];
+        let files = autostart_files_in(&dirs);
+        assert_eq!(
+            files,
+            [
+                Path::new("./test_data/autostart/system/bar.desktop"),
+                Path::new("./test_data/autostart/user/foo.desktop"),
+            ]
+        );
+        let started: Vec<_> = files
+            .iter()
+            .filter(|path| {
+                let entry = Entry::parse_file(path).unwrap();
+                should_start_in(&entry.desktop_entry(), &[])
+            })
+            .collect();
+        assert_eq!(started, [&files[0]]);
+    }
+}
@@ -0,0 +1,125 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::Entry;
+
+/// Build an [`Entry`](struct.Entry.html) from scratch.
+///
+/// Example:
+/// ```
+/// use freedesktop_entry_parser::EntryBuilder;
+///
+/// let entry = EntryBuilder::new()
+///     .section("Desktop Entry")
+///     .attr("Name", "Foo")
+///     .attr_with_param("Name", "de", "Foo")
+///     .section("Desktop Action new-window")
+///     .attr("Name", "New Window")
+///     .build();
+///
+/// assert_eq!(entry.section("Desktop Entry").attr("Name"), Some("Foo"));
+/// ```
+#[derive(Default)]
+pub struct EntryBuilder {
+    entry: Entry,
+}
+
+impl EntryBuilder {
+    /// Create a builder for an entry with no sections.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a section with `name`.  Attributes added after this belong to
+    /// this section.
+    pub fn section(self, name: impl Into<String>) -> SectionBuilder {
+        let name = name.into();
+        let mut entry = self.entry;
+        entry.add_section(&name);
+        SectionBuilder { name, entry }
+    }
+
+    /// Finish building the entry.
+    pub fn build(self) -> Entry {
+        self.entry
+    }
+}
+
+/// Add attributes to a section of an entry being built.
+///
+/// Created from [`EntryBuilder::section`](struct.EntryBuilder.html#method.section)
+///
+/// # Panics
+/// On names that can't be written out, like
+/// [`AttrSelectorMut`](struct.AttrSelectorMut.html).
+pub struct SectionBuilder {
+    name: String,
+    entry: Entry,
+}
+
+impl SectionBuilder {
+    /// Set the value of the attribute `name` in this section.
+    pub fn attr(
+        mut self,
+        name: impl AsRef<str>,
+        value: impl AsRef<str>,
+    ) -> Self {
+        self.entry.section_mut(&self.name).set_attr(name, value);
+        self
+    }
+
+    /// Set the value of the attribute `name` with param value `param_val` in
+    /// this section.
+    pub fn attr_with_param(
+        mut self,
+        name: impl AsRef<str>,
+        param_val: impl AsRef<str>,
+        value: impl AsRef<str>,
+    ) -> Self {
+        self.entry
+            .section_mut(&self.name)
+            .set_attr_with_param(name, param_val, value);
+        self
+    }
+
+    /// Start another section with `name`.
+    pub fn section(self, name: impl Into<String>) -> SectionBuilder {
+        EntryBuilder { entry: self.entry }.section(name)
+    }
+
+    /// Finish building the entry.
+    pub fn build(self) -> Entry {
+        self.entry
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn build() {
+        let entry = EntryBuilder::new()
+            .section("Unit")
+            .attr("Description", "OpenSSH Daemon")
+            .section("Service")
+            .attr("ExecStart", "/usr/bin/sshd -D")
+            .attr_with_param("ExecStart", "test", "/bin/true")
+            .build();
+        assert_eq!(
+            entry.section("Unit").attr("Description"),
+            Some("OpenSSH Daemon")
+        );
+        assert_eq!(
+            entry.section("Service").attr("ExecStart"),
+            Some("/usr/bin/sshd -D")
+        );
+        assert_eq!(
+            entry
+                .section("Service")
+                .attr_with_param("ExecStart", "test"),
+            Some("/bin/true")
+        );
+    }
+}
@@ -0,0 +1,518 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::errors::InvalidCalendarEvent;
+use std::{
+    convert::TryFrom,
+    str::FromStr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// The last year [`next_elapse`](struct.CalendarEvent.html#method.next_elapse)
+/// looks at
+const MAX_YEAR: i64 = 9999;
+
+/// A calendar event, the value of `OnCalendar` in timer units, like
+/// `Mon..Fri *-*-* 10:00:00` or `weekly`.
+///
+/// An event is made of optional weekdays, a date and a time.  Each part of
+/// the date and time can be `*` for any value, a value, a range like
+/// `1..5`, a repetition like `0/15` or `*/2`, or a `,` separated list of
+/// these.  A `~` between the month and the day counts the days from the end
+/// of the month, so `*-02~01` is the last day of February.  The shorthands
+/// `minutely`, `hourly`, `daily`, `weekly`, `monthly`, `quarterly`,
+/// `semiannually` and `yearly` are supported as well.
+///
+/// Times are in UTC.  `UTC` can be written after the event, other time
+/// zones aren't supported.
+///
+/// Example:
+/// ```
+/// use freedesktop_entry_parser::systemd::CalendarEvent;
+/// use std::time::{Duration, UNIX_EPOCH};
+///
+/// let event: CalendarEvent = "Mon..Fri *-*-* 10:00".parse()?;
+/// // Saturday, 2024-01-06 12:00:00
+/// let saturday = UNIX_EPOCH + Duration::from_secs(1_704_542_400);
+/// // Monday, 2024-01-08 10:00:00
+/// let monday = UNIX_EPOCH + Duration::from_secs(1_704_708_000);
+/// assert_eq!(event.next_elapse(saturday), Some(monday));
+/// # Ok::<(), freedesktop_entry_parser::errors::InvalidCalendarEvent>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CalendarEvent {
+    weekdays: Option<u8>,
+    year: Component,
+    month: Component,
+    day: Component,
+    from_end: bool,
+    hour: Component,
+    minute: Component,
+    second: Component,
+}
+
+/// The values of one part of an event, as ranges with a step
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Component(Vec<(u32, u32, u32)>);
+
+impl CalendarEvent {
+    /// Parse a calendar event.
+    pub fn parse(s: &str) -> Result<Self, InvalidCalendarEvent> {
+        Self::parse_parts(s).ok_or_else(|| InvalidCalendarEvent(s.to_owned()))
+    }
+
+    fn parse_parts(s: &str) -> Option<Self> {
+        let mut words: Vec<&str> = s.split_whitespace().collect();
+        if words.len() > 1 && words.last()?.eq_ignore_ascii_case("UTC") {
+            words.pop();
+        }
+        if let [word] = words[..] {
+            if let Some(expanded) = shorthand(&word.to_ascii_lowercase()) {
+                return Self::parse_parts(expanded);
+            }
+        }
+
+        let mut words = words.into_iter().peekable();
+        let weekdays = match words.peek() {
+            Some(word) if word.starts_with(char::is_alphabetic) => {
+                Some(parse_weekdays(words.next()?)?)
+            }
+            _ => None,
+        };
+        let (mut date, mut time) = (None, None);
+        for word in words {
+            if word.contains(':') && time.is_none() {
+                time = Some(word);
+            } else if !word.contains(':') && date.is_none() && time.is_none() {
+                date = Some(word);
+            } else {
+                return None;
+            }
+        }
+        if weekdays.is_none() && date.is_none() && time.is_none() {
+            return None;
+        }
+
+        let (year, month, day, from_end) = match date {
+            Some(date) => parse_date(date)?,
+            None => {
+                (Component::any(), Component::any(), Component::any(), false)
+            }
+        };
+        let (hour, minute, second) = match time {
+            Some(time) => {
+                let parts: Vec<&str> = time.split(':').collect();
+                let second = match parts.get(2) {
+                    Some(second) => Component::parse(second, 0, 59)?,
+                    None => Component::parse("0", 0, 59)?,
+                };
+                if parts.len() > 3 {
+                    return None;
+                }
+                (
+                    Component::parse(parts[0], 0, 23)?,
+                    Component::parse(parts.get(1)?, 0, 59)?,
+                    second,
+                )
+            }
+            None => (
+                Component::parse("0", 0, 23)?,
+                Component::parse("0", 0, 59)?,
+                Component::parse("0", 0, 59)?,
+            ),
+        };
+        Some(CalendarEvent {
+            weekdays,
+            year,
+            month,
+            day,
+            from_end,
+            hour,
+            minute,
+            second,
+        })
+    }
+
+    /// The first time after `after` that matches the event, to the second.
+    /// `None` if there's none before the year 10000.
+    pub fn next_elapse(&self, after: SystemTime) -> Option<SystemTime> {
+        let after = match after.duration_since(UNIX_EPOCH) {
+            Ok(since) => i64::try_from(since.as_secs()).ok()?,
+            // Round down to the second
+            Err(e) => {
+                let before = e.duration();
+                let secs = -i64::try_from(before.as_secs()).ok()?;
+                if before.subsec_nanos() > 0 {
+                    secs - 1
+                } else {
+                    secs
+                }
+            }
+        };
+        let start = after.checked_add(1)?;
+        let (mut year, mut month, mut day) =
+            civil_from_days(start.div_euclid(86_400));
+        let secs = start.rem_euclid(86_400);
+        let (mut hour, mut minute, mut second) =
+            (secs / 3600, secs / 60 % 60, secs % 60);
+
+        loop {
+            if second > 59 {
+                minute += 1;
+                second = 0;
+            }
+            if minute > 59 {
+                hour += 1;
+                minute = 0;
+            }
+            if hour > 23 {
+                day += 1;
+                hour = 0;
+            }
+            if month > 12 {
+                year += 1;
+                month = 1;
+            }
+            if year > MAX_YEAR {
+                return None;
+            }
+            if !self.year.matches(year) {
+                year += 1;
+                month = 1;
+                day = 1;
+                hour = 0;
+                minute = 0;
+                second = 0;
+                continue;
+            }
+            if !self.month.matches(month) {
+                month += 1;
+                day = 1;
+                hour = 0;
+                minute = 0;
+                second = 0;
+                continue;
+            }
+            let days_in_month = days_in_month(year, month);
+            if day > days_in_month {
+                month += 1;
+                day = 1;
+                continue;
+            }
+            let day_value = if self.from_end {
+                days_in_month - day + 1
+            } else {
+                day
+            };
+            let weekday = weekday(days_from_civil(year, month, day));
+            let weekday_matches = match self.weekdays {
+                Some(weekdays) => weekdays & (1 << weekday) != 0,
+                None => true,
+            };
+            if !self.day.matches(day_value) || !weekday_matches {
+                day += 1;
+                hour = 0;
+                minute = 0;
+                second = 0;
+                continue;
+            }
+            if !self.hour.matches(hour) {
+                hour += 1;
+                minute = 0;
+                second = 0;
+                continue;
+            }
+            if !self.minute.matches(minute) {
+                minute += 1;
+                second = 0;
+                continue;
+            }
+            if !self.second.matches(second) {
+                second += 1;
+                continue;
+            }
+            let secs = days_from_civil(year, month, day) * 86_400
+                + hour * 3600
+                + minute * 60
+                + second;
+            return Some(match u64::try_from(secs) {
+                Ok(secs) => UNIX_EPOCH + Duration::from_secs(secs),
+                Err(_) => UNIX_EPOCH - Duration::from_secs(secs.unsigned_abs()),
+            });
+        }
+    }
+}
+
+impl FromStr for CalendarEvent {
+    type Err = InvalidCalendarEvent;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        CalendarEvent::parse(s)
+    }
+}
+
+impl Component {
+    fn any() -> Self {
+        Component(vec![(0, u32::MAX, 1)])
+    }
+
+    /// Parse a `,` separated list of values, ranges and repetitions between
+    /// `min` and `max`.
+    fn parse(s: &str, min: u32, max: u32) -> Option<Self> {
+        let mut ranges = Vec::new();
+        for item in s.split(',') {
+            let mut parts = item.splitn(2, '/');
+            let range = parts.next()?;
+            let step = match parts.next() {
+                Some(step) => Some(step.parse().ok().filter(|step| *step > 0)?),
+                None => None,
+            };
+            let number = |s: &str| {
+                if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+                    return None;
+                }
+                s.parse().ok().filter(|n| (min..=max).contains(n))
+            };
+            let (start, end) = if range == "*" {
+                (min, max)
+            } else if let Some(dots) = range.find("..") {
+                (number(&range[..dots])?, number(&range[dots + 2..])?)
+            } else if step.is_some() {
+                (number(range)?, max)
+            } else {
+                let n = number(range)?;
+                (n, n)
+            };
+            if start > end {
+                return None;
+            }
+            ranges.push((start, end, step.unwrap_or(1)));
+        }
+        Some(Component(ranges))
+    }
+
+    fn matches(&self, value: i64) -> bool {
+        let value = match u32::try_from(value) {
+            Ok(value) => value,
+            Err(_) => return false,
+        };
+        self.0.iter().any(|&(start, end, step)| {
+            (start..=end).contains(&value) && (value - start) % step == 0
+        })
+    }
+}
+
+/// The event a shorthand like `weekly` stands for
+fn shorthand(s: &str) -> Option<&'static str> {
+    Some(match s {
+        "minutely" => "*-*-* *:*:00",
+        "hourly" => "*-*-* *:00:00",
+        "daily" => "*-*-* 00:00:00",
+        "weekly" => "Mon *-*-* 00:00:00",
+        "monthly" => "*-*-01 00:00:00",
+        "quarterly" => "*-01,04,07,10-01 00:00:00",
+        "semiannually" => "*-01,07-01 00:00:00",
+        "yearly" | "annually" => "*-01-01 00:00:00",
+        _ => return None,
+    })
+}
+
+/// Parse weekdays like `Mon..Fri,Sun` into a set of bits, with Monday as
+/// the lowest one.
+fn parse_weekdays(s: &str) -> Option<u8> {
+    const DAYS: [&str; 7] = [
+        "monday",
+        "tuesday",
+        "wednesday",
+        "thursday",
+        "friday",
+        "saturday",
+        "sunday",
+    ];
+    let day = |s: &str| {
+        let s = s.to_ascii_lowercase();
+        DAYS.iter()
+            .position(|day| s == *day || s.len() == 3 && day.starts_with(&s))
+    };
+    let mut weekdays = 0;
+    for item in s.split(',') {
+        let (start, end) = match item.find("..") {
+            Some(dots) => (day(&item[..dots])?, day(&item[dots + 2..])?),
+            None => (day(item)?, day(item)?),
+        };
+        if start > end {
+            return None;
+        }
+        for day in start..=end {
+            weekdays |= 1 << day;
+        }
+    }
+    Some(weekdays)
+}
+
+/// Parse a date like `*-*-01`, `2024-01-01`, `01-01` or `*-02~01` into its
+/// year, month and day, and whether the day counts from the end.
+fn parse_date(s: &str) -> Option<(Component, Component, Component, bool)> {
+    let (rest, day, from_end) = match s.find('~') {
+        Some(tilde) => (&s[..tilde], &s[tilde + 1..], true),
+        None => {
+            let dash = s.rfind('-')?;
+            (&s[..dash], &s[dash + 1..], false)
+        }
+    };
+    let (year, month) = match rest.find('-') {
+        Some(dash) => (
+            Component::parse(&rest[..dash], 1970, MAX_YEAR as u32)?,
+            &rest[dash + 1..],
+        ),
+        None => (Component::any(), rest),
+    };
+    Some((
+        year,
+        Component::parse(month, 1, 12)?,
+        Component::parse(day, 1, 31)?,
+        from_end,
+    ))
+}
+
+fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// The weekday of the day `days` after the epoch, with Monday as 0
+fn weekday(days: i64) -> i64 {
+    (days + 3).rem_euclid(7)
+}
+
+/// Days since the epoch of a date in the proleptic Gregorian calendar
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era =
+        year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// The date of the day `days` after the epoch
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524
+        - day_of_era / 146_096)
+        / 365;
+    let day_of_year =
+        day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400;
+    (if month <= 2 { year + 1 } else { year }, month, day)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn time(
+        (year, month, day): (i64, i64, i64),
+        (hour, minute, second): (i64, i64, i64),
+    ) -> SystemTime {
+        let secs = days_from_civil(year, month, day) * 86_400
+            + hour * 3600
+            + minute * 60
+            + second;
+        UNIX_EPOCH + Duration::from_secs(secs as u64)
+    }
+
+    fn next(event: &str, after: SystemTime) -> Option<SystemTime> {
+        CalendarEvent::parse(event).unwrap().next_elapse(after)
+    }
+
+    #[test]
+    fn civil() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(civil_from_days(19_723), (2024, 1, 1));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        assert_eq!(weekday(days_from_civil(2024, 1, 8)), 0);
+    }
+
+    #[test]
+    fn next_elapse() {
+        // Wednesday
+        let now = time((2024, 2, 14), (12, 30, 15));
+        let cases = [
+            ("minutely", time((2024, 2, 14), (12, 31, 0))),
+            ("hourly", time((2024, 2, 14), (13, 0, 0))),
+            ("daily", time((2024, 2, 15), (0, 0, 0))),
+            ("weekly", time((2024, 2, 19), (0, 0, 0))),
+            ("monthly", time((2024, 3, 1), (0, 0, 0))),
+            ("quarterly", time((2024, 4, 1), (0, 0, 0))),
+            ("yearly", time((2025, 1, 1), (0, 0, 0))),
+            ("Mon..Fri 10:00", time((2024, 2, 15), (10, 0, 0))),
+            ("Sat,Sun *-*-* 20:00:30", time((2024, 2, 17), (20, 0, 30))),
+            ("*:0/20", time((2024, 2, 14), (12, 40, 0))),
+            ("*-02~01", time((2024, 2, 29), (0, 0, 0))),
+            ("Fri *-*~07 18:00", time((2024, 2, 23), (18, 0, 0))),
+            ("2030-01-01 UTC", time((2030, 1, 1), (0, 0, 0))),
+            ("*-*-31", time((2024, 3, 31), (0, 0, 0))),
+            ("12:30:15", time((2024, 2, 15), (12, 30, 15))),
+            ("*-1..3-1..2 1,2:00", time((2024, 3, 1), (1, 0, 0))),
+            ("*:10/1", time((2024, 2, 14), (12, 31, 0))),
+            ("*-*~07/1", time((2024, 2, 15), (0, 0, 0))),
+        ];
+        for (event, expected) in &cases {
+            assert_eq!(next(event, now), Some(*expected), "{}", event);
+        }
+        assert_eq!(next("2020-01-01", now), None);
+        assert_eq!(next("*-02-30", now), None);
+    }
+
+    #[test]
+    fn strictly_after() {
+        let before_epoch = UNIX_EPOCH - Duration::from_secs(1);
+        assert_eq!(next("*:*:*", before_epoch), Some(UNIX_EPOCH));
+        let before_epoch = UNIX_EPOCH - Duration::from_millis(1500);
+        assert_eq!(
+            next("*:*:*", before_epoch),
+            Some(UNIX_EPOCH - Duration::from_secs(1))
+        );
+        let now = time((2024, 2, 14), (12, 30, 15));
+        assert_eq!(next("*:*:*", now), Some(now + Duration::from_secs(1)));
+        let last = UNIX_EPOCH
+            .checked_add(Duration::from_secs(i64::MAX as u64))
+            .unwrap();
+        assert_eq!(next("*:*:*", last), None);
+    }
+
+    #[test]
+    fn invalid() {
+        let invalid = [
+            "",
+            "Mo",
+            "Fri..Mon",
+            "*-13-01",
+            "*-*-* 24:00",
+            "*-*-* 10:00 Europe/Berlin",
+            "10:00 *-*-*",
+            "*:*/0",
+            "5..3:00",
+            "*-*-* 1:2:3:4",
+        ];
+        for event in &invalid {
+            assert!(CalendarEvent::parse(event).is_err(), "{}", event);
+        }
+    }
+}
@@ -0,0 +1,417 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::DesktopEntry;
+use std::{
+    convert::Infallible,
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+/// Declares `Category` with its main and additional categories.
+macro_rules! categories {
+    (
+        main { $($(#[$main_doc:meta])* $main:ident => $main_name:literal,)* }
+        additional { $($(#[$doc:meta])* $additional:ident => $name:literal,)* }
+    ) => {
+        /// A category from the [registered
+        /// categories](https://specifications.freedesktop.org/menu-spec/latest/category-registry.html)
+        /// of the menu spec, as used in `Categories`.
+        ///
+        /// Example:
+        /// ```
+        /// use freedesktop_entry_parser::{Category, Entry};
+        ///
+        /// let entry = Entry::parse(&b"[Desktop Entry]\nCategories=Network;WebBrowser;X-Foo;"[..])?;
+        /// let categories = entry.desktop_entry().typed_categories();
+        /// assert!(categories.contains(&Category::Network));
+        /// assert_eq!(categories[2], Category::Other("X-Foo".to_owned()));
+        /// # Ok::<(), freedesktop_entry_parser::ParseError>(())
+        /// ```
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub enum Category {
+            $($(#[$main_doc])* $main,)*
+            $($(#[$doc])* $additional,)*
+            /// Any category that isn't registered, like the `X-` ones
+            Other(String),
+        }
+
+        impl Category {
+            /// The category as it's written in `Categories`
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $(Category::$main => $main_name,)*
+                    $(Category::$additional => $name,)*
+                    Category::Other(name) => name,
+                }
+            }
+
+            /// Whether this is one of the main categories, which menus are
+            /// built from
+            pub fn is_main(&self) -> bool {
+                matches!(self, $(Category::$main)|*)
+            }
+        }
+
+        impl FromStr for Category {
+            type Err = Infallible;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Ok(match s {
+                    $($main_name => Category::$main,)*
+                    $($name => Category::$additional,)*
+                    name => Category::Other(name.to_owned()),
+                })
+            }
+        }
+    };
+}
+
+categories! {
+    main {
+        /// `AudioVideo`
+        AudioVideo => "AudioVideo",
+        /// `Audio`
+        Audio => "Audio",
+        /// `Video`
+        Video => "Video",
+        /// `Development`
+        Development => "Development",
+        /// `Education`
+        Education => "Education",
+        /// `Game`
+        Game => "Game",
+        /// `Graphics`
+        Graphics => "Graphics",
+        /// `Network`
+        Network => "Network",
+        /// `Office`
+        Office => "Office",
+        /// `Science`
+        Science => "Science",
+        /// `Settings`
+        Settings => "Settings",
+        /// `System`
+        System => "System",
+        /// `Utility`
+        Utility => "Utility",
+    }
+    additional {
+        /// `Building`
+        Building => "Building",
+        /// `Debugger`
+        Debugger => "Debugger",
+        /// `IDE`
+        IDE => "IDE",
+        /// `GUIDesigner`
+        GUIDesigner => "GUIDesigner",
+        /// `Profiling`
+        Profiling => "Profiling",
+        /// `RevisionControl`
+        RevisionControl => "RevisionControl",
+        /// `Translation`
+        Translation => "Translation",
+        /// `Calendar`
+        Calendar => "Calendar",
+        /// `ContactManagement`
+        ContactManagement => "ContactManagement",
+        /// `Database`
+        Database => "Database",
+        /// `Dictionary`
+        Dictionary => "Dictionary",
+        /// `Chart`
+        Chart => "Chart",
+        /// `Email`
+        Email => "Email",
+        /// `Finance`
+        Finance => "Finance",
+        /// `FlowChart`
+        FlowChart => "FlowChart",
+        /// `PDA`
+        PDA => "PDA",
+        /// `ProjectManagement`
+        ProjectManagement => "ProjectManagement",
+        /// `Presentation`
+        Presentation => "Presentation",
+        /// `Spreadsheet`
+        Spreadsheet => "Spreadsheet",
+        /// `WordProcessor`
+        WordProcessor => "WordProcessor",
+        /// `2DGraphics`
+        Graphics2D => "2DGraphics",
+        /// `VectorGraphics`
+        VectorGraphics => "VectorGraphics",
+        /// `RasterGraphics`
+        RasterGraphics => "RasterGraphics",
+        /// `3DGraphics`
+        Graphics3D => "3DGraphics",
+        /// `Scanning`
+        Scanning => "Scanning",
+        /// `OCR`
+        OCR => "OCR",
+        /// `Photography`
+        Photography => "Photography",
+        /// `Publishing`
+        Publishing => "Publishing",
+        /// `Viewer`
+        Viewer => "Viewer",
+        /// `TextTools`
+        TextTools => "TextTools",
+        /// `DesktopSettings`
+        DesktopSettings => "DesktopSettings",
+        /// `HardwareSettings`
+        HardwareSettings => "HardwareSettings",
+        /// `Printing`
+        Printing => "Printing",
+        /// `PackageManager`
+        PackageManager => "PackageManager",
+        /// `Dialup`
+        Dialup => "Dialup",
+        /// `InstantMessaging`
+        InstantMessaging => "InstantMessaging",
+        /// `Chat`
+        Chat => "Chat",
+        /// `IRCClient`
+        IRCClient => "IRCClient",
+        /// `Feed`
+        Feed => "Feed",
+        /// `FileTransfer`
+        FileTransfer => "FileTransfer",
+        /// `HamRadio`
+        HamRadio => "HamRadio",
+        /// `News`
+        News => "News",
+        /// `P2P`
+        P2P => "P2P",
+        /// `RemoteAccess`
+        RemoteAccess => "RemoteAccess",
+        /// `Telephony`
+        Telephony => "Telephony",
+        /// `TelephonyTools`
+        TelephonyTools => "TelephonyTools",
+        /// `VideoConference`
+        VideoConference => "VideoConference",
+        /// `WebBrowser`
+        WebBrowser => "WebBrowser",
+        /// `WebDevelopment`
+        WebDevelopment => "WebDevelopment",
+        /// `Midi`
+        Midi => "Midi",
+        /// `Mixer`
+        Mixer => "Mixer",
+        /// `Sequencer`
+        Sequencer => "Sequencer",
+        /// `Tuner`
+        Tuner => "Tuner",
+        /// `TV`
+        TV => "TV",
+        /// `AudioVideoEditing`
+        AudioVideoEditing => "AudioVideoEditing",
+        /// `Player`
+        Player => "Player",
+        /// `Recorder`
+        Recorder => "Recorder",
+        /// `DiscBurning`
+        DiscBurning => "DiscBurning",
+        /// `ActionGame`
+        ActionGame => "ActionGame",
+        /// `AdventureGame`
+        AdventureGame => "AdventureGame",
+        /// `ArcadeGame`
+        ArcadeGame => "ArcadeGame",
+        /// `BoardGame`
+        BoardGame => "BoardGame",
+        /// `BlocksGame`
+        BlocksGame => "BlocksGame",
+        /// `CardGame`
+        CardGame => "CardGame",
+        /// `KidsGame`
+        KidsGame => "KidsGame",
+        /// `LogicGame`
+        LogicGame => "LogicGame",
+        /// `RolePlaying`
+        RolePlaying => "RolePlaying",
+        /// `Shooter`
+        Shooter => "Shooter",
+        /// `Simulation`
+        Simulation => "Simulation",
+        /// `SportsGame`
+        SportsGame => "SportsGame",
+        /// `StrategyGame`
+        StrategyGame => "StrategyGame",
+        /// `Art`
+        Art => "Art",
+        /// `Construction`
+        Construction => "Construction",
+        /// `Music`
+        Music => "Music",
+        /// `Languages`
+        Languages => "Languages",
+        /// `ArtificialIntelligence`
+        ArtificialIntelligence => "ArtificialIntelligence",
+        /// `Astronomy`
+        Astronomy => "Astronomy",
+        /// `Biology`
+        Biology => "Biology",
+        /// `Chemistry`
+        Chemistry => "Chemistry",
+        /// `ComputerScience`
+        ComputerScience => "ComputerScience",
+        /// `DataVisualization`
+        DataVisualization => "DataVisualization",
+        /// `Economy`
+        Economy => "Economy",
+        /// `Electricity`
+        Electricity => "Electricity",
+        /// `Geography`
+        Geography => "Geography",
+        /// `Geology`
+        Geology => "Geology",
+        /// `Geoscience`
+        Geoscience => "Geoscience",
+        /// `History`
+        History => "History",
+        /// `Humanities`
+        Humanities => "Humanities",
+        /// `ImageProcessing`
+        ImageProcessing => "ImageProcessing",
+        /// `Literature`
+        Literature => "Literature",
+        /// `Maps`
+        Maps => "Maps",
+        /// `Math`
+        Math => "Math",
+        /// `NumericalAnalysis`
+        NumericalAnalysis => "NumericalAnalysis",
+        /// `MedicalSoftware`
+        MedicalSoftware => "MedicalSoftware",
+        /// `Physics`
+        Physics => "Physics",
+        /// `Robotics`
+        Robotics => "Robotics",
+        /// `Spirituality`
+        Spirituality => "Spirituality",
+        /// `Sports`
+        Sports => "Sports",
+        /// `ParallelComputing`
+        ParallelComputing => "ParallelComputing",
+        /// `Amusement`
+        Amusement => "Amusement",
+        /// `Archiving`
+        Archiving => "Archiving",
+        /// `Compression`
+        Compression => "Compression",
+        /// `Electronics`
+        Electronics => "Electronics",
+        /// `Emulator`
+        Emulator => "Emulator",
+        /// `Engineering`
+        Engineering => "Engineering",
+        /// `FileTools`
+        FileTools => "FileTools",
+        /// `FileManager`
+        FileManager => "FileManager",
+        /// `TerminalEmulator`
+        TerminalEmulator => "TerminalEmulator",
+        /// `Filesystem`
+        Filesystem => "Filesystem",
+        /// `Monitor`
+        Monitor => "Monitor",
+        /// `Security`
+        Security => "Security",
+        /// `Accessibility`
+        Accessibility => "Accessibility",
+        /// `Calculator`
+        Calculator => "Calculator",
+        /// `Clock`
+        Clock => "Clock",
+        /// `TextEditor`
+        TextEditor => "TextEditor",
+        /// `Documentation`
+        Documentation => "Documentation",
+        /// `Adult`
+        Adult => "Adult",
+        /// `Core`
+        Core => "Core",
+        /// `KDE`
+        KDE => "KDE",
+        /// `GNOME`
+        GNOME => "GNOME",
+        /// `XFCE`
+        XFCE => "XFCE",
+        /// `DDE`
+        DDE => "DDE",
+        /// `GTK`
+        GTK => "GTK",
+        /// `Qt`
+        Qt => "Qt",
+        /// `Motif`
+        Motif => "Motif",
+        /// `Java`
+        Java => "Java",
+        /// `ConsoleOnly`
+        ConsoleOnly => "ConsoleOnly",
+        /// `Screensaver`
+        Screensaver => "Screensaver",
+        /// `TrayIcon`
+        TrayIcon => "TrayIcon",
+        /// `Applet`
+        Applet => "Applet",
+        /// `Shell`
+        Shell => "Shell",
+    }
+}
+
+impl Display for Category {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl<'a> DesktopEntry<'a> {
+    /// `Categories` as [`Category`](enum.Category.html) values, empty if
+    /// not set
+    pub fn typed_categories(&self) -> Vec<Category> {
+        self.categories()
+            .map(|name| match name.parse() {
+                Ok(category) => category,
+                Err(e) => match e {},
+            })
+            .collect()
+    }
+
+    /// Whether `category` is in `Categories`
+    pub fn has_category(&self, category: &Category) -> bool {
+        self.categories().any(|name| name == category.as_str())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Entry;
+
+    #[test]
+    fn categories() {
+        let entry = Entry::parse(
+            &b"[Desktop Entry]\nCategories=Graphics;2DGraphics;X-Foo;"[..],
+        )
+        .unwrap();
+        let desktop_entry = entry.desktop_entry();
+        let categories = desktop_entry.typed_categories();
+        assert_eq!(
+            categories,
+            [
+                Category::Graphics,
+                Category::Graphics2D,
+                Category::Other("X-Foo".to_owned())
+            ]
+        );
+        assert!(categories[0].is_main());
+        assert!(!categories[1].is_main());
+        assert_eq!(categories[1].to_string(), "2DGraphics");
+        assert!(desktop_entry.has_category(&Category::Graphics2D));
+        assert!(!desktop_entry.has_category(&Category::Network));
+    }
+}
@@ -0,0 +1,922 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::systemd::SystemdUnit;
+use std::{
+    cmp::Ordering,
+    convert::Infallible,
+    env,
+    fmt::{self, Display, Formatter},
+    fs,
+    path::Path,
+    str::FromStr,
+};
+
+/// Container managers [`Probe::virtualization`](trait.Probe.html#method.virtualization)
+/// can return, all other values are virtual machines
+const CONTAINERS: &[&str] = &[
+    "openvz",
+    "lxc",
+    "lxc-libvirt",
+    "systemd-nspawn",
+    "docker",
+    "podman",
+    "rkt",
+    "wsl",
+    "proot",
+    "pouch",
+];
+
+/// A `Condition…` or `Assert…` key of a unit, which is checked before the
+/// unit is started.
+///
+/// Created by [`SystemdUnit::conditions`](struct.SystemdUnit.html#method.conditions)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Condition {
+    /// What's checked
+    pub kind: ConditionKind,
+    /// The value, without the `|` and `!` prefixes
+    pub value: String,
+    /// Whether this is an `Assert…` key, which fails the start of the unit
+    /// instead of skipping it
+    pub assert: bool,
+    /// Whether the value starts with `|`.  The unit starts if any of the
+    /// triggering conditions holds, instead of all of them.
+    pub trigger: bool,
+    /// Whether the value starts with `!`, which negates the check
+    pub negate: bool,
+}
+
+/// What a [`Condition`](struct.Condition.html) checks, its key without the
+/// `Condition` or `Assert` prefix.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ConditionKind {
+    /// The CPU architecture
+    Architecture,
+    /// The firmware, like `uefi` or a device tree
+    Firmware,
+    /// Whether, or in which virtualization, the system runs
+    Virtualization,
+    /// The host name or machine ID
+    Host,
+    /// An option on the kernel command line
+    KernelCommandLine,
+    /// The kernel release, compared to a version or pattern
+    KernelVersion,
+    /// Whether a credential was passed to the service manager
+    Credential,
+    /// An environment variable of the service manager
+    Environment,
+    /// A security technology, like `selinux`
+    Security,
+    /// A capability in the bounding set of the service manager
+    Capability,
+    /// Whether the system is on AC power
+    ACPower,
+    /// Whether a directory needs an update after `/usr` changed
+    NeedsUpdate,
+    /// Whether the system is booting for the first time
+    FirstBoot,
+    /// Whether a path exists
+    PathExists,
+    /// Whether a path matching a glob exists
+    PathExistsGlob,
+    /// Whether a path is a directory
+    PathIsDirectory,
+    /// Whether a path is a symlink
+    PathIsSymbolicLink,
+    /// Whether a path is a mount point
+    PathIsMountPoint,
+    /// Whether a path is on a file system mounted read-write
+    PathIsReadWrite,
+    /// Whether a path is on an encrypted block device
+    PathIsEncrypted,
+    /// Whether a path is a non-empty directory
+    DirectoryNotEmpty,
+    /// Whether a path is a non-empty file
+    FileNotEmpty,
+    /// Whether a path is an executable file
+    FileIsExecutable,
+    /// The user the service manager runs as
+    User,
+    /// A group of the service manager
+    Group,
+    /// Whether a cgroup controller is available
+    ControlGroupController,
+    /// The amount of physical memory
+    Memory,
+    /// The number of CPUs
+    CPUs,
+    /// A CPU feature, like `sse4_2`
+    CPUFeature,
+    /// A key in `os-release`
+    OSRelease,
+    /// The memory pressure
+    MemoryPressure,
+    /// The CPU pressure
+    CPUPressure,
+    /// The IO pressure
+    IOPressure,
+    /// Any other key
+    Unknown(String),
+}
+
+/// What a [`Probe`](trait.Probe.html) found out about a path.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PathInfo {
+    /// Whether it's a directory, following symlinks
+    pub is_dir: bool,
+    /// Whether it's a symlink itself
+    pub is_symlink: bool,
+    /// Whether a file system is mounted on it
+    pub is_mount_point: bool,
+    /// Whether the file system it's on is mounted read only
+    pub is_read_only: bool,
+    /// Whether it's a file that can be executed
+    pub is_executable: bool,
+    /// Whether it's an empty file or directory
+    pub is_empty: bool,
+}
+
+/// The system conditions are evaluated against.
+///
+/// Every method has a default that looks at the current system, so an
+/// implementation only has to override the ones it wants to fake, like
+/// for evaluating the conditions for another machine.
+pub trait Probe {
+    /// Information about `path`, `None` if it doesn't exist
+    fn path_info(&self, path: &Path) -> Option<PathInfo> {
+        let is_symlink =
+            fs::symlink_metadata(path).ok()?.file_type().is_symlink();
+        let meta = fs::metadata(path).ok();
+        let is_dir = meta.as_ref().map(|meta| meta.is_dir()) == Some(true);
+        let is_empty = if is_dir {
+            fs::read_dir(path).map(|mut dir| dir.next().is_none()).ok()
+                == Some(true)
+        } else {
+            meta.as_ref().map(|meta| meta.len()) == Some(0)
+        };
+        #[cfg(unix)]
+        let is_executable = {
+            use std::os::unix::fs::PermissionsExt;
+            match &meta {
+                Some(meta) => !is_dir && meta.permissions().mode() & 0o111 != 0,
+                None => false,
+            }
+        };
+        #[cfg(not(unix))]
+        let is_executable = false;
+        let mounts =
+            fs::read_to_string("/proc/self/mountinfo").unwrap_or_default();
+        let path = fs::canonicalize(path).unwrap_or_else(|_| path.into());
+        let mut is_mount_point = false;
+        let mut mount = (0, false);
+        for line in mounts.lines() {
+            let fields: Vec<&str> = line.split(' ').collect();
+            let (point, options) = match (fields.get(4), fields.get(5)) {
+                (Some(point), Some(options)) => {
+                    (unescape_mount(point), options)
+                }
+                _ => continue,
+            };
+            if path == Path::new(&point) {
+                is_mount_point = true;
+            }
+            let len = point.len();
+            if path.starts_with(&point) && len >= mount.0 {
+                mount = (len, options.split(',').any(|option| option == "ro"));
+            }
+        }
+        Some(PathInfo {
+            is_dir,
+            is_symlink,
+            is_mount_point,
+            is_read_only: mount.1,
+            is_executable,
+            is_empty,
+        })
+    }
+
+    /// The virtualization the system runs in, like `kvm` or `docker`, with
+    /// the names systemd uses.  `None` on bare metal.
+    fn virtualization(&self) -> Option<String> {
+        let container = fs::read_to_string("/run/systemd/container")
+            .ok()
+            .or_else(|| env::var("container").ok())
+            .map(|container| container.trim().to_owned())
+            .filter(|container| !container.is_empty());
+        if container.is_some() {
+            return container;
+        }
+        let vendor = fs::read_to_string("/sys/class/dmi/id/sys_vendor").ok()?;
+        let vm = match vendor.trim() {
+            "QEMU" => "qemu",
+            "KVM" => "kvm",
+            "VMware, Inc." => "vmware",
+            "Microsoft Corporation" => "microsoft",
+            "innotek GmbH" | "Oracle Corporation" => "oracle",
+            "Xen" => "xen",
+            "Parallels Software International Inc." => "parallels",
+            "Amazon EC2" => "amazon",
+            _ => return None,
+        };
+        Some(vm.to_owned())
+    }
+
+    /// The architecture, like `x86-64` or `arm64`, with the names systemd
+    /// uses
+    fn architecture(&self) -> String {
+        match env::consts::ARCH {
+            "x86_64" => "x86-64",
+            "aarch64" => "arm64",
+            "powerpc64" if cfg!(target_endian = "little") => "ppc64-le",
+            "powerpc64" => "ppc64",
+            "powerpc" => "ppc",
+            "loongarch64" => "loongarch64",
+            arch => arch,
+        }
+        .to_owned()
+    }
+
+    /// The host name
+    fn host_name(&self) -> Option<String> {
+        read_trimmed("/proc/sys/kernel/hostname")
+            .or_else(|| read_trimmed("/etc/hostname"))
+    }
+
+    /// The machine ID
+    fn machine_id(&self) -> Option<String> {
+        read_trimmed("/etc/machine-id")
+    }
+
+    /// The kernel command line
+    fn kernel_command_line(&self) -> String {
+        read_trimmed("/proc/cmdline").unwrap_or_default()
+    }
+
+    /// The kernel release, like `6.1.0-13-amd64`
+    fn kernel_release(&self) -> Option<String> {
+        read_trimmed("/proc/sys/kernel/osrelease")
+    }
+
+    /// The value of the environment variable `name` of the service manager
+    fn env_var(&self, name: &str) -> Option<String> {
+        env::var(name).ok()
+    }
+
+    /// Whether the system is booting for the first time, so it doesn't
+    /// have a machine ID yet
+    fn first_boot(&self) -> bool {
+        match self.machine_id() {
+            Some(id) => id == "uninitialized",
+            None => true,
+        }
+    }
+
+    /// Whether the system is on AC power.  Also `true` if it doesn't know.
+    fn ac_power(&self) -> bool {
+        let supplies = match fs::read_dir("/sys/class/power_supply") {
+            Ok(supplies) => supplies,
+            Err(_) => return true,
+        };
+        let mut found = false;
+        for supply in supplies.flatten() {
+            let path = supply.path();
+            if read_trimmed(path.join("type")).as_deref() != Some("Mains") {
+                continue;
+            }
+            if read_trimmed(path.join("online")).as_deref() == Some("1") {
+                return true;
+            }
+            found = true;
+        }
+        !found
+    }
+}
+
+/// The [`Probe`](trait.Probe.html) of the current system.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SystemProbe;
+
+impl Probe for SystemProbe {}
+
+impl Condition {
+    /// Parse the key `key` with `value`.  Returns `None` if the key isn't a
+    /// condition or assertion, or the value is empty.
+    pub fn parse(key: &str, value: &str) -> Option<Self> {
+        let (kind, assert) = match key.strip_prefix("Condition") {
+            Some(kind) => (kind, false),
+            None => (key.strip_prefix("Assert")?, true),
+        };
+        let value = value.trim();
+        let (trigger, value) = match value.strip_prefix('|') {
+            Some(value) => (true, value.trim_start()),
+            None => (false, value),
+        };
+        let (negate, value) = match value.strip_prefix('!') {
+            Some(value) => (true, value.trim_start()),
+            None => (false, value),
+        };
+        if kind.is_empty() || value.is_empty() {
+            return None;
+        }
+        Some(Condition {
+            kind: match kind.parse() {
+                Ok(kind) => kind,
+                Err(e) => match e {},
+            },
+            value: value.to_owned(),
+            assert,
+            trigger,
+            negate,
+        })
+    }
+
+    /// Check the condition on the system of `probe`, including the
+    /// negation.  Returns `None` for kinds that can't be checked.  The ones
+    /// that can are `PathExists`, `PathIsDirectory`, `PathIsSymbolicLink`,
+    /// `PathIsMountPoint`, `PathIsReadWrite`, `DirectoryNotEmpty`,
+    /// `FileNotEmpty`, `FileIsExecutable`, `Virtualization`, `Architecture`,
+    /// `Host`, `KernelCommandLine`, `KernelVersion`, `Environment`,
+    /// `FirstBoot` and `ACPower`.  `PathExistsGlob` and `PathIsEncrypted`
+    /// aren't supported.
+    pub fn evaluate(&self, probe: &dyn Probe) -> Option<bool> {
+        let value = self.value.as_str();
+        let path = || probe.path_info(Path::new(value));
+        let path_is = |check: fn(PathInfo) -> bool| {
+            Some(match path() {
+                Some(info) => check(info),
+                None => false,
+            })
+        };
+        let result = match self.kind {
+            ConditionKind::PathExists => Some(path().is_some()),
+            ConditionKind::PathIsDirectory => path_is(|info| info.is_dir),
+            ConditionKind::PathIsSymbolicLink => {
+                path_is(|info| info.is_symlink)
+            }
+            ConditionKind::PathIsMountPoint => {
+                path_is(|info| info.is_mount_point)
+            }
+            ConditionKind::PathIsReadWrite => {
+                path_is(|info| !info.is_read_only)
+            }
+            ConditionKind::DirectoryNotEmpty => {
+                path_is(|info| info.is_dir && !info.is_empty)
+            }
+            ConditionKind::FileNotEmpty => {
+                path_is(|info| !info.is_dir && !info.is_empty)
+            }
+            ConditionKind::FileIsExecutable => {
+                path_is(|info| info.is_executable)
+            }
+            ConditionKind::Virtualization => {
+                let virtualization = probe.virtualization();
+                let is_container =
+                    |v: &String| CONTAINERS.contains(&v.as_str());
+                Some(match value {
+                    "vm" => {
+                        virtualization.filter(|v| !is_container(v)).is_some()
+                    }
+                    "container" => {
+                        virtualization.filter(is_container).is_some()
+                    }
+                    value => match parse_bool(value) {
+                        Some(virtualized) => {
+                            virtualization.is_some() == virtualized
+                        }
+                        None => virtualization.as_deref() == Some(value),
+                    },
+                })
+            }
+            ConditionKind::Architecture => {
+                Some(value == "native" || probe.architecture() == value)
+            }
+            ConditionKind::Host => Some(
+                probe.host_name().as_deref() == Some(value)
+                    || probe
+                        .machine_id()
+                        .map(|id| id.eq_ignore_ascii_case(value))
+                        == Some(true),
+            ),
+            ConditionKind::KernelCommandLine => {
+                let command_line = probe.kernel_command_line();
+                Some(command_line.split_whitespace().any(|word| {
+                    word == value
+                        || !value.contains('=')
+                            && word.split('=').next() == Some(value)
+                }))
+            }
+            ConditionKind::KernelVersion => {
+                let release = probe.kernel_release()?;
+                Some(
+                    value
+                        .split_whitespace()
+                        .all(|check| check_version(&release, check)),
+                )
+            }
+            ConditionKind::Environment => Some(match value.find('=') {
+                Some(eq) => {
+                    probe.env_var(&value[..eq]).as_deref()
+                        == Some(&value[eq + 1..])
+                }
+                None => probe.env_var(value).is_some(),
+            }),
+            ConditionKind::FirstBoot => {
+                Some(probe.first_boot() == parse_bool(value)?)
+            }
+            ConditionKind::ACPower => {
+                Some(probe.ac_power() == parse_bool(value)?)
+            }
+            _ => None,
+        }?;
+        Some(result != self.negate)
+    }
+}
+
+/// Check `conditions` on the system of `probe`, the way systemd does
+/// before starting a unit: all of the conditions without
+/// [`trigger`](struct.Condition.html#structfield.trigger) have to hold, and
+/// at least one with it if there are any.  Conditions and assertions are
+/// checked separately, and both have to pass.
+///
+/// Returns `None` if the result depends on a condition that can't be
+/// checked.
+pub fn evaluate_conditions(
+    conditions: &[Condition],
+    probe: &dyn Probe,
+) -> Option<bool> {
+    let check = |assert: bool| {
+        let mut unknown = false;
+        let mut triggers = None;
+        for condition in conditions.iter().filter(|c| c.assert == assert) {
+            let result = condition.evaluate(probe);
+            if condition.trigger {
+                triggers = match (triggers, result) {
+                    (Some(Some(true)), _) | (_, Some(true)) => Some(Some(true)),
+                    (Some(None), _) | (_, None) => Some(None),
+                    _ => Some(Some(false)),
+                };
+            } else {
+                match result {
+                    Some(false) => return Some(false),
+                    None => unknown = true,
+                    Some(true) => {}
+                }
+            }
+        }
+        match (unknown, triggers) {
+            (_, Some(Some(false))) => Some(false),
+            (true, _) | (_, Some(None)) => None,
+            _ => Some(true),
+        }
+    };
+    match (check(false), check(true)) {
+        (Some(false), _) | (_, Some(false)) => Some(false),
+        (Some(true), Some(true)) => Some(true),
+        _ => None,
+    }
+}
+
+impl<'a> SystemdUnit<'a> {
+    /// The `Condition…` and `Assert…` keys of the `[Unit]` section, in the
+    /// order they're assigned in.  An empty `Condition…` value resets all
+    /// conditions before it, and an empty `Assert…` value all assertions.
+    pub fn conditions(&self) -> Vec<Condition> {
+        let mut conditions: Vec<Condition> = Vec::new();
+        let sections = self.entry.sections_named("Unit");
+        for attrs in sections.filter_map(|section| section.section) {
+            for (key, value) in attrs.values_in_order() {
+                let assert = if key.starts_with("Condition") {
+                    false
+                } else if key.starts_with("Assert") {
+                    true
+                } else {
+                    continue;
+                };
+                if value.trim().is_empty() {
+                    conditions.retain(|condition| condition.assert != assert);
+                } else if let Some(condition) = Condition::parse(key, value) {
+                    conditions.push(condition);
+                }
+            }
+        }
+        conditions
+    }
+
+    /// Whether the unit's conditions hold on the system of `probe`, see
+    /// [`evaluate_conditions`](fn.evaluate_conditions.html).
+    pub fn conditions_met(&self, probe: &dyn Probe) -> Option<bool> {
+        evaluate_conditions(&self.conditions(), probe)
+    }
+}
+
+impl ConditionKind {
+    /// The kind as it's written after `Condition` or `Assert`
+    pub fn as_str(&self) -> &str {
+        match self {
+            ConditionKind::Architecture => "Architecture",
+            ConditionKind::Firmware => "Firmware",
+            ConditionKind::Virtualization => "Virtualization",
+            ConditionKind::Host => "Host",
+            ConditionKind::KernelCommandLine => "KernelCommandLine",
+            ConditionKind::KernelVersion => "KernelVersion",
+            ConditionKind::Credential => "Credential",
+            ConditionKind::Environment => "Environment",
+            ConditionKind::Security => "Security",
+            ConditionKind::Capability => "Capability",
+            ConditionKind::ACPower => "ACPower",
+            ConditionKind::NeedsUpdate => "NeedsUpdate",
+            ConditionKind::FirstBoot => "FirstBoot",
+            ConditionKind::PathExists => "PathExists",
+            ConditionKind::PathExistsGlob => "PathExistsGlob",
+            ConditionKind::PathIsDirectory => "PathIsDirectory",
+            ConditionKind::PathIsSymbolicLink => "PathIsSymbolicLink",
+            ConditionKind::PathIsMountPoint => "PathIsMountPoint",
+            ConditionKind::PathIsReadWrite => "PathIsReadWrite",
+            ConditionKind::PathIsEncrypted => "PathIsEncrypted",
+            ConditionKind::DirectoryNotEmpty => "DirectoryNotEmpty",
+            ConditionKind::FileNotEmpty => "FileNotEmpty",
+            ConditionKind::FileIsExecutable => "FileIsExecutable",
+            ConditionKind::User => "User",
+            ConditionKind::Group => "Group",
+            ConditionKind::ControlGroupController => "ControlGroupController",
+            ConditionKind::Memory => "Memory",
+            ConditionKind::CPUs => "CPUs",
+            ConditionKind::CPUFeature => "CPUFeature",
+            ConditionKind::OSRelease => "OSRelease",
+            ConditionKind::MemoryPressure => "MemoryPressure",
+            ConditionKind::CPUPressure => "CPUPressure",
+            ConditionKind::IOPressure => "IOPressure",
+            ConditionKind::Unknown(kind) => kind,
+        }
+    }
+}
+
+impl FromStr for ConditionKind {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "Architecture" => ConditionKind::Architecture,
+            "Firmware" => ConditionKind::Firmware,
+            "Virtualization" => ConditionKind::Virtualization,
+            "Host" => ConditionKind::Host,
+            "KernelCommandLine" => ConditionKind::KernelCommandLine,
+            "KernelVersion" => ConditionKind::KernelVersion,
+            "Credential" => ConditionKind::Credential,
+            "Environment" => ConditionKind::Environment,
+            "Security" => ConditionKind::Security,
+            "Capability" => ConditionKind::Capability,
+            "ACPower" => ConditionKind::ACPower,
+            "NeedsUpdate" => ConditionKind::NeedsUpdate,
+            "FirstBoot" => ConditionKind::FirstBoot,
+            "PathExists" => ConditionKind::PathExists,
+            "PathExistsGlob" => ConditionKind::PathExistsGlob,
+            "PathIsDirectory" => ConditionKind::PathIsDirectory,
+            "PathIsSymbolicLink" => ConditionKind::PathIsSymbolicLink,
+            "PathIsMountPoint" => ConditionKind::PathIsMountPoint,
+            "PathIsReadWrite" => ConditionKind::PathIsReadWrite,
+            "PathIsEncrypted" => ConditionKind::PathIsEncrypted,
+            "DirectoryNotEmpty" => ConditionKind::DirectoryNotEmpty,
+            "FileNotEmpty" => ConditionKind::FileNotEmpty,
+            "FileIsExecutable" => ConditionKind::FileIsExecutable,
+            "User" => ConditionKind::User,
+            "Group" => ConditionKind::Group,
+            "ControlGroupController" => ConditionKind::ControlGroupController,
+            "Memory" => ConditionKind::Memory,
+            "CPUs" => ConditionKind::CPUs,
+            "CPUFeature" => ConditionKind::CPUFeature,
+            "OSRelease" => ConditionKind::OSRelease,
+            "MemoryPressure" => ConditionKind::MemoryPressure,
+            "CPUPressure" => ConditionKind::CPUPressure,
+            "IOPressure" => ConditionKind::IOPressure,
+            kind => ConditionKind::Unknown(kind.to_owned()),
+        })
+    }
+}
+
+impl Display for ConditionKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Booleans the way systemd writes them
+pub(crate) fn parse_bool(value: &str) -> Option<bool> {
+    match value {
+        "1" | "yes" | "y" | "true" | "t" | "on" => Some(true),
+        "0" | "no" | "n" | "false" | "f" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+fn read_trimmed(path: impl AsRef<Path>) -> Option<String> {
+    fs::read_to_string(path)
+        .ok()
+        .map(|contents| contents.trim().to_owned())
+        .filter(|contents| !contents.is_empty())
+}
+
+/// Decode the octal escapes of spaces and other special characters in the
+/// paths of `/proc/self/mountinfo`
+fn unescape_mount(path: &str) -> String {
+    let mut unescaped = String::with_capacity(path.len());
+    let mut rest = path;
+    while let Some(backslash) = rest.find('\\') {
+        unescaped.push_str(&rest[..backslash]);
+        let code = rest.get(backslash + 1..backslash + 4);
+        match code.and_then(|code| u8::from_str_radix(code, 8).ok()) {
+            Some(byte) => {
+                unescaped.push(byte as char);
+                rest = &rest[backslash + 4..];
+            }
+            None => {
+                unescaped.push('\\');
+                rest = &rest[backslash + 1..];
+            }
+        }
+    }
+    unescaped.push_str(rest);
+    unescaped
+}
+
+/// Check `release` against a `KernelVersion` check like `>=5.10`, or a
+/// pattern with `*` and `?` without an operator.
+fn check_version(release: &str, check: &str) -> bool {
+    let operator = ["<=", ">=", "!=", "<", ">", "="]
+        .iter()
+        .find_map(|operator| Some((*operator, check.strip_prefix(operator)?)));
+    let (operator, version) = match operator {
+        Some(operator) => operator,
+        None => return glob_match(check, release),
+    };
+    let ordering = compare_versions(release, version.trim_start());
+    match operator {
+        "<=" => ordering != Ordering::Greater,
+        ">=" => ordering != Ordering::Less,
+        "!=" => ordering != Ordering::Equal,
+        "<" => ordering == Ordering::Less,
+        ">" => ordering == Ordering::Greater,
+        _ => ordering == Ordering::Equal,
+    }
+}
+
+/// Compare versions by their numeric and other parts, like
+/// `rpmvercmp` and systemd do in simplified form
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    let parts = |s: &str| -> Vec<String> {
+        let mut parts = Vec::new();
+        let mut part = String::new();
+        for c in s.chars() {
+            let same = part.chars().next().map(|p| p.is_ascii_digit())
+                == Some(c.is_ascii_digit());
+            if !c.is_ascii_alphanumeric() || !part.is_empty() && !same {
+                parts.extend(
+                    Some(std::mem::take(&mut part)).filter(|p| !p.is_empty()),
+                );
+            }
+            if c.is_ascii_alphanumeric() {
+                part.push(c);
+            }
+        }
+        parts.extend(Some(part).filter(|p| !p.is_empty()));
+        parts
+    };
+    let (a, b) = (parts(a), parts(b));
+    for (a, b) in a.iter().zip(&b) {
+        let ordering = match (a.parse::<u64>(), b.parse::<u64>()) {
+            (Ok(a), Ok(b)) => a.cmp(&b),
+            (Ok(_), Err(_)) => Ordering::Greater,
+            (Err(_), Ok(_)) => Ordering::Less,
+            (Err(_), Err(_)) => a.cmp(b),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    a.len().cmp(&b.len())
+}
+
+/// Match `s` against a pattern with `*` and `?`
+fn glob_match(pattern: &str, s: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let s: Vec<char> = s.chars().collect();
+    let (mut p, mut i) = (0, 0);
+    let mut star = None;
+    while i < s.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == s[i]) {
+            p += 1;
+            i += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, i));
+            p += 1;
+        } else if let Some((star_p, star_i)) = star {
+            p = star_p + 1;
+            i = star_i + 1;
+            star = Some((star_p, star_i + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Entry;
+
+    struct FakeProbe;
+
+    impl Probe for FakeProbe {
+        fn path_info(&self, path: &Path) -> Option<PathInfo> {
+            match path.to_str()? {
+                "/etc" => Some(PathInfo {
+                    is_dir: true,
+                    ..PathInfo::default()
+                }),
+                "/usr" => Some(PathInfo {
+                    is_dir: true,
+                    is_mount_point: true,
+                    is_read_only: true,
+                    ..PathInfo::default()
+                }),
+                "/etc/empty" => Some(PathInfo {
+                    is_empty: true,
+                    ..PathInfo::default()
+                }),
+                _ => None,
+            }
+        }
+
+        fn virtualization(&self) -> Option<String> {
+            Some("docker".to_owned())
+        }
+
+        fn architecture(&self) -> String {
+            "arm64".to_owned()
+        }
+
+        fn kernel_command_line(&self) -> String {
+            "root=/dev/sda1 quiet splash=silent".to_owned()
+        }
+
+        fn kernel_release(&self) -> Option<String> {
+            Some("6.1.0-13-amd64".to_owned())
+        }
+
+        fn env_var(&self, name: &str) -> Option<String> {
+            Some("1".to_owned()).filter(|_| name == "DEBUG")
+        }
+
+        fn first_boot(&self) -> bool {
+            false
+        }
+    }
+
+    fn check(key: &str, value: &str) -> Option<bool> {
+        Condition::parse(key, value).unwrap().evaluate(&FakeProbe)
+    }
+
+    #[test]
+    fn parse() {
+        let condition = Condition::parse("AssertPathExists", "|!/etc").unwrap();
+        assert_eq!(condition.kind, ConditionKind::PathExists);
+        assert_eq!(condition.value, "/etc");
+        assert!(condition.assert && condition.trigger && condition.negate);
+        assert_eq!(
+            Condition::parse("ConditionFoo", "x").unwrap().kind,
+            ConditionKind::Unknown("Foo".to_owned())
+        );
+        assert_eq!(Condition::parse("ConditionPathExists", ""), None);
+        assert_eq!(Condition::parse("After", "x"), None);
+        assert_eq!(ConditionKind::ACPower.to_string(), "ACPower");
+    }
+
+    #[test]
+    fn evaluate() {
+        let cases = [
+            ("ConditionPathExists", "/etc", Some(true)),
+            ("ConditionPathExists", "!/etc", Some(false)),
+            ("ConditionPathExists", "/nope", Some(false)),
+            ("ConditionPathIsDirectory", "/etc/empty", Some(false)),
+            ("ConditionPathIsMountPoint", "/usr", Some(true)),
+            ("ConditionPathIsReadWrite", "/usr", Some(false)),
+            ("ConditionFileNotEmpty", "/etc/empty", Some(false)),
+            ("ConditionVirtualization", "container", Some(true)),
+            ("ConditionVirtualization", "vm", Some(false)),
+            ("ConditionVirtualization", "yes", Some(true)),
+            ("ConditionVirtualization", "!docker", Some(false)),
+            ("ConditionArchitecture", "arm64", Some(true)),
+            ("ConditionArchitecture", "native", Some(true)),
+            ("ConditionKernelCommandLine", "quiet", Some(true)),
+            ("ConditionKernelCommandLine", "splash", Some(true)),
+            ("ConditionKernelCommandLine", "splash=auto", Some(false)),
+            ("ConditionKernelVersion", ">=5.10", Some(true)),
+            ("ConditionKernelVersion", ">=6.1 <6.2", Some(true)),
+            ("ConditionKernelVersion", "<6.1", Some(false)),
+            ("ConditionKernelVersion", "6.1.*", Some(true)),
+            ("ConditionEnvironment", "DEBUG=1", Some(true)),
+            ("ConditionEnvironment", "HOME", Some(false)),
+            ("ConditionFirstBoot", "no", Some(true)),
+            ("ConditionUser", "root", None),
+            ("ConditionPathExistsGlob", "/etc/*", None),
+            ("ConditionPathIsEncrypted", "/etc", None),
+        ];
+        for (key, value, expected) in &cases {
+            assert_eq!(check(key, value), *expected, "{}={}", key, value);
+        }
+    }
+
+    #[test]
+    fn unit() {
+        let unit = |conditions: &str| {
+            Entry::parse(format!("[Unit]\n{}", conditions).into_bytes())
+                .unwrap()
+                .systemd_unit()
+                .conditions_met(&FakeProbe)
+        };
+        assert_eq!(unit(""), Some(true));
+        assert_eq!(
+            unit("ConditionPathExists=/etc\nAssertPathExists=/usr"),
+            Some(true)
+        );
+        assert_eq!(unit("ConditionPathExists=/nope"), Some(false));
+        assert_eq!(
+            unit("ConditionPathExists=|/nope\nConditionPathExists=|/etc"),
+            Some(true)
+        );
+        assert_eq!(
+            unit("ConditionPathExists=|/nope\nConditionUser=|root"),
+            None
+        );
+        assert_eq!(
+            unit("ConditionUser=root\nConditionPathExists=/nope"),
+            Some(false)
+        );
+        assert_eq!(unit("ConditionUser=root\nConditionPathExists=/etc"), None);
+        assert_eq!(
+            unit("ConditionPathExists=/nope\nConditionPathExists=\nAssertArchitecture=arm64"),
+            Some(true)
+        );
+        assert_eq!(unit("AssertArchitecture=x86-64"), Some(false));
+    }
+
+    #[test]
+    fn reset() {
+        let conditions = |unit: &str| {
+            Entry::parse(unit.as_bytes())
+                .unwrap()
+                .systemd_unit()
+                .conditions()
+                .into_iter()
+                .map(|condition| {
+                    let prefix = if condition.assert {
+                        "Assert"
+                    } else {
+                        "Condition"
+                    };
+                    format!("{}{}={}", prefix, condition.kind, condition.value)
+                })
+                .collect::<Vec<_>>()
+        };
+        assert!(conditions(
+            "[Unit]\nConditionPathExists=/nonexistent\n\
+             ConditionPathIsDirectory="
+        )
+        .is_empty());
+        assert_eq!(
+            conditions(
+                "[Unit]\nConditionPathExists=/a\nAssertPathExists=/b\n\
+                 ConditionHost=\nConditionPathExists=/c\nAssertHost=foo"
+            ),
+            [
+                "AssertPathExists=/b",
+                "ConditionPathExists=/c",
+                "AssertHost=foo"
+            ]
+        );
+        assert_eq!(
+            conditions(
+                "[Unit]\nAssertPathExists=/a\nConditionHost=foo\n\
+                 [Unit]\nAssertArchitecture=\nAssertPathExists=/b"
+            ),
+            ["ConditionHost=foo", "AssertPathExists=/b"]
+        );
+    }
+
+    #[test]
+    fn versions() {
+        assert_eq!(
+            compare_versions("6.1.0-13-amd64", "6.1"),
+            Ordering::Greater
+        );
+        assert_eq!(compare_versions("5.9", "5.10"), Ordering::Less);
+        assert_eq!(compare_versions("1.0", "1.0"), Ordering::Equal);
+        assert!(glob_match("6.*-amd64", "6.1.0-13-amd64"));
+        assert!(!glob_match("6.?", "6.10"));
+        assert_eq!(unescape_mount(r"/mnt/my\040disk"), "/mnt/my disk");
+    }
+}
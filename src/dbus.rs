@@ -0,0 +1,58 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Launching entries with `DBusActivatable=true` through the
+//! `org.freedesktop.Application` interface, as the spec prescribes for them.
+use crate::{dbus_bus_name, dbus_object_path, DesktopEntry};
+use std::collections::HashMap;
+use zbus::{blocking::Connection, zvariant::Value};
+
+impl<'a> DesktopEntry<'a> {
+    /// Launch this entry over D-Bus instead of running `Exec`, opening
+    /// `uris` if there are any.  Only use this if
+    /// [`dbus_activatable`](#method.dbus_activatable) is `true`.
+    ///
+    /// `file_id` is the desktop file ID of the entry, like
+    /// `org.gnome.Nautilus.desktop`, which the bus name of the application
+    /// is derived from, see [`dbus_bus_name`](fn.dbus_bus_name.html).
+    ///
+    /// Requires the `zbus` feature.
+    pub fn dbus_launch(
+        &self,
+        file_id: &str,
+        uris: &[&str],
+    ) -> zbus::Result<()> {
+        let (name, path) =
+            match (dbus_bus_name(file_id), dbus_object_path(file_id)) {
+                (Some(name), Some(path)) => (name, path),
+                _ => {
+                    return Err(zbus::Error::Failure(format!(
+                        "invalid D-Bus desktop file ID: {}",
+                        file_id
+                    )))
+                }
+            };
+        let platform_data: HashMap<&str, Value<'_>> = HashMap::new();
+        let connection = Connection::session()?;
+        let interface = Some("org.freedesktop.Application");
+        if uris.is_empty() {
+            connection.call_method(
+                Some(name),
+                path.as_str(),
+                interface,
+                "Activate",
+                &(platform_data,),
+            )?;
+        } else {
+            connection.call_method(
+                Some(name),
+                path.as_str(),
+                interface,
+                "Open",
+                &(uris, platform_data),
+            )?;
+        }
+        Ok(())
+    }
+}
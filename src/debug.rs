@@ -2,7 +2,9 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::internal::{AttrMap, AttrValue, ParamMap, Section};
 use crate::parser::{AttrBytes, ParamBytes, SectionBytes};
+use crate::{AttrSelector, Entry, EntryRef};
 use std::fmt::{Debug, Formatter, Result};
 use std::str::from_utf8;
 
@@ -53,3 +55,68 @@ impl<'a> Debug for ParamBytes<'a> {
             .finish()
     }
 }
+
+impl Debug for Entry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        f.debug_struct("Entry")
+            .field("sections", &self.0 .0.sections().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl<'a> Debug for EntryRef<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        f.debug_struct("EntryRef")
+            .field("sections", &self.0.sections().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl<'a> Debug for Section<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        f.debug_struct("Section")
+            .field("name", &self.name())
+            .field("attrs", self.attrs())
+            .finish()
+    }
+}
+
+impl<'a, T: AsRef<str>> Debug for AttrSelector<'a, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        f.debug_struct("AttrSelector")
+            .field("name", &self.name())
+            .field("attrs", &self.section)
+            .finish()
+    }
+}
+
+impl<'a> Debug for AttrMap<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        f.debug_map()
+            .entries(
+                self.attr_names_iter()
+                    .filter_map(|name| Some((name, self.get_attr(name)?))),
+            )
+            .finish()
+    }
+}
+
+impl<'a> Debug for AttrValue<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        f.debug_struct("Attr")
+            .field("values", &self.get_values())
+            .field("params", &self.get_params())
+            .finish()
+    }
+}
+
+impl<'a> Debug for ParamMap<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        f.debug_map()
+            .entries(
+                self.param_names_iter()
+                    .filter_map(|name| Some((name, self.get_param(name)?))),
+            )
+            .finish()
+    }
+}
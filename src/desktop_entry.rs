@@ -0,0 +1,952 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::{
+    errors::InvalidVersion, AttrSelector, EntryError, EntryRef, ListIter,
+    Locale,
+};
+use std::{
+    borrow::Cow,
+    convert::Infallible,
+    env,
+    fmt::{self, Display, Formatter},
+    ops::Deref,
+    path::Path,
+    str::FromStr,
+};
+
+/// A typed view of the `Desktop Entry` section of an entry.  Keys have the
+/// types and defaults from the spec and localized keys are looked up for the
+/// user's locales.
+///
+/// Derefs to [`AttrSelector`](struct.AttrSelector.html) for everything else.
+/// Created by [`EntryRef::desktop_entry`](struct.EntryRef.html#method.desktop_entry)
+///
+/// Example:
+/// ```no_run
+/// use freedesktop_entry_parser::parse_entry;
+///
+/// let entry = parse_entry("/usr/share/applications/firefox.desktop")?;
+/// let desktop_entry = entry.desktop_entry();
+/// println!("{:?}", desktop_entry.localized_name());
+/// println!("{:?}", desktop_entry.attr("Exec"));
+/// # Ok::<(), freedesktop_entry_parser::ParseError>(())
+/// ```
+pub struct DesktopEntry<'a> {
+    pub(crate) entry: &'a EntryRef<'a>,
+    section: AttrSelector<'a, &'static str>,
+    locales: Vec<Locale>,
+    location: Option<String>,
+}
+
+/// The `Type` of a desktop entry.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DesktopEntryType {
+    /// A program to run, it needs an `Exec` key unless it's
+    /// `DBusActivatable`
+    Application,
+    /// A link to a `URL`
+    Link,
+    /// A directory in a menu
+    Directory,
+    /// Any other type, like the `X-` ones of other specs
+    Unknown(String),
+}
+
+impl DesktopEntryType {
+    /// The type as it's written in the `Type` key
+    pub fn as_str(&self) -> &str {
+        match self {
+            DesktopEntryType::Application => "Application",
+            DesktopEntryType::Link => "Link",
+            DesktopEntryType::Directory => "Directory",
+            DesktopEntryType::Unknown(ty) => ty,
+        }
+    }
+}
+
+impl FromStr for DesktopEntryType {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "Application" => DesktopEntryType::Application,
+            "Link" => DesktopEntryType::Link,
+            "Directory" => DesktopEntryType::Directory,
+            ty => DesktopEntryType::Unknown(ty.to_owned()),
+        })
+    }
+}
+
+impl Display for DesktopEntryType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An action from a `[Desktop Action id]` section, like opening a new
+/// window.
+///
+/// Created by [`DesktopEntry::actions`](struct.DesktopEntry.html#method.actions)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DesktopAction<'a> {
+    /// Id of the action, from `Actions`
+    pub id: Cow<'a, str>,
+    /// `Name` in the preferred locale
+    pub name: Option<&'a str>,
+    /// `Exec` with its escapes decoded
+    pub exec: Option<Cow<'a, str>>,
+    /// `Icon`
+    pub icon: Option<&'a str>,
+}
+
+/// Which checks [`DesktopEntry::should_show_with`](struct.DesktopEntry.html#method.should_show_with)
+/// applies.  All of them are on by default.
+#[derive(Debug, Clone)]
+pub struct VisibilityChecks {
+    hidden: bool,
+    no_display: bool,
+    desktops: bool,
+    try_exec: bool,
+}
+
+impl Default for VisibilityChecks {
+    fn default() -> Self {
+        Self {
+            hidden: true,
+            no_display: true,
+            desktops: true,
+            try_exec: true,
+        }
+    }
+}
+
+impl VisibilityChecks {
+    /// Hide entries with `Hidden=true`, which are deleted.
+    pub fn hidden(mut self, hidden: bool) -> Self {
+        self.hidden = hidden;
+        self
+    }
+
+    /// Hide entries with `NoDisplay=true`.
+    pub fn no_display(mut self, no_display: bool) -> Self {
+        self.no_display = no_display;
+        self
+    }
+
+    /// Hide entries that aren't shown in the current desktop, see
+    /// [`DesktopEntry::shown_in_current_desktop`](struct.DesktopEntry.html#method.shown_in_current_desktop).
+    pub fn desktops(mut self, desktops: bool) -> Self {
+        self.desktops = desktops;
+        self
+    }
+
+    /// Hide entries whose `TryExec` isn't an executable file, either an
+    /// absolute path or a program in `PATH`.
+    pub fn try_exec(mut self, try_exec: bool) -> Self {
+        self.try_exec = try_exec;
+        self
+    }
+}
+
+/// The version of the spec an entry follows, from `Version`, like `1.5`.
+///
+/// Versions compare by their numbers, so `1.10` is newer than `1.9`.
+///
+/// Example:
+/// ```
+/// use freedesktop_entry_parser::{Entry, SpecVersion};
+///
+/// let entry = Entry::parse(&b"[Desktop Entry]\nVersion=1.5"[..])?;
+/// let version = entry.desktop_entry().spec_version().unwrap();
+/// assert!(version.at_least("1.4"));
+/// assert!(version < "1.10".parse::<SpecVersion>()?);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SpecVersion {
+    /// The part before the `.`
+    pub major: u32,
+    /// The part after the `.`
+    pub minor: u32,
+}
+
+impl SpecVersion {
+    /// Whether this is `version` or newer.  Returns `false` if `version`
+    /// isn't valid.
+    pub fn at_least(&self, version: &str) -> bool {
+        match version.parse::<SpecVersion>() {
+            Ok(version) => *self >= version,
+            Err(_) => false,
+        }
+    }
+}
+
+impl FromStr for SpecVersion {
+    type Err = InvalidVersion;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidVersion(s.to_owned());
+        let (major, minor) = s.split_once('.').ok_or_else(invalid)?;
+        let number = |part: &str| {
+            if part.is_empty() || !part.bytes().all(|c| c.is_ascii_digit()) {
+                return Err(invalid());
+            }
+            part.parse().map_err(|_| invalid())
+        };
+        Ok(SpecVersion {
+            major: number(major)?,
+            minor: number(minor)?,
+        })
+    }
+}
+
+impl Display for SpecVersion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// How an application announces that it started.
+///
+/// Created by [`DesktopEntry::startup_info`](struct.DesktopEntry.html#method.startup_info)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StartupInfo<'a> {
+    /// `StartupNotify`, whether the application sends a "remove" message
+    /// when it started.  `false` if it's not set.
+    pub notify: bool,
+    /// `StartupWMClass`, the WM class or name hint of the application's
+    /// windows
+    pub wm_class: Option<&'a str>,
+}
+
+impl<'a> EntryRef<'a> {
+    /// Get the `Desktop Entry` section, using the locales from
+    /// [`Locale::from_env`](struct.Locale.html#method.from_env) for its
+    /// localized keys.
+    pub fn desktop_entry(&self) -> DesktopEntry<'_> {
+        DesktopEntry {
+            entry: self,
+            section: self.section("Desktop Entry"),
+            locales: Locale::from_env(),
+            location: None,
+        }
+    }
+}
+
+impl<'a> DesktopEntry<'a> {
+    /// Use `locales` instead of the ones from the environment.
+    pub fn with_locales(mut self, locales: Vec<Locale>) -> Self {
+        self.locales = locales;
+        self
+    }
+
+    /// The locales used for localized keys, most preferred first
+    pub fn locales(&self) -> &[Locale] {
+        &self.locales
+    }
+
+    /// Set the path or URI the entry was read from, which `%k` in `Exec` is
+    /// replaced by.
+    pub fn with_location(mut self, location: impl Into<String>) -> Self {
+        self.location = Some(location.into());
+        self
+    }
+
+    /// The path or URI the entry was read from, see
+    /// [`with_location`](#method.with_location)
+    pub fn location(&self) -> Option<&str> {
+        self.location.as_deref()
+    }
+
+    /// The `Name` in the preferred locale
+    pub fn localized_name(&self) -> Option<&'a str> {
+        self.section.attr_preferred("Name", &self.locales)
+    }
+
+    /// The `GenericName` in the preferred locale
+    pub fn localized_generic_name(&self) -> Option<&'a str> {
+        self.section.attr_preferred("GenericName", &self.locales)
+    }
+
+    /// The `Comment` in the preferred locale
+    pub fn localized_comment(&self) -> Option<&'a str> {
+        self.section.attr_preferred("Comment", &self.locales)
+    }
+
+    /// The `Keywords` in the preferred locale
+    pub fn localized_keywords(&self) -> ListIter<'a> {
+        let keywords = self.section.attr_preferred("Keywords", &self.locales);
+        ListIter::new(keywords.unwrap_or(""))
+    }
+
+    /// The `Keywords` for `locale`, with escapes decoded, for searching
+    /// entries.  Uses `locale` instead of the locales of this
+    /// `DesktopEntry`.
+    pub fn keywords(&self, locale: &Locale) -> ListIter<'a> {
+        self.section.attr_list_localized("Keywords", locale)
+    }
+
+    /// `Type` of the entry
+    pub fn entry_type(&self) -> Option<DesktopEntryType> {
+        self.section.attr_parse("Type").ok().flatten()
+    }
+
+    /// `Type` of the entry, checking that it's set and that the keys its
+    /// type needs are there: `Exec` for an `Application` that isn't
+    /// `DBusActivatable` and `URL` for a `Link`.  The error names the first
+    /// missing key.
+    ///
+    /// With the `url` feature the `URL` of a `Link` also has to be a valid
+    /// URL.
+    pub fn require_type(&self) -> Result<DesktopEntryType, EntryError> {
+        let ty: DesktopEntryType = match self.section.require("Type")?.parse() {
+            Ok(ty) => ty,
+            Err(e) => match e {},
+        };
+        match ty {
+            DesktopEntryType::Application if !self.dbus_activatable() => {
+                self.section.require("Exec")?;
+            }
+            DesktopEntryType::Link => {
+                self.section.require("URL")?;
+                #[cfg(feature = "url")]
+                self.parsed_url()?;
+            }
+            _ => {}
+        }
+        Ok(ty)
+    }
+
+    /// `Version` of the spec the entry follows
+    pub fn version(&self) -> Option<&'a str> {
+        self.section.attr("Version")
+    }
+
+    /// `Version` parsed, `None` if it's missing or invalid
+    pub fn spec_version(&self) -> Option<SpecVersion> {
+        self.version()?.parse().ok()
+    }
+
+    /// `Name` without a locale
+    pub fn name(&self) -> Option<&'a str> {
+        self.section.attr("Name")
+    }
+
+    /// `GenericName` without a locale
+    pub fn generic_name(&self) -> Option<&'a str> {
+        self.section.attr("GenericName")
+    }
+
+    /// `Comment` without a locale
+    pub fn comment(&self) -> Option<&'a str> {
+        self.section.attr("Comment")
+    }
+
+    /// `Icon`, a name from the icon theme or an absolute path
+    pub fn icon(&self) -> Option<&'a str> {
+        self.section.attr("Icon")
+    }
+
+    /// `Exec`, the command line to run, with its escapes decoded.  Field
+    /// codes like `%f` are kept.
+    pub fn exec(&self) -> Option<Cow<'a, str>> {
+        self.section.attr_unescaped("Exec")
+    }
+
+    /// `TryExec`, a program that has to exist for the entry to be shown
+    pub fn try_exec(&self) -> Option<Cow<'a, str>> {
+        self.section.attr_unescaped("TryExec")
+    }
+
+    /// `Path`, the working directory to run the program in
+    pub fn path(&self) -> Option<Cow<'a, str>> {
+        self.section.attr_unescaped("Path")
+    }
+
+    /// `URL` of a `Link` entry
+    pub fn url(&self) -> Option<Cow<'a, str>> {
+        self.section.attr_unescaped("URL")
+    }
+
+    /// `URL` of a `Link` entry parsed, `None` if it's missing
+    #[cfg(feature = "url")]
+    pub fn parsed_url(&self) -> Result<Option<url::Url>, EntryError> {
+        let url = match self.url() {
+            Some(url) => url,
+            None => return Ok(None),
+        };
+        url::Url::parse(&url)
+            .map(Some)
+            .map_err(|e| EntryError::InvalidValue {
+                section: self.section.name().to_owned(),
+                attr: "URL".to_owned(),
+                value: url.into_owned(),
+                source: Box::new(e),
+            })
+    }
+
+    /// `Terminal`, whether the program runs in a terminal.  Defaults to
+    /// `false`.
+    pub fn terminal(&self) -> bool {
+        self.section.attr_bool("Terminal").unwrap_or(false)
+    }
+
+    /// `NoDisplay`, whether the entry is hidden from menus.  Defaults to
+    /// `false`.
+    pub fn no_display(&self) -> bool {
+        self.section.attr_bool("NoDisplay").unwrap_or(false)
+    }
+
+    /// `Hidden`, whether the entry should be treated as deleted.  Defaults
+    /// to `false`.
+    pub fn hidden(&self) -> bool {
+        self.section.attr_bool("Hidden").unwrap_or(false)
+    }
+
+    /// `DBusActivatable`.  Defaults to `false`.
+    pub fn dbus_activatable(&self) -> bool {
+        self.section.attr_bool("DBusActivatable").unwrap_or(false)
+    }
+
+    /// `PrefersNonDefaultGPU`, whether the program should run on a
+    /// discrete GPU if there is one.  Added in version 1.4 of the spec.
+    /// Defaults to `false`.
+    pub fn prefers_non_default_gpu(&self) -> bool {
+        self.section
+            .attr_bool("PrefersNonDefaultGPU")
+            .unwrap_or(false)
+    }
+
+    /// `SingleMainWindow`, whether the program has a single main window
+    /// so launchers shouldn't offer to open a new one.  Added in version
+    /// 1.5 of the spec.  Defaults to `false`.
+    pub fn single_main_window(&self) -> bool {
+        self.section.attr_bool("SingleMainWindow").unwrap_or(false)
+    }
+
+    /// `StartupNotify`.  `None` if it's not set, in which case the spec
+    /// leaves it up to the launcher.
+    pub fn startup_notify(&self) -> Option<bool> {
+        self.section.attr_bool("StartupNotify")
+    }
+
+    /// `StartupWMClass`
+    pub fn startup_wm_class(&self) -> Option<&'a str> {
+        self.section.attr("StartupWMClass")
+    }
+
+    /// `StartupNotify` and `StartupWMClass` together, for matching windows
+    /// to the entry that started them
+    pub fn startup_info(&self) -> StartupInfo<'a> {
+        StartupInfo {
+            notify: self.startup_notify().unwrap_or(false),
+            wm_class: self.startup_wm_class(),
+        }
+    }
+
+    /// `Categories`, empty if not set
+    pub fn categories(&self) -> ListIter<'a> {
+        self.section.attr_list("Categories")
+    }
+
+    /// `MimeType`, empty if not set
+    pub fn mime_types(&self) -> ListIter<'a> {
+        self.section.attr_list("MimeType")
+    }
+
+    /// Whether the entry can open files of type `mime`, according to
+    /// `MimeType`.
+    ///
+    /// MIME types are compared ignoring ASCII case.  A `*` subtype matches
+    /// all subtypes, both in `MimeType` and in `mime`, so `image/*` matches
+    /// `image/png`.
+    pub fn handles_mime(&self, mime: &str) -> bool {
+        self.mime_types().any(|handled| {
+            mime_matches(&handled, mime) || mime_matches(mime, &handled)
+        })
+    }
+
+    /// `Actions`, the ids of the actions, empty if not set
+    pub fn action_ids(&self) -> ListIter<'a> {
+        self.section.attr_list("Actions")
+    }
+
+    /// The actions listed in `Actions`, in that order.  Actions without a
+    /// `[Desktop Action id]` section are left out, as the spec requires.
+    ///
+    /// Example:
+    /// ```
+    /// use freedesktop_entry_parser::Entry;
+    ///
+    /// let entry = Entry::parse(
+    ///     &b"[Desktop Entry]\nActions=new-window;\n\
+    ///        [Desktop Action new-window]\nName=New Window\nExec=firefox --new-window"[..],
+    /// )?;
+    /// let actions = entry.desktop_entry().actions();
+    /// assert_eq!(actions[0].id, "new-window");
+    /// assert_eq!(actions[0].name, Some("New Window"));
+    /// # Ok::<(), freedesktop_entry_parser::ParseError>(())
+    /// ```
+    pub fn actions(&self) -> Vec<DesktopAction<'a>> {
+        self.action_ids()
+            .filter_map(|id| {
+                let name = format!("Desktop Action {}", id);
+                if !self.entry.has_section(&name) {
+                    return None;
+                }
+                let section = self.entry.section(name);
+                Some(DesktopAction {
+                    name: section.attr_preferred("Name", &self.locales),
+                    exec: section.attr_unescaped("Exec"),
+                    icon: section.attr("Icon"),
+                    id,
+                })
+            })
+            .collect()
+    }
+
+    /// Whether the entry should be shown in the current desktop
+    /// environments, from `XDG_CURRENT_DESKTOP`.  See
+    /// [`shown_in`](#method.shown_in).
+    pub fn shown_in_current_desktop(&self) -> bool {
+        let current = env::var("XDG_CURRENT_DESKTOP").unwrap_or_default();
+        let desktops: Vec<&str> =
+            current.split(':').filter(|d| !d.is_empty()).collect();
+        self.shown_in(&desktops)
+    }
+
+    /// Whether the entry should be shown in a session of the desktop
+    /// environments `desktops`, like `["GNOME"]`, according to
+    /// `OnlyShowIn` and `NotShowIn`.  Names are case sensitive.
+    ///
+    /// With `OnlyShowIn` one of `desktops` has to be in it and with
+    /// `NotShowIn` none of them may be.
+    pub fn shown_in(&self, desktops: &[&str]) -> bool {
+        let listed = |mut list: ListIter<'_>| {
+            list.any(|desktop| desktops.contains(&desktop.as_ref()))
+        };
+        if self.section.has_attr("OnlyShowIn") && !listed(self.only_show_in()) {
+            return false;
+        }
+        !listed(self.not_show_in())
+    }
+
+    /// Whether a menu should show this entry, with all the checks of
+    /// [`VisibilityChecks`](struct.VisibilityChecks.html) applied.
+    pub fn should_show(&self) -> bool {
+        self.should_show_with(&VisibilityChecks::default())
+    }
+
+    /// Whether a menu should show this entry, applying the `checks`.
+    ///
+    /// Example:
+    /// ```
+    /// use freedesktop_entry_parser::{Entry, VisibilityChecks};
+    ///
+    /// let entry = Entry::parse(&b"[Desktop Entry]\nNoDisplay=true"[..])?;
+    /// let desktop_entry = entry.desktop_entry();
+    /// assert!(!desktop_entry.should_show());
+    /// // Still show it in a list of programs to open a file with
+    /// let checks = VisibilityChecks::default().no_display(false);
+    /// assert!(desktop_entry.should_show_with(&checks));
+    /// # Ok::<(), freedesktop_entry_parser::ParseError>(())
+    /// ```
+    pub fn should_show_with(&self, checks: &VisibilityChecks) -> bool {
+        if checks.hidden && self.hidden() {
+            return false;
+        }
+        if checks.no_display && self.no_display() {
+            return false;
+        }
+        if checks.desktops && !self.shown_in_current_desktop() {
+            return false;
+        }
+        if checks.try_exec {
+            if let Some(try_exec) = self.try_exec() {
+                return find_executable(&try_exec);
+            }
+        }
+        true
+    }
+
+    /// `Implements`, the D-Bus interfaces the application implements, like
+    /// `org.gnome.Shell.SearchProvider2`.  Empty if not set.
+    pub fn interfaces(&self) -> ListIter<'a> {
+        self.section.attr_list("Implements")
+    }
+
+    /// Whether `Implements` has the interface `iface`
+    pub fn implements(&self, iface: &str) -> bool {
+        self.interfaces().any(|i| i == iface)
+    }
+
+    /// `OnlyShowIn`, empty if not set
+    pub fn only_show_in(&self) -> ListIter<'a> {
+        self.section.attr_list("OnlyShowIn")
+    }
+
+    /// `NotShowIn`, empty if not set
+    pub fn not_show_in(&self) -> ListIter<'a> {
+        self.section.attr_list("NotShowIn")
+    }
+}
+
+impl<'a> Deref for DesktopEntry<'a> {
+    type Target = AttrSelector<'a, &'static str>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.section
+    }
+}
+
+/// Whether `mime` matches `pattern`, which may have a `*` subtype
+fn mime_matches(pattern: &str, mime: &str) -> bool {
+    if pattern.eq_ignore_ascii_case(mime) {
+        return true;
+    }
+    match (pattern.split_once('/'), mime.split_once('/')) {
+        (Some((ty, "*")), Some((mime_ty, _))) => {
+            ty.eq_ignore_ascii_case(mime_ty)
+        }
+        _ => false,
+    }
+}
+
+/// Whether `program` is an executable file, looking it up in `PATH` unless
+/// it's a path.
+pub(crate) fn find_executable(program: &str) -> bool {
+    let program = Path::new(program);
+    if program.components().count() > 1 {
+        return is_executable(program);
+    }
+    let paths = env::var_os("PATH").unwrap_or_default();
+    env::split_paths(&paths).any(|dir| is_executable(&dir.join(program)))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    match path.metadata() {
+        Ok(meta) => meta.is_file() && meta.permissions().mode() & 0o111 != 0,
+        Err(_) => false,
+    }
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Entry;
+
+    #[test]
+    fn typed() {
+        let entry = Entry::parse(
+            &b"[Desktop Entry]\nExec=foo\\sbar %U\nTerminal=true\n\
+               NoDisplay=yes\nCategories=GTK;Utility;\nIcon=foo\n\
+               StartupNotify=false"[..],
+        )
+        .unwrap();
+        let desktop_entry = entry.desktop_entry();
+        assert_eq!(desktop_entry.exec().as_deref(), Some("foo bar %U"));
+        assert_eq!(desktop_entry.icon(), Some("foo"));
+        assert!(desktop_entry.terminal());
+        assert!(!desktop_entry.no_display());
+        assert!(!desktop_entry.hidden());
+        assert_eq!(desktop_entry.startup_notify(), Some(false));
+        assert_eq!(
+            desktop_entry.categories().collect::<Vec<_>>(),
+            ["GTK", "Utility"]
+        );
+        assert_eq!(desktop_entry.mime_types().count(), 0);
+        assert_eq!(desktop_entry.url(), None);
+    }
+
+    #[test]
+    fn gpu_and_windows() {
+        let entry = Entry::parse(&b"[Desktop Entry]\nExec=foo"[..]).unwrap();
+        let desktop_entry = entry.desktop_entry();
+        assert!(!desktop_entry.prefers_non_default_gpu());
+        assert!(!desktop_entry.single_main_window());
+
+        let entry = Entry::parse(
+            &b"[Desktop Entry]\nExec=foo\n\
+               PrefersNonDefaultGPU=true\nSingleMainWindow=true"[..],
+        )
+        .unwrap();
+        let desktop_entry = entry.desktop_entry();
+        assert!(desktop_entry.prefers_non_default_gpu());
+        assert!(desktop_entry.single_main_window());
+
+        let entry = Entry::parse(
+            &b"[Desktop Entry]\nExec=foo\n\
+               PrefersNonDefaultGPU=false\nSingleMainWindow=yes"[..],
+        )
+        .unwrap();
+        let desktop_entry = entry.desktop_entry();
+        assert!(!desktop_entry.prefers_non_default_gpu());
+        assert!(!desktop_entry.single_main_window());
+    }
+
+    #[test]
+    fn implements() {
+        let entry = Entry::parse(
+            &b"[Desktop Entry]\nExec=foo\n\
+               Implements=org.gnome.Shell.SearchProvider2;org.example.Foo;"[..],
+        )
+        .unwrap();
+        let desktop_entry = entry.desktop_entry();
+        assert_eq!(
+            desktop_entry.interfaces().collect::<Vec<_>>(),
+            ["org.gnome.Shell.SearchProvider2", "org.example.Foo"]
+        );
+        assert!(desktop_entry.implements("org.gnome.Shell.SearchProvider2"));
+        assert!(desktop_entry.implements("org.example.Foo"));
+        assert!(!desktop_entry.implements("org.gnome.Shell"));
+        assert!(!desktop_entry.implements(""));
+
+        let entry = Entry::parse(&b"[Desktop Entry]\nExec=foo"[..]).unwrap();
+        let desktop_entry = entry.desktop_entry();
+        assert_eq!(desktop_entry.interfaces().count(), 0);
+        assert!(!desktop_entry.implements("org.example.Foo"));
+    }
+
+    #[test]
+    fn startup_info() {
+        let entry = Entry::parse(&b"[Desktop Entry]\nExec=foo"[..]).unwrap();
+        assert_eq!(
+            entry.desktop_entry().startup_info(),
+            StartupInfo {
+                notify: false,
+                wm_class: None
+            }
+        );
+
+        let entry = Entry::parse(
+            &b"[Desktop Entry]\nExec=foo\n\
+               StartupNotify=true\nStartupWMClass=foo-bin"[..],
+        )
+        .unwrap();
+        assert_eq!(
+            entry.desktop_entry().startup_info(),
+            StartupInfo {
+                notify: true,
+                wm_class: Some("foo-bin")
+            }
+        );
+    }
+
+    #[test]
+    fn entry_type() {
+        let entry_type = |input: &str| {
+            let entry = Entry::parse(input.as_bytes()).unwrap();
+            entry.desktop_entry().require_type()
+        };
+        assert_eq!(
+            entry_type("[Desktop Entry]\nType=Application\nExec=foo").unwrap(),
+            DesktopEntryType::Application
+        );
+        assert_eq!(
+            entry_type("[Desktop Entry]\nType=X-Foo").unwrap(),
+            DesktopEntryType::Unknown("X-Foo".to_owned())
+        );
+        assert_eq!(
+            entry_type(
+                "[Desktop Entry]\nType=Application\nDBusActivatable=true"
+            )
+            .unwrap(),
+            DesktopEntryType::Application
+        );
+        let entry = Entry::parse(&b"[Desktop Entry]\nType=Link"[..]).unwrap();
+        assert_eq!(
+            entry.desktop_entry().entry_type(),
+            Some(DesktopEntryType::Link)
+        );
+        match entry_type("[Desktop Entry]\nType=Link") {
+            Err(EntryError::MissingKey { attr, .. }) => assert_eq!(attr, "URL"),
+            _ => panic!("Expected a missing key error"),
+        }
+        match entry_type("[Desktop Entry]\nName=Foo") {
+            Err(EntryError::MissingKey { attr, .. }) => {
+                assert_eq!(attr, "Type")
+            }
+            _ => panic!("Expected a missing key error"),
+        }
+    }
+
+    #[cfg(feature = "url")]
+    #[test]
+    fn parsed_url() {
+        let entry = Entry::parse(
+            &b"[Desktop Entry]\nType=Link\nURL=https://example.com/"[..],
+        )
+        .unwrap();
+        let url = entry.desktop_entry().parsed_url().unwrap().unwrap();
+        assert_eq!(url.host_str(), Some("example.com"));
+        let entry =
+            Entry::parse(&b"[Desktop Entry]\nType=Link\nURL=example"[..])
+                .unwrap();
+        match entry.desktop_entry().require_type() {
+            Err(EntryError::InvalidValue { attr, .. }) => {
+                assert_eq!(attr, "URL")
+            }
+            _ => panic!("Expected an invalid value error"),
+        }
+    }
+
+    #[test]
+    fn actions() {
+        let entry = Entry::parse(
+            &b"[Desktop Entry]\nActions=private;missing;new;\n\
+               [Desktop Action new]\nName=New\nName[de]=Neu\nExec=foo --new\n\
+               [Desktop Action private]\nName=Private\nIcon=private\n"[..],
+        )
+        .unwrap();
+        let locales = vec!["de".parse().unwrap()];
+        let actions = entry.desktop_entry().with_locales(locales).actions();
+        assert_eq!(
+            actions,
+            [
+                DesktopAction {
+                    id: "private".into(),
+                    name: Some("Private"),
+                    exec: None,
+                    icon: Some("private"),
+                },
+                DesktopAction {
+                    id: "new".into(),
+                    name: Some("Neu"),
+                    exec: Some("foo --new".into()),
+                    icon: None,
+                },
+            ]
+        );
+        assert_eq!(entry.desktop_entry().action_ids().count(), 3);
+    }
+
+    #[test]
+    fn shown_in() {
+        let shown_in = |input: &str, desktops: &[&str]| {
+            let input = format!("[Desktop Entry]\n{}", input);
+            let entry = Entry::parse(input.as_bytes()).unwrap();
+            let shown = entry.desktop_entry().shown_in(desktops);
+            shown
+        };
+        assert!(shown_in("", &[]));
+        assert!(shown_in("", &["GNOME"]));
+        assert!(shown_in("OnlyShowIn=KDE;GNOME;", &["ubuntu", "GNOME"]));
+        assert!(!shown_in("OnlyShowIn=KDE;", &["GNOME"]));
+        assert!(!shown_in("OnlyShowIn=KDE;", &[]));
+        assert!(!shown_in("OnlyShowIn=gnome;", &["GNOME"]));
+        assert!(!shown_in("NotShowIn=GNOME;", &["ubuntu", "GNOME"]));
+        assert!(shown_in("NotShowIn=GNOME;", &["KDE"]));
+    }
+
+    #[test]
+    fn should_show() {
+        let should_show = |input: &str, checks: VisibilityChecks| {
+            let input = format!("[Desktop Entry]\n{}", input);
+            let entry = Entry::parse(input.as_bytes()).unwrap();
+            let shown = entry.desktop_entry().should_show_with(&checks);
+            shown
+        };
+        let all = VisibilityChecks::default().desktops(false);
+        assert!(should_show("Name=Foo", all.clone()));
+        assert!(!should_show("Hidden=true", all.clone()));
+        assert!(should_show("Hidden=true", all.clone().hidden(false)));
+        assert!(!should_show("NoDisplay=true", all.clone()));
+        assert!(should_show("NoDisplay=true", all.clone().no_display(false)));
+        assert!(should_show("TryExec=sh", all.clone()));
+        assert!(!should_show("TryExec=/no/such/program", all.clone()));
+        assert!(should_show("TryExec=/no/such/program", all.try_exec(false)));
+    }
+
+    #[test]
+    fn mime_types() {
+        let entry = Entry::parse(
+            &b"[Desktop Entry]\nMimeType=image/png;video/*;text/HTML;"[..],
+        )
+        .unwrap();
+        let desktop_entry = entry.desktop_entry();
+        assert_eq!(desktop_entry.mime_types().count(), 3);
+        assert!(desktop_entry.handles_mime("image/png"));
+        assert!(!desktop_entry.handles_mime("image/jpeg"));
+        assert!(desktop_entry.handles_mime("image/*"));
+        assert!(desktop_entry.handles_mime("video/mp4"));
+        assert!(desktop_entry.handles_mime("text/html"));
+        assert!(!desktop_entry.handles_mime("text/plain"));
+        assert!(!desktop_entry.handles_mime("audio/*"));
+    }
+
+    #[test]
+    fn spec_version() {
+        let version = |s: &str| s.parse::<SpecVersion>();
+        assert_eq!(version("1.5").unwrap(), SpecVersion { major: 1, minor: 5 });
+        assert!(version("1.10").unwrap() > version("1.9").unwrap());
+        assert!(!version("1.0").unwrap().at_least("1.1"));
+        assert!(!version("1.0").unwrap().at_least("0.9.4"));
+        assert!(version("1.0").unwrap().at_least("1.0"));
+        assert_eq!(version("1.5").unwrap().to_string(), "1.5");
+        for bad in &["1", "1.", ".5", "1.5.1", "1.x", "+1.5"] {
+            assert!(version(bad).is_err(), "{}", bad);
+        }
+        let entry = Entry::parse(&b"[Desktop Entry]\nVersion=1.x"[..]).unwrap();
+        assert_eq!(entry.desktop_entry().spec_version(), None);
+    }
+
+    #[test]
+    fn localized() {
+        let entry = Entry::parse(
+            &b"[Desktop Entry]\nName=Files\nName[de]=Dateien\n\
+               GenericName=File Manager\nComment[de]=Dateien verwalten\n\
+               Exec=nautilus"[..],
+        )
+        .unwrap();
+        let locales = vec!["de_DE.UTF-8".parse().unwrap()];
+        let desktop_entry = entry.desktop_entry().with_locales(locales);
+        assert_eq!(desktop_entry.localized_name(), Some("Dateien"));
+        assert_eq!(
+            desktop_entry.localized_generic_name(),
+            Some("File Manager")
+        );
+        assert_eq!(
+            desktop_entry.localized_comment(),
+            Some("Dateien verwalten")
+        );
+        assert_eq!(desktop_entry.attr("Exec"), Some("nautilus"));
+        assert_eq!(desktop_entry.name(), Some("Files"));
+
+        let desktop_entry = entry.desktop_entry().with_locales(Vec::new());
+        assert_eq!(desktop_entry.localized_name(), Some("Files"));
+        assert_eq!(desktop_entry.localized_comment(), None);
+    }
+
+    #[test]
+    fn keywords() {
+        let entry = Entry::parse_str(
+            "[Desktop Entry]\nKeywords=folder;\nKeywords[pt]=pasta;\n\
+             Keywords[pt_BR]=diretório;pasta\\;arquivo;",
+        )
+        .unwrap();
+        let desktop_entry = entry.desktop_entry().with_locales(Vec::new());
+        let keywords = |locale: &str| {
+            desktop_entry
+                .keywords(&locale.parse().unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(keywords("pt_BR.UTF-8"), ["diretório", "pasta;arquivo"]);
+        assert_eq!(keywords("pt_PT"), ["pasta"]);
+        assert_eq!(keywords("pt"), ["pasta"]);
+        assert_eq!(keywords("de_DE"), ["folder"]);
+        assert_eq!(desktop_entry.localized_keywords().count(), 1);
+
+        let entry = Entry::parse(&b"[Desktop Entry]\nName=Foo"[..]).unwrap();
+        let desktop_entry = entry.desktop_entry();
+        assert_eq!(desktop_entry.keywords(&"pt".parse().unwrap()).count(), 0);
+    }
+}
@@ -0,0 +1,102 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::{errors::ParseError, span::Span};
+use std::fmt::{self, Display, Formatter};
+
+/// How serious a [`Diagnostic`](struct.Diagnostic.html) is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Something the spec doesn't allow but that could still be read.
+    Warning,
+    /// Something that couldn't be parsed and was skipped.
+    Error,
+}
+
+/// A problem found while parsing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// How serious the problem is
+    pub severity: Severity,
+    /// Line of the problem, starting at 1.  `0` if it has no position.
+    pub line: usize,
+    /// Column of the problem in bytes, starting at 1.  `0` if it has no
+    /// position.
+    pub column: usize,
+    /// Description of the problem
+    pub message: String,
+}
+
+impl Diagnostic {
+    /// A diagnostic for `at`, which has to be a slice of `input`.
+    pub(crate) fn new(
+        severity: Severity,
+        input: &[u8],
+        at: &[u8],
+        message: impl Into<String>,
+    ) -> Self {
+        let (line, column) =
+            Span::of(input, at).map_or((0, 0), |span| (span.line, span.column));
+        Diagnostic {
+            severity,
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+
+    /// An error diagnostic for `e`, at its position if it has one.
+    pub(crate) fn from_error(e: &ParseError) -> Self {
+        let (line, column) = match e {
+            ParseError::Other { line, column, .. } => (*line, *column),
+            _ => (0, 0),
+        };
+        Diagnostic {
+            severity: Severity::Error,
+            line,
+            column,
+            message: e.to_string(),
+        }
+    }
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Warning => f.write_str("warning"),
+            Severity::Error => f.write_str("error"),
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {}: {}",
+            self.line, self.column, self.severity, self.message
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Entry;
+
+    #[test]
+    fn from_error() {
+        let e = Entry::parse(&b"[A]\nB=1\nnot an attribute"[..]).unwrap_err();
+        let diagnostic = Diagnostic::from_error(&e);
+        assert_eq!(diagnostic.severity, Severity::Error);
+        assert_eq!((diagnostic.line, diagnostic.column), (3, 17));
+        assert_eq!(diagnostic.message, e.to_string());
+
+        let e = ParseError::DuplicateSection {
+            section: "A".to_owned(),
+        };
+        let diagnostic = Diagnostic::from_error(&e);
+        assert_eq!((diagnostic.line, diagnostic.column), (0, 0));
+    }
+}
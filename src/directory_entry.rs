@@ -0,0 +1,101 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::{DesktopEntry, DesktopEntryType, EntryError, EntryRef};
+use std::ops::Deref;
+
+/// A typed view of a `.directory` file from the [menu
+/// spec](https://specifications.freedesktop.org/menu-spec/latest/), which
+/// describes a submenu.
+///
+/// Derefs to [`DesktopEntry`](struct.DesktopEntry.html), which has the
+/// accessors for the keys it shares with desktop entries, like
+/// `localized_name` and `icon`.
+///
+/// Created by [`EntryRef::directory_entry`](struct.EntryRef.html#method.directory_entry)
+///
+/// Example:
+/// ```
+/// use freedesktop_entry_parser::Entry;
+///
+/// let entry = Entry::parse(
+///     &b"[Desktop Entry]\nType=Directory\nName=Games\nIcon=applications-games"[..],
+/// )?;
+/// let directory = entry.directory_entry();
+/// directory.check()?;
+/// assert_eq!(directory.icon(), Some("applications-games"));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct DirectoryEntry<'a> {
+    entry: DesktopEntry<'a>,
+}
+
+impl<'a> EntryRef<'a> {
+    /// Get a typed view of a `.directory` file.  Localized keys use the
+    /// locales from the environment, like
+    /// [`desktop_entry`](#method.desktop_entry).
+    pub fn directory_entry(&self) -> DirectoryEntry<'_> {
+        DirectoryEntry {
+            entry: self.desktop_entry(),
+        }
+    }
+}
+
+impl<'a> DirectoryEntry<'a> {
+    /// Check that the entry has the keys the menu spec requires:
+    /// `Type=Directory`, `Name` and `Icon`.  The error names the first
+    /// problem.
+    pub fn check(&self) -> Result<(), EntryError> {
+        match self.entry.require("Type")?.parse() {
+            Ok(DesktopEntryType::Directory) => {}
+            Ok(ty) => {
+                return Err(EntryError::UnexpectedType {
+                    expected: DesktopEntryType::Directory.to_string(),
+                    found: ty.to_string(),
+                })
+            }
+            Err(e) => match e {},
+        }
+        self.entry.require("Name")?;
+        self.entry.require("Icon")?;
+        Ok(())
+    }
+}
+
+impl<'a> Deref for DirectoryEntry<'a> {
+    type Target = DesktopEntry<'a>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.entry
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Entry;
+
+    #[test]
+    fn check() {
+        let check = |input: &str| {
+            let input = format!("[Desktop Entry]\n{}", input);
+            let entry = Entry::parse(input.as_bytes()).unwrap();
+            let result = entry.directory_entry().check();
+            result
+        };
+        assert!(check("Type=Directory\nName=Games\nIcon=games").is_ok());
+        match check("Type=Application\nName=Games\nIcon=games") {
+            Err(EntryError::UnexpectedType { found, .. }) => {
+                assert_eq!(found, "Application")
+            }
+            _ => panic!("Expected an unexpected type error"),
+        }
+        match check("Type=Directory\nName=Games") {
+            Err(EntryError::MissingKey { attr, .. }) => {
+                assert_eq!(attr, "Icon")
+            }
+            _ => panic!("Expected a missing key error"),
+        }
+    }
+}
@@ -1,5 +1,6 @@
+use crate::span::{line_col, Span};
 use nom::error::{Error as NomError, ErrorKind};
-use std::str::Utf8Error;
+use std::{io, str::Utf8Error};
 use thiserror::Error;
 
 pub type Result<T> = std::result::Result<T, ParseError>;
@@ -10,18 +11,142 @@ pub type Result<T> = std::result::Result<T, ParseError>;
 pub enum ParseError {
     /// Parse encountered some other error.
     /// This is probably the most common error.
-    #[error("Error parings input: {} at {at}", .kind.description())]
+    #[error(
+        "Error parings input: {} at line {line}, column {column}: `{text}`",
+        .kind.description()
+    )]
     Other {
         /// Remain input when error occurred
         at: ErrorBytes,
         /// Type of error
         kind: ErrorKind,
+        /// Line the error occurred on, starting at 1
+        line: usize,
+        /// Column the error occurred at in bytes, starting at 1
+        column: usize,
+        /// Text of the line the error occurred on
+        text: String,
     },
     /// Parser couldn't finish due to incomplete input
     #[error("Incomplete input")]
     Incomplete,
     #[error("Error parsing string to utf8")]
     Utf8Error { bytes: Vec<u8>, source: Utf8Error },
+    /// A section appeared more than once and
+    /// [`Duplicates::Error`](../enum.Duplicates.html#variant.Error) was used
+    #[error("Duplicate section `{section}`")]
+    DuplicateSection {
+        /// Name of the section
+        section: String,
+    },
+    /// An attribute appeared more than once in a section and
+    /// [`Duplicates::Error`](../enum.Duplicates.html#variant.Error) was used
+    #[error("Duplicate attribute `{attr}` in section `{section}`")]
+    DuplicateAttr {
+        /// Name of the section
+        section: String,
+        /// Name of the attribute, including the param if there is one
+        attr: String,
+    },
+    /// Couldn't read the input
+    #[error("Error reading input: {0}")]
+    Io(#[from] io::Error),
+}
+
+/// An error getting a value out of a parsed entry.
+#[derive(Debug, Error)]
+pub enum EntryError {
+    /// A required attribute is missing
+    #[error("Missing `{attr}` in section `{section}`")]
+    MissingKey {
+        /// Name of the section
+        section: String,
+        /// Name of the attribute
+        attr: String,
+    },
+    /// The `Type` of the entry isn't the one that was expected
+    #[error("Expected an entry of type `{expected}`, found `{found}`")]
+    UnexpectedType {
+        /// The type that was expected
+        expected: String,
+        /// The type of the entry
+        found: String,
+    },
+    /// The value of an attribute couldn't be parsed
+    #[error("Invalid value `{value}` for `{attr}` in section `{section}`")]
+    InvalidValue {
+        /// Name of the section
+        section: String,
+        /// Name of the attribute
+        attr: String,
+        /// The value that couldn't be parsed
+        value: String,
+        /// Why the value couldn't be parsed
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+}
+
+/// A string that isn't a valid [`Locale`](../struct.Locale.html).
+#[derive(Debug, Error)]
+#[error("Invalid locale `{0}`")]
+pub struct InvalidLocale(pub String);
+
+/// A string that isn't a valid [`SpecVersion`](../struct.SpecVersion.html).
+#[derive(Debug, Error)]
+#[error("Invalid version `{0}`")]
+pub struct InvalidVersion(pub String);
+
+/// A string that isn't a valid systemd
+/// [`UnitName`](../systemd/struct.UnitName.html).
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+#[error("Invalid unit name `{0}`")]
+pub struct InvalidUnitName(pub String);
+
+/// A string that isn't a valid systemd
+/// [`CalendarEvent`](../systemd/struct.CalendarEvent.html).
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+#[error("Invalid calendar event `{0}`")]
+pub struct InvalidCalendarEvent(pub String);
+
+/// A string that isn't a valid systemd
+/// [`SocketAddress`](../systemd/enum.SocketAddress.html).
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+#[error("Invalid socket address `{0}`")]
+pub struct InvalidSocketAddress(pub String);
+
+/// An `Exec` value that couldn't be split into arguments.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum ExecError {
+    /// A quoted argument has no closing `"`
+    #[error("Unterminated quote in `{exec}`")]
+    UnterminatedQuote {
+        /// The `Exec` value
+        exec: String,
+    },
+    /// There's no program to run
+    #[error("Empty command line")]
+    Empty,
+    /// The entry has no `Exec` key
+    #[error("Missing `Exec`")]
+    MissingExec,
+    /// The `Link` entry has no `URL` key
+    #[error("Missing `URL`")]
+    MissingUrl,
+}
+
+/// A value with a systemd specifier that couldn't be expanded.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum SpecifierError {
+    /// A `%` at the end of the value
+    #[error("Unterminated specifier in `{value}`")]
+    Unterminated {
+        /// The value
+        value: String,
+    },
+    /// `%` followed by a character that isn't a specifier, or one there's
+    /// no value for
+    #[error("Unknown specifier `%{0}`")]
+    Unknown(char),
 }
 
 /// The remaining input from the parser.  Useful for debugging to see where the
@@ -38,23 +163,47 @@ pub enum ErrorBytes {
     Invalid(Vec<u8>),
 }
 
-impl From<nom::Err<NomError<&[u8]>>> for ParseError {
-    fn from(e: nom::Err<NomError<&[u8]>>) -> Self {
+impl ParseError {
+    /// Convert a parser error, `input` is the whole input being parsed.
+    pub(crate) fn from_nom(e: nom::Err<NomError<&[u8]>>, input: &[u8]) -> Self {
         match e {
-            nom::Err::Error(NomError { input, code })
-            | nom::Err::Failure(NomError { input, code }) => {
-                match std::str::from_utf8(&input) {
-                    Ok(s) => ParseError::Other {
-                        at: ErrorBytes::Valid(s.to_owned()),
-                        kind: code,
-                    },
-                    Err(_) => ParseError::Other {
-                        at: ErrorBytes::Invalid(input.to_vec()),
-                        kind: code,
-                    },
+            nom::Err::Error(NomError { input: rem, code })
+            | nom::Err::Failure(NomError { input: rem, code }) => {
+                let offset = Span::of(input, rem)
+                    .map_or(input.len() - rem.len(), |span| span.start);
+                let (line, column) = line_col(input, offset);
+                let line_start = offset + 1 - column;
+                let line_end = input[offset..]
+                    .iter()
+                    .position(|c| *c == b'\n')
+                    .map_or(input.len(), |i| offset + i);
+                let text = &input[line_start..line_end];
+                let text = text.strip_suffix(b"\r").unwrap_or(text);
+                let at = match std::str::from_utf8(rem) {
+                    Ok(s) => ErrorBytes::Valid(s.to_owned()),
+                    Err(_) => ErrorBytes::Invalid(rem.to_vec()),
+                };
+                ParseError::Other {
+                    at,
+                    kind: code,
+                    line,
+                    column,
+                    text: String::from_utf8_lossy(text).into_owned(),
                 }
             }
             nom::Err::Incomplete(_) => ParseError::Incomplete,
         }
     }
 }
+
+/// Allows using `?` on this library's results in functions that return
+/// `std::io::Result`.  I/O errors are passed through as is, other errors are
+/// wrapped with the `ErrorKind::Other` kind.
+impl From<ParseError> for io::Error {
+    fn from(e: ParseError) -> Self {
+        match e {
+            ParseError::Io(e) => e,
+            e => io::Error::other(e),
+        }
+    }
+}
@@ -0,0 +1,559 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Command lines from `Exec` keys.
+//!
+//! The [Desktop Entry
+//! spec](https://specifications.freedesktop.org/desktop-entry-spec/desktop-entry-spec-latest.html#exec-variables)
+//! has its own quoting rules for `Exec`, applied after the escapes of the
+//! value itself have been decoded.  Arguments are separated by spaces and
+//! can be quoted with `"`.  In a quoted argument `"`, `` ` ``, `$` and `\`
+//! are escaped with a `\`.
+//!
+//! Example:
+//! ```
+//! use freedesktop_entry_parser::{exec::split, Entry};
+//!
+//! let entry = Entry::parse(
+//!     &br#"[Desktop Entry]
+//! Exec="/opt/My App/app" --name "say \\"hi\\"" %U"#[..],
+//! )?;
+//! let exec = entry.section("Desktop Entry").attr_unescaped("Exec").unwrap();
+//! assert_eq!(
+//!     split(&exec)?,
+//!     ["/opt/My App/app", "--name", "say \"hi\"", "%U"]
+//! );
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! The field codes in the arguments, like `%f`, are replaced with
+//! [`expand`](fn.expand.html).  [`DesktopEntry::command`](../struct.DesktopEntry.html#method.command)
+//! does all of it to get a [`Command`](std::process::Command) to run, with
+//! a [`TerminalLauncher`](trait.TerminalLauncher.html) for entries that run
+//! in a terminal.
+use crate::{
+    desktop_entry::find_executable, errors::ExecError, DesktopEntry,
+    DesktopEntryType,
+};
+use std::{env, process::Command};
+
+/// Split an `Exec` value into the program and its arguments.  `exec` has to
+/// be unescaped already, see
+/// [`AttrSelector::attr_unescaped`](../struct.AttrSelector.html#method.attr_unescaped).
+///
+/// Field codes like `%f` are kept as they are.  A `\` outside of quotes
+/// escapes the next character, which the spec doesn't allow but is common
+/// in real files.
+pub fn split(exec: &str) -> Result<Vec<String>, ExecError> {
+    let mut args = Vec::new();
+    let mut chars = exec.chars();
+    // Whether an argument was started, so `""` gives an empty argument
+    let mut started = false;
+    let mut arg = String::new();
+    while let Some(c) = chars.next() {
+        match c {
+            ' ' | '\t' | '\n' => {
+                if started {
+                    args.push(std::mem::take(&mut arg));
+                    started = false;
+                }
+            }
+            '"' => {
+                started = true;
+                quoted(&mut chars, &mut arg).ok_or_else(|| {
+                    ExecError::UnterminatedQuote {
+                        exec: exec.to_owned(),
+                    }
+                })?;
+            }
+            '\\' => {
+                started = true;
+                arg.extend(chars.next());
+            }
+            c => {
+                started = true;
+                arg.push(c);
+            }
+        }
+    }
+    if started {
+        args.push(arg);
+    }
+    if args.is_empty() {
+        return Err(ExecError::Empty);
+    }
+    Ok(args)
+}
+
+/// Read a quoted argument up to the closing `"` into `arg`.  Returns `None`
+/// if there's no closing `"`.
+fn quoted(chars: &mut std::str::Chars<'_>, arg: &mut String) -> Option<()> {
+    loop {
+        match chars.next()? {
+            '"' => return Some(()),
+            '\\' => match chars.next()? {
+                c @ ('"' | '`' | '$' | '\\') => arg.push(c),
+                // Not an escape, keep the backslash
+                c => {
+                    arg.push('\\');
+                    arg.push(c);
+                }
+            },
+            c => arg.push(c),
+        }
+    }
+}
+
+/// Replace the field codes in `args`, the result of [`split`](fn.split.html),
+/// to get the command lines to run for opening `files`.
+///
+/// * `%f` and `%u` are replaced by a single file or URL.  If there are more,
+///   there's a command line for each of them.
+/// * `%F` and `%U` are replaced by all the files or URLs, each as its own
+///   argument.  They have to be arguments on their own.
+/// * `%i` is replaced by `--icon` and the `Icon` of `entry`, if it has one.
+///   It has to be an argument on its own.
+/// * `%c` is replaced by the `Name` of `entry` in the preferred locale.
+/// * `%k` is replaced by `location`, the path or URI of the entry file.
+/// * `%%` is replaced by `%`.
+///
+/// Other field codes, including the deprecated ones, are removed.  An
+/// argument that's left empty by removing its field codes is removed as
+/// well.  There's always at least one command line.
+///
+/// Example:
+/// ```
+/// use freedesktop_entry_parser::{exec::{expand, split}, Entry};
+///
+/// let entry = Entry::parse(&b"[Desktop Entry]\nName=Viewer\nExec=viewer %f"[..])?;
+/// let desktop_entry = entry.desktop_entry();
+/// let args = split(&desktop_entry.exec().unwrap())?;
+/// assert_eq!(
+///     expand(&args, &["a.png", "b.png"], &desktop_entry, None),
+///     [["viewer", "a.png"], ["viewer", "b.png"]]
+/// );
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn expand(
+    args: &[String],
+    files: &[&str],
+    entry: &DesktopEntry<'_>,
+    location: Option<&str>,
+) -> Vec<Vec<String>> {
+    let context = Context {
+        name: entry.localized_name(),
+        icon: entry.icon(),
+        location,
+    };
+    let single = args.iter().any(|arg| has_single_file_code(arg));
+    if single && files.len() > 1 {
+        files
+            .iter()
+            .map(|file| context.expand(args, std::slice::from_ref(file)))
+            .collect()
+    } else {
+        vec![context.expand(args, files)]
+    }
+}
+
+impl<'a> DesktopEntry<'a> {
+    /// Build the command to open `files` with this entry, from `Exec`,
+    /// `Path` and `Terminal`.  A `Link` entry opens its `URL` with
+    /// `xdg-open` instead and ignores `files`, see
+    /// [`link_command`](#method.link_command).
+    ///
+    /// If `Exec` can only open a single file, with `%f` or `%u`, only the
+    /// first of `files` is used.  Use [`commands`](#method.commands) to get
+    /// a command for each of them.
+    ///
+    /// Example:
+    /// ```no_run
+    /// use freedesktop_entry_parser::parse_entry;
+    ///
+    /// let entry = parse_entry("/usr/share/applications/firefox.desktop")?;
+    /// entry
+    ///     .desktop_entry()
+    ///     .command(&["https://example.com"])?
+    ///     .spawn()?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn command(&self, files: &[&str]) -> Result<Command, ExecError> {
+        self.command_with_terminal(files, &DefaultTerminal)
+    }
+
+    /// [`command`](#method.command) with `Terminal=true` entries run by
+    /// `terminal`.
+    pub fn command_with_terminal(
+        &self,
+        files: &[&str],
+        terminal: &dyn TerminalLauncher,
+    ) -> Result<Command, ExecError> {
+        let files = match files.first() {
+            Some(file) if self.single_file()? => std::slice::from_ref(file),
+            _ => files,
+        };
+        let mut commands = self.commands_with_terminal(files, terminal)?;
+        Ok(commands.remove(0))
+    }
+
+    /// Build the commands to open `files` with this entry, one for each
+    /// command line from [`expand`](exec/fn.expand.html).
+    ///
+    /// A command with `Terminal=true` is run in a terminal emulator by
+    /// [`DefaultTerminal`](exec/struct.DefaultTerminal.html).  The working
+    /// directory is set to `Path` if it's there.  `%k` is replaced by the
+    /// [`location`](#method.with_location) of the entry, or removed if it
+    /// isn't set.
+    pub fn commands(&self, files: &[&str]) -> Result<Vec<Command>, ExecError> {
+        self.commands_with_terminal(files, &DefaultTerminal)
+    }
+
+    /// [`commands`](#method.commands) with `Terminal=true` entries run by
+    /// `terminal`.
+    pub fn commands_with_terminal(
+        &self,
+        files: &[&str],
+        terminal: &dyn TerminalLauncher,
+    ) -> Result<Vec<Command>, ExecError> {
+        if self.entry_type() == Some(DesktopEntryType::Link) {
+            return Ok(vec![self.link_command("xdg-open")?]);
+        }
+        let args = split(&self.exec().ok_or(ExecError::MissingExec)?)?;
+        let path = self.path();
+        let mut commands = Vec::new();
+        for args in expand(&args, files, self, self.location()) {
+            let (program, args) = args.split_first().ok_or(ExecError::Empty)?;
+            let mut command = if self.terminal() {
+                terminal.command(program, args)
+            } else {
+                let mut command = Command::new(program);
+                command.args(args);
+                command
+            };
+            if let Some(path) = &path {
+                command.current_dir(&**path);
+            }
+            commands.push(command);
+        }
+        Ok(commands)
+    }
+
+    /// Build the command to open the `URL` of a `Link` entry with `opener`,
+    /// like `xdg-open` or a browser, which gets the URL as its only
+    /// argument.
+    pub fn link_command(&self, opener: &str) -> Result<Command, ExecError> {
+        let url = self.url().ok_or(ExecError::MissingUrl)?;
+        let mut command = Command::new(opener);
+        command.arg(&*url);
+        Ok(command)
+    }
+
+    /// Whether `Exec` opens a single file at a time
+    fn single_file(&self) -> Result<bool, ExecError> {
+        if self.entry_type() == Some(DesktopEntryType::Link) {
+            return Ok(false);
+        }
+        let exec = self.exec().ok_or(ExecError::MissingExec)?;
+        Ok(has_single_file_code(&exec))
+    }
+}
+
+/// How [`DesktopEntry::commands`](../struct.DesktopEntry.html#method.commands)
+/// runs entries with `Terminal=true` in a terminal emulator.
+///
+/// It's implemented for closures, so a fixed terminal can be used like
+/// this:
+/// ```
+/// use freedesktop_entry_parser::Entry;
+/// use std::process::Command;
+///
+/// let entry =
+///     Entry::parse(&b"[Desktop Entry]\nExec=htop\nTerminal=true"[..])?;
+/// let kitty = |program: &str, args: &[String]| {
+///     let mut command = Command::new("kitty");
+///     command.arg(program).args(args);
+///     command
+/// };
+/// let command = entry.desktop_entry().command_with_terminal(&[], &kitty)?;
+/// assert_eq!(command.get_program(), "kitty");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub trait TerminalLauncher {
+    /// Build the command running `program` with `args` in a terminal
+    fn command(&self, program: &str, args: &[String]) -> Command;
+}
+
+impl<F> TerminalLauncher for F
+where
+    F: Fn(&str, &[String]) -> Command,
+{
+    fn command(&self, program: &str, args: &[String]) -> Command {
+        self(program, args)
+    }
+}
+
+/// The terminal emulators [`DefaultTerminal`](struct.DefaultTerminal.html)
+/// falls back to and the option that runs a command in them
+const FALLBACK_TERMINALS: &[(&str, &str)] = &[
+    ("x-terminal-emulator", "-e"),
+    ("gnome-terminal", "--"),
+    ("konsole", "-e"),
+    ("xfce4-terminal", "-x"),
+    ("alacritty", "-e"),
+    ("xterm", "-e"),
+];
+
+/// The [`TerminalLauncher`](trait.TerminalLauncher.html) used by default.
+///
+/// Uses the first of:
+/// - `xdg-terminal-exec`, which picks the user's preferred terminal, if it's
+///   in `PATH`
+/// - the terminal in the `TERMINAL` environment variable, with `-e`
+/// - the first of some common terminals in `PATH`, starting with
+///   `x-terminal-emulator`
+/// - `xterm -e`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DefaultTerminal;
+
+impl TerminalLauncher for DefaultTerminal {
+    fn command(&self, program: &str, args: &[String]) -> Command {
+        let mut command = if find_executable("xdg-terminal-exec") {
+            Command::new("xdg-terminal-exec")
+        } else if let Some(terminal) = env::var("TERMINAL")
+            .ok()
+            .filter(|terminal| !terminal.is_empty())
+        {
+            let mut command = Command::new(terminal);
+            command.arg("-e");
+            command
+        } else {
+            let (terminal, option) = FALLBACK_TERMINALS
+                .iter()
+                .find(|(terminal, _)| find_executable(terminal))
+                .unwrap_or(&("xterm", "-e"));
+            let mut command = Command::new(terminal);
+            command.arg(option);
+            command
+        };
+        command.arg(program).args(args);
+        command
+    }
+}
+
+/// What the field codes other than the file ones are replaced with
+struct Context<'a> {
+    name: Option<&'a str>,
+    icon: Option<&'a str>,
+    location: Option<&'a str>,
+}
+
+impl<'a> Context<'a> {
+    /// Expand the field codes of a single command line.
+    fn expand(&self, args: &[String], files: &[&str]) -> Vec<String> {
+        let mut expanded = Vec::with_capacity(args.len() + files.len());
+        for arg in args {
+            match arg.as_str() {
+                "%F" | "%U" => {
+                    expanded.extend(files.iter().map(|f| f.to_string()));
+                    continue;
+                }
+                "%i" => {
+                    if let Some(icon) = self.icon {
+                        expanded.push("--icon".to_owned());
+                        expanded.push(icon.to_owned());
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+            let mut value = String::with_capacity(arg.len());
+            let mut chars = arg.chars();
+            while let Some(c) = chars.next() {
+                if c != '%' {
+                    value.push(c);
+                    continue;
+                }
+                match chars.next() {
+                    Some('%') => value.push('%'),
+                    Some('f') | Some('u') => {
+                        value.push_str(files.first().copied().unwrap_or(""))
+                    }
+                    Some('c') => value.push_str(self.name.unwrap_or("")),
+                    Some('k') => value.push_str(self.location.unwrap_or("")),
+                    _ => {}
+                }
+            }
+            if !value.is_empty() || arg.is_empty() {
+                expanded.push(value);
+            }
+        }
+        expanded
+    }
+}
+
+/// Whether `arg` has a `%f` or `%u`
+fn has_single_file_code(arg: &str) -> bool {
+    let mut chars = arg.chars();
+    while let Some(c) = chars.next() {
+        if c == '%' && matches!(chars.next(), Some('f') | Some('u')) {
+            return true;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn split_args() {
+        assert_eq!(split("firefox %u").unwrap(), ["firefox", "%u"]);
+        assert_eq!(split("  a\t b  ").unwrap(), ["a", "b"]);
+        assert_eq!(
+            split(r#""a b"c "" "\$HOME \` \\ \x""#).unwrap(),
+            ["a bc", "", r"$HOME ` \ \x"]
+        );
+        assert_eq!(split(r"a\ b c\").unwrap(), ["a b", "c"]);
+    }
+
+    #[test]
+    fn field_codes() {
+        let entry = crate::Entry::parse(
+            &b"[Desktop Entry]\nName=App\nName[de]=Anwendung\nIcon=app"[..],
+        )
+        .unwrap();
+        let entry = entry
+            .desktop_entry()
+            .with_locales(vec!["de".parse().unwrap()]);
+        let expand = |exec: &str, files: &[&str]| {
+            expand(&split(exec).unwrap(), files, &entry, Some("/app.desktop"))
+        };
+        assert_eq!(
+            expand("app %i --title=%c %k 100%% %d \"\" %F", &["a", "b"]),
+            [[
+                "app",
+                "--icon",
+                "app",
+                "--title=Anwendung",
+                "/app.desktop",
+                "100%",
+                "",
+                "a",
+                "b"
+            ]]
+        );
+        assert_eq!(
+            expand("app --file=%u %U", &["a", "b"]),
+            [["app", "--file=a", "a"], ["app", "--file=b", "b"]]
+        );
+        assert_eq!(expand("app %f", &[]), [["app"]]);
+        assert_eq!(expand("app %F --x", &[]), [["app", "--x"]]);
+    }
+
+    #[test]
+    fn command() {
+        let entry = crate::Entry::parse(
+            &b"[Desktop Entry]\nExec=viewer --x %f\nPath=/tmp"[..],
+        )
+        .unwrap();
+        let desktop_entry = entry.desktop_entry();
+        let command = desktop_entry.command(&["a", "b"]).unwrap();
+        assert_eq!(command.get_program(), "viewer");
+        let args: Vec<_> = command.get_args().collect();
+        assert_eq!(args, ["--x", "a"]);
+        assert_eq!(command.get_current_dir(), Some("/tmp".as_ref()));
+        assert_eq!(desktop_entry.commands(&["a", "b"]).unwrap().len(), 2);
+
+        let entry =
+            crate::Entry::parse(&b"[Desktop Entry]\nExec=viewer %k"[..])
+                .unwrap();
+        let args = |desktop_entry: crate::DesktopEntry| {
+            let command = desktop_entry.command(&[]).unwrap();
+            command
+                .get_args()
+                .map(|arg| arg.to_str().unwrap().to_owned())
+                .collect::<Vec<_>>()
+        };
+        assert!(args(entry.desktop_entry()).is_empty());
+        let location = "/usr/share/applications/viewer.desktop";
+        assert_eq!(
+            args(entry.desktop_entry().with_location(location)),
+            [location]
+        );
+
+        let entry =
+            crate::Entry::parse(&b"[Desktop Entry]\nName=Foo"[..]).unwrap();
+        assert_eq!(
+            entry.desktop_entry().command(&[]).unwrap_err(),
+            ExecError::MissingExec
+        );
+        let entry =
+            crate::Entry::parse(&b"[Desktop Entry]\nExec=%f"[..]).unwrap();
+        assert_eq!(
+            entry.desktop_entry().command(&[]).unwrap_err(),
+            ExecError::Empty
+        );
+    }
+
+    #[test]
+    fn terminal() {
+        let entry = crate::Entry::parse(
+            &b"[Desktop Entry]\nExec=htop -d %f\nTerminal=true\nPath=/tmp"[..],
+        )
+        .unwrap();
+        let terminal = |program: &str, args: &[String]| {
+            let mut command = Command::new("term");
+            command.arg("--").arg(program).args(args);
+            command
+        };
+        let command = entry
+            .desktop_entry()
+            .command_with_terminal(&["a", "b"], &terminal)
+            .unwrap();
+        assert_eq!(command.get_program(), "term");
+        let args: Vec<_> = command.get_args().collect();
+        assert_eq!(args, ["--", "htop", "-d", "a"]);
+        assert_eq!(command.get_current_dir(), Some("/tmp".as_ref()));
+    }
+
+    #[test]
+    fn link_command() {
+        let entry = crate::Entry::parse(
+            &b"[Desktop Entry]\nType=Link\nURL=https://example.com/a\\sb"[..],
+        )
+        .unwrap();
+        let desktop_entry = entry.desktop_entry();
+        let command = desktop_entry.command(&["a"]).unwrap();
+        assert_eq!(command.get_program(), "xdg-open");
+        let args: Vec<_> = command.get_args().collect();
+        assert_eq!(args, ["https://example.com/a b"]);
+        let command = desktop_entry.link_command("firefox").unwrap();
+        assert_eq!(command.get_program(), "firefox");
+
+        let entry =
+            crate::Entry::parse(&b"[Desktop Entry]\nType=Link"[..]).unwrap();
+        assert_eq!(
+            entry.desktop_entry().command(&[]).unwrap_err(),
+            ExecError::MissingUrl
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            split(r#"a "b"#),
+            Err(ExecError::UnterminatedQuote {
+                exec: r#"a "b"#.to_owned()
+            })
+        );
+        assert_eq!(
+            split(r#""a\""#),
+            Err(ExecError::UnterminatedQuote {
+                exec: r#""a\""#.to_owned()
+            })
+        );
+        assert_eq!(split("  "), Err(ExecError::Empty));
+    }
+}
@@ -0,0 +1,119 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::{internal::AttrNamesIter, AttrMap, AttrSelector};
+
+/// An extension key, one starting with `X-`, like
+/// `X-GNOME-UsesNotifications`.
+///
+/// Created by [`AttrSelector::extensions`](struct.AttrSelector.html#method.extensions)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Extension<'a> {
+    /// The whole key, `X-GNOME-UsesNotifications`
+    pub key: &'a str,
+    /// The vendor, `GNOME`, if the key has one
+    pub vendor: Option<&'a str>,
+    /// The key without `X-` and the vendor, `UsesNotifications`
+    pub name: &'a str,
+    /// The value without a param, if there is one
+    pub value: Option<&'a str>,
+}
+
+impl<'a, T: AsRef<str>> AttrSelector<'a, T> {
+    /// Get the value of the extension key `key`.  The `X-` prefix is
+    /// optional, so `extension("GNOME-UsesNotifications")` gets
+    /// `X-GNOME-UsesNotifications`.
+    pub fn extension(&self, key: impl AsRef<str>) -> Option<&'a str> {
+        let key = key.as_ref();
+        if key.starts_with("X-") {
+            self.attr(key)
+        } else {
+            self.attr(format!("X-{}", key))
+        }
+    }
+
+    /// Iterator over the extension keys of this section, the ones starting
+    /// with `X-`.
+    ///
+    /// Example:
+    /// ```
+    /// use freedesktop_entry_parser::Entry;
+    ///
+    /// let entry = Entry::parse(
+    ///     &b"[Desktop Entry]\nName=Foo\nX-GNOME-UsesNotifications=true"[..],
+    /// )?;
+    /// let section = entry.section("Desktop Entry");
+    /// let extension = section.extensions().next().unwrap();
+    /// assert_eq!(extension.vendor, Some("GNOME"));
+    /// assert_eq!(extension.name, "UsesNotifications");
+    /// assert_eq!(extension.value, Some("true"));
+    /// # Ok::<(), freedesktop_entry_parser::ParseError>(())
+    /// ```
+    pub fn extensions(&self) -> ExtensionIter<'a> {
+        ExtensionIter {
+            iter: self.section.map(AttrMap::attr_names_iter),
+            section: self.section,
+        }
+    }
+}
+
+/// Iterator over the extension keys of a section.
+///
+/// Created by [`AttrSelector::extensions`](struct.AttrSelector.html#method.extensions)
+pub struct ExtensionIter<'a> {
+    iter: Option<AttrNamesIter<'a>>,
+    section: Option<&'a AttrMap<'a>>,
+}
+
+impl<'a> Iterator for ExtensionIter<'a> {
+    type Item = Extension<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let section = self.section?;
+        let iter = self.iter.as_mut()?;
+        iter.find_map(|key| {
+            let rest = key.strip_prefix("X-")?;
+            let (vendor, name) = match rest.split_once('-') {
+                Some((vendor, name)) => (Some(vendor), name),
+                None => (None, rest),
+            };
+            Some(Extension {
+                key,
+                vendor,
+                name,
+                value: section.get_attr(key)?.get_value(),
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Entry;
+
+    #[test]
+    fn extensions() {
+        let entry = Entry::parse(
+            &b"[A]\nX-KDE-Foo-Bar=1\nName=x\nX-Plain=2\nX-Loc[de]=3"[..],
+        )
+        .unwrap();
+        let section = entry.section("A");
+        assert_eq!(section.extension("X-KDE-Foo-Bar"), Some("1"));
+        assert_eq!(section.extension("Plain"), Some("2"));
+        assert_eq!(section.extension("Name"), None);
+        let extensions: Vec<_> = section
+            .extensions()
+            .map(|e| (e.key, e.vendor, e.name, e.value))
+            .collect();
+        assert_eq!(
+            extensions,
+            [
+                ("X-KDE-Foo-Bar", Some("KDE"), "Foo-Bar", Some("1")),
+                ("X-Plain", None, "Plain", Some("2")),
+                ("X-Loc", None, "Loc", None),
+            ]
+        );
+        assert_eq!(entry.section("B").extensions().count(), 0);
+    }
+}
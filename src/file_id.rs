@@ -0,0 +1,203 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::{
+    env,
+    path::{Component, Path, PathBuf},
+};
+
+/// The desktop file ID of the file at `path` in `applications_dir`, like
+/// `org-example-viewer.desktop` for `org/example-viewer.desktop`.  Returns
+/// `None` if `path` isn't in `applications_dir` or isn't valid UTF-8.
+///
+/// IDs are how other specs, like `mimeapps.list` and D-Bus activation, refer
+/// to entries.
+///
+/// Example:
+/// ```
+/// use freedesktop_entry_parser::desktop_file_id;
+///
+/// let id = desktop_file_id(
+///     "/usr/share/applications/kde4/konsole.desktop",
+///     "/usr/share/applications",
+/// );
+/// assert_eq!(id.as_deref(), Some("kde4-konsole.desktop"));
+/// ```
+pub fn desktop_file_id(
+    path: impl AsRef<Path>,
+    applications_dir: impl AsRef<Path>,
+) -> Option<String> {
+    let relative = path.as_ref().strip_prefix(applications_dir).ok()?;
+    let mut id = String::new();
+    for component in relative.components() {
+        let part = match component {
+            Component::Normal(part) => part.to_str()?,
+            _ => return None,
+        };
+        if !id.is_empty() {
+            id.push('-');
+        }
+        id.push_str(part);
+    }
+    if id.is_empty() {
+        None
+    } else {
+        Some(id)
+    }
+}
+
+/// The `applications` directories entries are installed in, from the most
+/// to the least important: the one in `XDG_DATA_HOME` followed by the ones
+/// in `XDG_DATA_DIRS`.
+pub fn applications_dirs() -> Vec<PathBuf> {
+    data_home()
+        .into_iter()
+        .chain(data_dirs())
+        .map(|dir| dir.join("applications"))
+        .collect()
+}
+
+/// `XDG_DATA_HOME`, defaulting to `~/.local/share`.
+pub(crate) fn data_home() -> Option<PathBuf> {
+    env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            env::var_os("HOME")
+                .map(|home| Path::new(&home).join(".local/share"))
+        })
+}
+
+/// `XDG_DATA_DIRS`, defaulting to `/usr/local/share` and `/usr/share`.
+pub(crate) fn data_dirs() -> Vec<PathBuf> {
+    let data_dirs = env::var_os("XDG_DATA_DIRS")
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".into());
+    env::split_paths(&data_dirs).collect()
+}
+
+/// Find the file of the entry with desktop file `id` in the
+/// [`applications_dirs`](fn.applications_dirs.html).  The reverse of
+/// [`desktop_file_id`](fn.desktop_file_id.html).
+pub fn find_desktop_file(id: &str) -> Option<PathBuf> {
+    find_desktop_file_in(&applications_dirs(), id)
+}
+
+/// Find the file of the entry with desktop file `id` in `dirs`, the first
+/// one that has it wins.
+///
+/// As a `-` in an ID can be either a `-` or a `/` in the path, every
+/// subdirectory the ID could refer to is searched.
+pub fn find_desktop_file_in(
+    dirs: &[impl AsRef<Path>],
+    id: &str,
+) -> Option<PathBuf> {
+    dirs.iter().find_map(|dir| find_in(dir.as_ref(), id))
+}
+
+fn find_in(dir: &Path, id: &str) -> Option<PathBuf> {
+    let path = dir.join(id);
+    if path.is_file() {
+        return Some(path);
+    }
+    id.match_indices('-').find_map(|(i, _)| {
+        let subdir = dir.join(&id[..i]);
+        if subdir.is_dir() {
+            find_in(&subdir, &id[i + 1..])
+        } else {
+            None
+        }
+    })
+}
+
+/// The D-Bus bus name of a `DBusActivatable` entry with desktop file `id`,
+/// the ID without `.desktop`.  Returns `None` if that isn't a valid
+/// well-known bus name, which the spec requires for these IDs.
+///
+/// Example:
+/// ```
+/// use freedesktop_entry_parser::{dbus_bus_name, dbus_object_path};
+///
+/// let id = "org.example.Foo-Bar.desktop";
+/// assert_eq!(dbus_bus_name(id), Some("org.example.Foo-Bar"));
+/// assert_eq!(dbus_object_path(id).as_deref(), Some("/org/example/Foo_Bar"));
+/// assert_eq!(dbus_bus_name("firefox.desktop"), None);
+/// ```
+pub fn dbus_bus_name(id: &str) -> Option<&str> {
+    let name = id.strip_suffix(".desktop")?;
+    let valid_element = |element: &str| {
+        !element.is_empty()
+            && !element.starts_with(|c: char| c.is_ascii_digit())
+            && element
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    };
+    if name.len() <= 255
+        && name.contains('.')
+        && name.split('.').all(valid_element)
+    {
+        Some(name)
+    } else {
+        None
+    }
+}
+
+/// The D-Bus object path of a `DBusActivatable` entry with desktop file
+/// `id`: its [bus name](fn.dbus_bus_name.html) with `.` replaced by `/` and
+/// `-` by `_`, after a leading `/`.
+pub fn dbus_object_path(id: &str) -> Option<String> {
+    let name = dbus_bus_name(id)?;
+    let mut path = String::with_capacity(name.len() + 1);
+    path.push('/');
+    path.extend(name.chars().map(|c| match c {
+        '.' => '/',
+        '-' => '_',
+        c => c,
+    }));
+    Some(path)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn file_id() {
+        let id = |path: &str| desktop_file_id(path, "/usr/share/applications");
+        assert_eq!(
+            id("/usr/share/applications/firefox.desktop").as_deref(),
+            Some("firefox.desktop")
+        );
+        assert_eq!(id("/usr/share/firefox.desktop"), None);
+        assert_eq!(id("/usr/share/applications"), None);
+    }
+
+    #[test]
+    fn find() {
+        let dirs = ["./test_data/missing", "./test_data/applications"];
+        let path = find_desktop_file_in(&dirs, "org-example-viewer.desktop");
+        assert_eq!(
+            path.as_deref(),
+            Some(Path::new(
+                "./test_data/applications/org/example-viewer.desktop"
+            ))
+        );
+        let id = desktop_file_id(path.unwrap(), dirs[1]);
+        assert_eq!(id.as_deref(), Some("org-example-viewer.desktop"));
+        assert_eq!(find_desktop_file_in(&dirs, "org-example.desktop"), None);
+    }
+
+    #[test]
+    fn dbus_names() {
+        let id = "org.example.Foo-Bar.desktop";
+        assert_eq!(dbus_bus_name(id), Some("org.example.Foo-Bar"));
+        assert_eq!(
+            dbus_object_path(id).as_deref(),
+            Some("/org/example/Foo_Bar")
+        );
+        for id in &["org.example.Foo", "org..Foo.desktop", "org.1x.desktop"] {
+            assert_eq!(dbus_bus_name(id), None);
+        }
+    }
+}
@@ -0,0 +1,290 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::{
+    escape,
+    icon_theme::{
+        find_icon_with, icon_dirs, theme_chain_with, FoundIcon, Source,
+    },
+    install::write_atomic,
+    Entry,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Version of the cache file format, files with another one are ignored
+const VERSION: &str = "1";
+
+/// A cache of the `index.theme` files and directory listings used to look
+/// up icons, which can be saved to a file and loaded on the next start.
+///
+/// Every cached file and directory is checked against its modification
+/// time when it's used, so the cache never returns stale results: changed
+/// files are read again and directories listed again.  That's a single
+/// `stat` instead of reading every `index.theme` and checking every icon
+/// file.
+///
+/// Example:
+/// ```no_run
+/// use freedesktop_entry_parser::icon_theme::IconCache;
+///
+/// let mut cache = IconCache::open("/home/me/.cache/my-app/icons");
+/// let path = cache.find_icon("firefox", 48, 1, "Adwaita");
+/// cache.save()?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct IconCache {
+    path: PathBuf,
+    base_dirs: Vec<PathBuf>,
+    indexes: HashMap<PathBuf, CachedIndex>,
+    dirs: HashMap<PathBuf, CachedDir>,
+}
+
+#[derive(Debug)]
+struct CachedIndex {
+    modified: SystemTime,
+    contents: String,
+}
+
+#[derive(Debug)]
+struct CachedDir {
+    modified: SystemTime,
+    files: HashSet<String>,
+}
+
+impl IconCache {
+    /// Load the cache saved at `path` with [`save`](#method.save), looking
+    /// up icons in the [`icon_dirs`](fn.icon_dirs.html).  The cache starts
+    /// out empty if the file is missing or can't be read.
+    pub fn open(path: impl Into<PathBuf>) -> Self {
+        let mut cache = IconCache {
+            path: path.into(),
+            base_dirs: icon_dirs(),
+            indexes: HashMap::new(),
+            dirs: HashMap::new(),
+        };
+        if let Ok(entry) = Entry::parse_file(&cache.path) {
+            cache.load(&entry);
+        }
+        cache
+    }
+
+    /// Look up icons in `base_dirs` instead of the
+    /// [`icon_dirs`](fn.icon_dirs.html).
+    pub fn with_dirs(mut self, base_dirs: Vec<PathBuf>) -> Self {
+        self.base_dirs = base_dirs;
+        self
+    }
+
+    /// [`find_icon`](fn.find_icon.html) using the cache
+    pub fn find_icon(
+        &mut self,
+        icon: &str,
+        size: u32,
+        scale: u32,
+        theme: &str,
+    ) -> Option<PathBuf> {
+        self.find_icon_match(icon, size, scale, theme)
+            .map(|icon| icon.path)
+    }
+
+    /// [`find_icon_match`](fn.find_icon_match.html) using the cache
+    pub fn find_icon_match(
+        &mut self,
+        icon: &str,
+        size: u32,
+        scale: u32,
+        theme: &str,
+    ) -> Option<FoundIcon> {
+        let base_dirs = self.base_dirs.clone();
+        find_icon_with(self, &base_dirs, icon, size, scale, theme)
+    }
+
+    /// [`theme_chain`](fn.theme_chain.html) using the cache
+    pub fn theme_chain(&mut self, theme: &str) -> Vec<String> {
+        let base_dirs = self.base_dirs.clone();
+        theme_chain_with(self, &base_dirs, theme)
+    }
+
+    /// Save the cache to the file it was opened from, creating its
+    /// directory if needed.
+    pub fn save(&self) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        write_atomic(&self.path, |writer| self.write(writer))
+    }
+
+    /// Write the cache as a keyfile, with a section for each file and
+    /// directory.  Paths that aren't valid UTF-8 aren't saved.
+    fn write(&self, mut writer: impl Write) -> io::Result<()> {
+        writeln!(writer, "[Icon Cache]\nVersion={}", VERSION)?;
+        let indexes = self
+            .indexes
+            .iter()
+            .filter_map(|(path, index)| Some((path.to_str()?, index)));
+        for (i, (path, index)) in indexes.enumerate() {
+            writeln!(writer, "\n[Index {}]", i)?;
+            writeln!(writer, "Path={}", escape(path))?;
+            writeln!(writer, "Modified={}", format_time(index.modified))?;
+            writeln!(writer, "Contents={}", escape(&index.contents))?;
+        }
+        let dirs = self
+            .dirs
+            .iter()
+            .filter_map(|(path, dir)| Some((path.to_str()?, dir)));
+        for (i, (path, dir)) in dirs.enumerate() {
+            writeln!(writer, "\n[Directory {}]", i)?;
+            writeln!(writer, "Path={}", escape(path))?;
+            writeln!(writer, "Modified={}", format_time(dir.modified))?;
+            write!(writer, "Files=")?;
+            for file in &dir.files {
+                write!(writer, "{};", escape(file).replace(';', "\\;"))?;
+            }
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+
+    /// Read the sections written by [`write`](#method.write), skipping
+    /// invalid ones.
+    fn load(&mut self, entry: &Entry) {
+        if entry.section("Icon Cache").attr("Version") != Some(VERSION) {
+            return;
+        }
+        for section in entry.sections() {
+            let path = match section.attr_unescaped("Path") {
+                Some(path) => PathBuf::from(path.into_owned()),
+                None => continue,
+            };
+            let modified = match section.attr("Modified").and_then(parse_time) {
+                Some(modified) => modified,
+                None => continue,
+            };
+            if section.name().starts_with("Index ") {
+                if let Some(contents) = section.attr_unescaped("Contents") {
+                    let contents = contents.into_owned();
+                    self.indexes
+                        .insert(path, CachedIndex { modified, contents });
+                }
+            } else if section.name().starts_with("Directory ") {
+                let files = section
+                    .attr_list("Files")
+                    .map(|file| file.into_owned())
+                    .collect();
+                self.dirs.insert(path, CachedDir { modified, files });
+            }
+        }
+    }
+}
+
+impl Source for IconCache {
+    fn index(&mut self, path: &Path) -> Option<Entry> {
+        let modified = match modified(path) {
+            Some(modified) => modified,
+            None => {
+                self.indexes.remove(path);
+                return None;
+            }
+        };
+        let cached = self.indexes.get(path);
+        if cached.map(|index| index.modified) != Some(modified) {
+            let contents = fs::read_to_string(path).ok()?;
+            self.indexes
+                .insert(path.to_owned(), CachedIndex { modified, contents });
+        }
+        Entry::parse(self.indexes[path].contents.as_bytes()).ok()
+    }
+
+    fn has_file(&mut self, dir: &Path, name: &str) -> bool {
+        let modified = match modified(dir) {
+            Some(modified) => modified,
+            None => {
+                self.dirs.remove(dir);
+                return false;
+            }
+        };
+        let cached = self.dirs.get(dir);
+        if cached.map(|dir| dir.modified) != Some(modified) {
+            let files = match fs::read_dir(dir) {
+                Ok(files) => files
+                    .flatten()
+                    .filter_map(|file| file.file_name().into_string().ok())
+                    .collect(),
+                Err(_) => return false,
+            };
+            self.dirs
+                .insert(dir.to_owned(), CachedDir { modified, files });
+        }
+        self.dirs[dir].files.contains(name)
+    }
+}
+
+/// When the file or directory at `path` was last modified, `None` if it
+/// doesn't exist
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+/// `time` as seconds and nanoseconds since the epoch, like
+/// `1700000000.000000001`
+fn format_time(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    format!(
+        "{}.{:09}",
+        since_epoch.as_secs(),
+        since_epoch.subsec_nanos()
+    )
+}
+
+fn parse_time(s: &str) -> Option<SystemTime> {
+    let mut parts = s.splitn(2, '.');
+    let secs = parts.next()?.parse().ok()?;
+    let nanos = parts.next()?.parse().ok()?;
+    UNIX_EPOCH.checked_add(Duration::new(secs, nanos))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::process;
+
+    #[test]
+    fn round_trip() {
+        let dir = std::env::temp_dir()
+            .join(format!("freedesktop-entry-icon-cache-{}", process::id()));
+        let path = dir.join("icons.cache");
+        let base_dirs = vec![PathBuf::from("./test_data/icons")];
+        let expected =
+            Some(PathBuf::from("./test_data/icons/Foo/48x48/apps/viewer.png"));
+
+        let mut cache = IconCache::open(&path).with_dirs(base_dirs.clone());
+        assert_eq!(cache.find_icon("viewer", 48, 1, "Bar"), expected);
+        assert_eq!(cache.theme_chain("Bar"), ["Bar", "Foo", "hicolor"]);
+        cache.save().unwrap();
+
+        let mut cache = IconCache::open(&path).with_dirs(base_dirs);
+        assert_eq!(cache.indexes.len(), 2);
+        assert!(cache
+            .dirs
+            .values()
+            .any(|dir| dir.files.contains("viewer.png")));
+        assert_eq!(cache.find_icon("viewer", 48, 1, "Bar"), expected);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn time() {
+        let time = UNIX_EPOCH + Duration::new(1_700_000_000, 1);
+        assert_eq!(format_time(time), "1700000000.000000001");
+        assert_eq!(parse_time(&format_time(time)), Some(time));
+        assert_eq!(parse_time("17"), None);
+    }
+}
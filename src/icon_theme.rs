@@ -0,0 +1,1389 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Icon theme `index.theme` files from the [Icon Theme
+//! spec](https://specifications.freedesktop.org/icon-theme-spec/icon-theme-spec-latest.html).
+//!
+//! Example:
+//! ```
+//! use freedesktop_entry_parser::Entry;
+//!
+//! let entry = Entry::parse(
+//!     &b"[Icon Theme]\nName=Adwaita\nComment=The default theme\n\
+//!        Inherits=hicolor\nDirectories=16x16/apps,scalable/apps"[..],
+//! )?;
+//! let theme = entry.icon_theme();
+//! theme.check()?;
+//! assert_eq!(theme.name(), Some("Adwaita"));
+//! assert_eq!(theme.directories(), ["16x16/apps", "scalable/apps"]);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! Cursor themes use the same format, see
+//! [`CursorTheme`](struct.CursorTheme.html) and
+//! [`find_cursor`](fn.find_cursor.html).
+//!
+//! [`find_icon`](fn.find_icon.html) looks up icons in the installed themes
+//! as the spec describes, following the theme's `Inherits`:
+//! ```no_run
+//! use freedesktop_entry_parser::icon_theme::find_icon;
+//!
+//! if let Some(path) = find_icon("firefox", 48, 1, "Adwaita") {
+//!     println!("{}", path.display());
+//! }
+//! ```
+//!
+//! Programs that look up many icons on startup can keep an
+//! [`IconCache`](struct.IconCache.html) between runs instead.
+pub use crate::icon_cache::IconCache;
+use crate::{
+    file_id::{data_dirs, data_home},
+    validate::Finding,
+    AttrSelector, DesktopEntry, Entry, EntryError, EntryRef, Locale, Severity,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    convert::Infallible,
+    env,
+    fmt::{self, Display, Formatter},
+    fs,
+    ops::Deref,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// An icon theme found by [`installed_themes`](fn.installed_themes.html).
+#[derive(Debug)]
+pub struct InstalledTheme {
+    /// The internal name of the theme, which is the name of its directory
+    /// and what `Inherits` and [`find_icon`](fn.find_icon.html) use
+    pub name: String,
+    /// The path of its `index.theme`
+    pub path: PathBuf,
+    /// The parsed `index.theme`
+    pub entry: Entry,
+}
+
+impl InstalledTheme {
+    /// Get a typed view of the theme's `Icon Theme` section
+    pub fn icon_theme(&self) -> IconTheme<'_> {
+        self.entry.icon_theme()
+    }
+
+    /// The names of the icons with `context` in the theme's directory, see
+    /// [`IconTheme::icons_in_context`](struct.IconTheme.html#method.icons_in_context).
+    pub fn icons_in_context(&self, context: &Context) -> Vec<String> {
+        let dirs: Vec<&Path> = self.path.parent().into_iter().collect();
+        self.icon_theme().icons_in_context(context, &dirs)
+    }
+}
+
+/// An icon found by [`find_icon_match`](fn.find_icon_match.html).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FoundIcon {
+    /// The icon's file
+    pub path: PathBuf,
+    /// The `Size` of the directory the icon is in, `None` for icons that
+    /// aren't in a theme, like the ones in `/usr/share/pixmaps`
+    pub size: Option<u32>,
+    /// The `Scale` of the directory the icon is in, 1 for icons that
+    /// aren't in a theme
+    pub scale: u32,
+    /// Whether the icon is in a `Scalable` directory, or is an SVG outside
+    /// of a theme, so it can be rendered at any size
+    pub scalable: bool,
+}
+
+impl FoundIcon {
+    /// An icon that isn't in a theme
+    fn unthemed(path: PathBuf) -> Self {
+        let scalable = path.extension() == Some("svg".as_ref());
+        FoundIcon {
+            path,
+            size: None,
+            scale: 1,
+            scalable,
+        }
+    }
+}
+
+/// File extensions of icons, in the order they're looked up
+const EXTENSIONS: &[&str] = &["png", "svg", "xpm"];
+
+/// The theme every other theme falls back to
+const FALLBACK_THEME: &str = "hicolor";
+
+/// A typed view of the `Icon Theme` section of an `index.theme` file.
+/// Keys have the types and defaults from the spec and localized keys are
+/// looked up for the user's locales.
+///
+/// Derefs to [`AttrSelector`](../struct.AttrSelector.html) for everything
+/// else.  Created by
+/// [`EntryRef::icon_theme`](../struct.EntryRef.html#method.icon_theme)
+pub struct IconTheme<'a> {
+    entry: &'a EntryRef<'a>,
+    section: AttrSelector<'a, &'static str>,
+    locales: Vec<Locale>,
+}
+
+/// A typed view of the `Icon Theme` section of a cursor theme's
+/// `index.theme`, which only uses the keys about the theme itself.
+///
+/// Derefs to [`AttrSelector`](../struct.AttrSelector.html) for everything
+/// else.  Created by
+/// [`EntryRef::cursor_theme`](../struct.EntryRef.html#method.cursor_theme)
+pub struct CursorTheme<'a> {
+    section: AttrSelector<'a, &'static str>,
+}
+
+/// How the icons of an [`IconDirectory`](struct.IconDirectory.html) can be
+/// scaled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IconDirectoryType {
+    /// The icons can't be scaled
+    Fixed,
+    /// The icons can be scaled between `MinSize` and `MaxSize`
+    Scalable,
+    /// The icons can be used for sizes within `Threshold` of their size
+    Threshold,
+}
+
+/// The `Context` of an [`IconDirectory`](struct.IconDirectory.html), the
+/// kind of icons in it, from the [Icon Naming
+/// spec](https://specifications.freedesktop.org/icon-naming-spec/latest/).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Context {
+    /// Icons for actions in menus and toolbars, like `document-open`
+    Actions,
+    /// Animated images, like a spinner
+    Animations,
+    /// Icons of applications
+    Applications,
+    /// Icons of menu categories, like `applications-games`
+    Categories,
+    /// Icons of hardware devices, like `audio-card`
+    Devices,
+    /// Icons for tags and properties of files, like `emblem-important`
+    Emblems,
+    /// Emoticons, like `face-smile`
+    Emotes,
+    /// Icons of countries and languages, like flags
+    International,
+    /// Icons of file types, like `text-x-generic`
+    MimeTypes,
+    /// Icons of locations, like `folder`
+    Places,
+    /// Icons of states, like `battery-low`
+    Status,
+    /// Any other context
+    Other(String),
+}
+
+impl Context {
+    /// The context as it's written in the `Context` key
+    pub fn as_str(&self) -> &str {
+        match self {
+            Context::Actions => "Actions",
+            Context::Animations => "Animations",
+            Context::Applications => "Applications",
+            Context::Categories => "Categories",
+            Context::Devices => "Devices",
+            Context::Emblems => "Emblems",
+            Context::Emotes => "Emotes",
+            Context::International => "International",
+            Context::MimeTypes => "MimeTypes",
+            Context::Places => "Places",
+            Context::Status => "Status",
+            Context::Other(context) => context,
+        }
+    }
+}
+
+impl FromStr for Context {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "Actions" => Context::Actions,
+            "Animations" => Context::Animations,
+            "Applications" => Context::Applications,
+            "Categories" => Context::Categories,
+            "Devices" => Context::Devices,
+            "Emblems" => Context::Emblems,
+            "Emotes" => Context::Emotes,
+            "International" => Context::International,
+            "MimeTypes" => Context::MimeTypes,
+            "Places" => Context::Places,
+            "Status" => Context::Status,
+            context => Context::Other(context.to_owned()),
+        })
+    }
+}
+
+impl Display for Context {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A directory section of an `index.theme` file, with the defaults from
+/// the spec filled in.
+///
+/// Created by [`IconTheme::directory`](struct.IconTheme.html#method.directory)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IconDirectory<'a> {
+    /// The name of the section, which is the path of the directory in the
+    /// theme
+    pub name: &'a str,
+    /// `Size`, the nominal size of the icons
+    pub size: u32,
+    /// `Scale`, the scale the icons are for.  Defaults to 1.
+    pub scale: u32,
+    /// `Context`, the kind of icons in the directory, like `Applications`
+    pub context: Option<&'a str>,
+    /// `Type`.  Defaults to `Threshold`.
+    pub ty: IconDirectoryType,
+    /// `MinSize`, the smallest size `Scalable` icons can be used for.
+    /// Defaults to `size`.
+    pub min_size: u32,
+    /// `MaxSize`, the largest size `Scalable` icons can be used for.
+    /// Defaults to `size`.
+    pub max_size: u32,
+    /// `Threshold`, how far from `size` `Threshold` icons can be used.
+    /// Defaults to 2.
+    pub threshold: u32,
+}
+
+impl<'a> EntryRef<'a> {
+    /// Get a typed view of the `Icon Theme` section of an `index.theme`
+    /// file.  It uses the locales from
+    /// [`Locale::from_env`](struct.Locale.html#method.from_env) for its
+    /// localized keys.
+    pub fn icon_theme(&self) -> IconTheme<'_> {
+        IconTheme {
+            entry: self,
+            section: self.section("Icon Theme"),
+            locales: Locale::from_env(),
+        }
+    }
+}
+
+impl<'a> EntryRef<'a> {
+    /// Get a typed view of the `Icon Theme` section of a cursor theme's
+    /// `index.theme` file.
+    pub fn cursor_theme(&self) -> CursorTheme<'_> {
+        CursorTheme {
+            section: self.section("Icon Theme"),
+        }
+    }
+}
+
+impl<'a> CursorTheme<'a> {
+    /// `Name` without a locale
+    pub fn name(&self) -> Option<&'a str> {
+        self.section.attr("Name")
+    }
+
+    /// `Comment` without a locale
+    pub fn comment(&self) -> Option<&'a str> {
+        self.section.attr("Comment")
+    }
+
+    /// `Inherits`, the names of the themes to look in when a cursor isn't
+    /// in this one
+    pub fn inherits(&self) -> Vec<&'a str> {
+        comma_list(self.section.attr("Inherits"))
+    }
+}
+
+impl<'a> DesktopEntry<'a> {
+    /// Find the file of the entry's `Icon` for `size` at `scale` in
+    /// `theme`, see [`find_icon`](icon_theme/fn.find_icon.html).  An `Icon`
+    /// that's an absolute path is used as is.
+    pub fn icon_path(
+        &self,
+        size: u32,
+        scale: u32,
+        theme: &str,
+    ) -> Option<PathBuf> {
+        find_icon(self.icon()?, size, scale, theme)
+    }
+}
+
+impl<'a> Deref for CursorTheme<'a> {
+    type Target = AttrSelector<'a, &'static str>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.section
+    }
+}
+
+impl<'a> IconTheme<'a> {
+    /// Use `locales` instead of the ones from the environment.
+    pub fn with_locales(mut self, locales: Vec<Locale>) -> Self {
+        self.locales = locales;
+        self
+    }
+
+    /// Check that the theme has the keys the spec requires: `Name`,
+    /// `Comment` and `Directories`.  The error names the first missing
+    /// key.
+    pub fn check(&self) -> Result<(), EntryError> {
+        self.section.require("Name")?;
+        self.section.require("Comment")?;
+        self.section.require("Directories")?;
+        Ok(())
+    }
+
+    /// `Name` without a locale
+    pub fn name(&self) -> Option<&'a str> {
+        self.section.attr("Name")
+    }
+
+    /// The `Name` in the preferred locale
+    pub fn localized_name(&self) -> Option<&'a str> {
+        self.section.attr_preferred("Name", &self.locales)
+    }
+
+    /// `Comment` without a locale
+    pub fn comment(&self) -> Option<&'a str> {
+        self.section.attr("Comment")
+    }
+
+    /// The `Comment` in the preferred locale
+    pub fn localized_comment(&self) -> Option<&'a str> {
+        self.section.attr_preferred("Comment", &self.locales)
+    }
+
+    /// `Inherits`, the names of the themes to look in when an icon isn't in
+    /// this one
+    pub fn inherits(&self) -> Vec<&'a str> {
+        comma_list(self.section.attr("Inherits"))
+    }
+
+    /// `Directories`, the subdirectories of the theme that have icons
+    pub fn directories(&self) -> Vec<&'a str> {
+        comma_list(self.section.attr("Directories"))
+    }
+
+    /// `ScaledDirectories`, additional subdirectories for icons with a
+    /// `Scale` other than 1
+    pub fn scaled_directories(&self) -> Vec<&'a str> {
+        comma_list(self.section.attr("ScaledDirectories"))
+    }
+
+    /// `Hidden`, whether the theme is hidden from users choosing a theme.
+    /// Defaults to `false`.
+    pub fn hidden(&self) -> bool {
+        self.section.attr_bool("Hidden").unwrap_or(false)
+    }
+
+    /// `Example`, the name of an icon that represents the theme
+    pub fn example(&self) -> Option<&'a str> {
+        self.section.attr("Example")
+    }
+
+    /// The directory section `name`.  Returns `None` if there's no such
+    /// section or it has no valid `Size`, which the spec requires.
+    pub fn directory(&self, name: &str) -> Option<IconDirectory<'a>> {
+        let section = self
+            .entry
+            .sections()
+            .find(|section| section.name() == name)?;
+        let name = section.name;
+        let size = section.attr_parse("Size").ok()??;
+        let int = |key: &str| section.attr_parse(key).ok().flatten();
+        let defaults = IconDirectory::new(name, size);
+        Some(IconDirectory {
+            scale: int("Scale").unwrap_or(defaults.scale),
+            context: section.attr("Context"),
+            ty: match section.attr("Type") {
+                Some(ty) => match ty.parse() {
+                    Ok(ty) => ty,
+                    Err(e) => match e {},
+                },
+                None => defaults.ty,
+            },
+            min_size: int("MinSize").unwrap_or(defaults.min_size),
+            max_size: int("MaxSize").unwrap_or(defaults.max_size),
+            threshold: int("Threshold").unwrap_or(defaults.threshold),
+            ..defaults
+        })
+    }
+
+    /// Check the theme for problems theme authors should fix, like
+    /// [`validate`](../validate/fn.validate.html) does for desktop entries.
+    ///
+    /// Errors are missing required keys, directories listed in
+    /// `Directories` or `ScaledDirectories` without a section, and invalid
+    /// values.  Warnings are directory sections that aren't listed and keys
+    /// that don't apply to a directory's `Type`, like `Threshold` on a
+    /// `Fixed` one.
+    pub fn validate(&self) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        let mut push =
+            |severity, section: &str, attr: Option<&str>, message: String| {
+                findings.push(Finding {
+                    severity,
+                    section: section.to_owned(),
+                    attr: attr.map(ToOwned::to_owned),
+                    message,
+                })
+            };
+        let name = self.section.name();
+        if !self.entry.has_section(name) {
+            push(
+                Severity::Error,
+                name,
+                None,
+                "Missing `Icon Theme` section".to_owned(),
+            );
+            return findings;
+        }
+        for key in &["Name", "Comment", "Directories"] {
+            if !self.section.has_attr(key) {
+                push(
+                    Severity::Error,
+                    name,
+                    Some(key),
+                    format!("Required key `{}` is missing", key),
+                );
+            }
+        }
+        let listed: Vec<&str> = self
+            .directories()
+            .into_iter()
+            .chain(self.scaled_directories())
+            .collect();
+        for dir in &listed {
+            if !self.entry.has_section(dir) {
+                push(
+                    Severity::Error,
+                    name,
+                    Some("Directories"),
+                    format!("Directory `{}` has no section", dir),
+                );
+            }
+        }
+        for section in self.entry.sections() {
+            let dir = section.name();
+            if dir == name || dir.starts_with("X-") {
+                continue;
+            }
+            if !listed.contains(&dir) {
+                push(
+                    Severity::Warning,
+                    dir,
+                    None,
+                    "Directory isn't listed in `Directories`".to_owned(),
+                );
+            }
+            if !section.has_attr("Size") {
+                push(
+                    Severity::Error,
+                    dir,
+                    Some("Size"),
+                    "Required key `Size` is missing".to_owned(),
+                );
+            }
+            for key in &["Size", "Scale", "MinSize", "MaxSize", "Threshold"] {
+                if let Some(value) = section.attr(key) {
+                    if value.parse::<u32>().is_err() {
+                        push(
+                            Severity::Error,
+                            dir,
+                            Some(key),
+                            format!("`{}` isn't a positive integer", value),
+                        );
+                    }
+                }
+            }
+            let ty = section.attr("Type").unwrap_or("Threshold");
+            let unused: &[&str] = match ty {
+                "Fixed" => &["MinSize", "MaxSize", "Threshold"],
+                "Scalable" => &["Threshold"],
+                "Threshold" => &["MinSize", "MaxSize"],
+                _ => {
+                    push(
+                        Severity::Error,
+                        dir,
+                        Some("Type"),
+                        format!("Unknown type `{}`", ty),
+                    );
+                    &[]
+                }
+            };
+            for key in unused {
+                if section.has_attr(key) {
+                    push(
+                        Severity::Warning,
+                        dir,
+                        Some(key),
+                        format!("`{}` is only used by other types", key),
+                    );
+                }
+            }
+        }
+        findings
+    }
+
+    /// The names of the icons in the directories with `context`, sorted and
+    /// without duplicates, for icon pickers.  `theme_dirs` are the
+    /// directories of the theme, like `/usr/share/icons/Adwaita`, usually
+    /// only one.
+    ///
+    /// Only icons with one of the extensions the lookup uses, `png`, `svg`
+    /// and `xpm`, are listed.
+    pub fn icons_in_context<P: AsRef<Path>>(
+        &self,
+        context: &Context,
+        theme_dirs: &[P],
+    ) -> Vec<String> {
+        let mut icons = BTreeSet::new();
+        let dirs = self.icon_directories();
+        let dirs = dirs
+            .iter()
+            .filter(|dir| dir.context == Some(context.as_str()));
+        for dir in dirs {
+            for theme_dir in theme_dirs {
+                let files =
+                    match fs::read_dir(theme_dir.as_ref().join(dir.name)) {
+                        Ok(files) => files,
+                        Err(_) => continue,
+                    };
+                for file in files.flatten() {
+                    let path = file.path();
+                    let is_icon =
+                        match path.extension().and_then(|e| e.to_str()) {
+                            Some(ext) => EXTENSIONS.contains(&ext),
+                            None => false,
+                        };
+                    let name = path.file_stem().and_then(|name| name.to_str());
+                    if let (true, Some(name)) = (is_icon, name) {
+                        icons.insert(name.to_owned());
+                    }
+                }
+            }
+        }
+        icons.into_iter().collect()
+    }
+
+    /// The sections of the [`directories`](#method.directories) and
+    /// [`scaled_directories`](#method.scaled_directories), skipping the
+    /// ones that are missing or invalid.
+    pub fn icon_directories(&self) -> Vec<IconDirectory<'a>> {
+        self.directories()
+            .into_iter()
+            .chain(self.scaled_directories())
+            .filter_map(|name| self.directory(name))
+            .collect()
+    }
+}
+
+impl<'a> IconDirectory<'a> {
+    /// A directory `name` with icons of `size` and the defaults from the
+    /// spec for everything else, for programs that describe directories
+    /// themselves to use [`matches_size`](#method.matches_size) and
+    /// [`size_distance`](#method.size_distance).
+    pub fn new(name: &'a str, size: u32) -> Self {
+        IconDirectory {
+            name,
+            size,
+            scale: 1,
+            context: None,
+            ty: IconDirectoryType::Threshold,
+            min_size: size,
+            max_size: size,
+            threshold: 2,
+        }
+    }
+
+    /// Whether icons in the directory can be used for icons of `size` at
+    /// `scale`, the spec's `DirectoryMatchesSize`.
+    ///
+    /// Example:
+    /// ```
+    /// use freedesktop_entry_parser::icon_theme::{
+    ///     IconDirectory, IconDirectoryType,
+    /// };
+    ///
+    /// let dir = IconDirectory {
+    ///     ty: IconDirectoryType::Scalable,
+    ///     min_size: 16,
+    ///     max_size: 256,
+    ///     ..IconDirectory::new("scalable/apps", 48)
+    /// };
+    /// assert!(dir.matches_size(128, 1));
+    /// assert!(!dir.matches_size(128, 2));
+    /// assert_eq!(dir.size_distance(8, 1), 8);
+    /// ```
+    pub fn matches_size(&self, size: u32, scale: u32) -> bool {
+        if self.scale != scale {
+            return false;
+        }
+        match self.ty {
+            IconDirectoryType::Fixed => self.size == size,
+            IconDirectoryType::Scalable => {
+                self.min_size <= size && size <= self.max_size
+            }
+            IconDirectoryType::Threshold => {
+                self.size.saturating_sub(self.threshold) <= size
+                    && size <= self.size.saturating_add(self.threshold)
+            }
+        }
+    }
+
+    /// How far icons in the directory are from `size` at `scale`, in
+    /// pixels, the spec's `DirectorySizeDistance`.  0 if they
+    /// [match](#method.matches_size).  Sizes too large for a `u32` are
+    /// capped at `u32::MAX`.
+    pub fn size_distance(&self, size: u32, scale: u32) -> u32 {
+        let scaled = size.saturating_mul(scale);
+        let (min, max) = match self.ty {
+            IconDirectoryType::Fixed => (self.size, self.size),
+            IconDirectoryType::Scalable => (self.min_size, self.max_size),
+            IconDirectoryType::Threshold => (
+                self.size.saturating_sub(self.threshold),
+                self.size.saturating_add(self.threshold),
+            ),
+        };
+        let (min, max) = (
+            min.saturating_mul(self.scale),
+            max.saturating_mul(self.scale),
+        );
+        if scaled < min {
+            min - scaled
+        } else {
+            scaled.saturating_sub(max)
+        }
+    }
+}
+
+impl IconDirectoryType {
+    /// The value of `Type` for this type
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IconDirectoryType::Fixed => "Fixed",
+            IconDirectoryType::Scalable => "Scalable",
+            IconDirectoryType::Threshold => "Threshold",
+        }
+    }
+}
+
+impl FromStr for IconDirectoryType {
+    type Err = Infallible;
+
+    /// Unknown types are read as `Threshold`, the default.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "Fixed" => IconDirectoryType::Fixed,
+            "Scalable" => IconDirectoryType::Scalable,
+            _ => IconDirectoryType::Threshold,
+        })
+    }
+}
+
+impl Display for IconDirectoryType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl<'a> Deref for IconTheme<'a> {
+    type Target = AttrSelector<'a, &'static str>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.section
+    }
+}
+
+/// The base directories icon themes are installed in, from the most to
+/// the least important: `~/.icons`, the `icons` directories in
+/// `XDG_DATA_HOME` and `XDG_DATA_DIRS`, then `/usr/share/pixmaps`.
+pub fn icon_dirs() -> Vec<PathBuf> {
+    env::var_os("HOME")
+        .map(|home| Path::new(&home).join(".icons"))
+        .into_iter()
+        .chain(
+            data_home()
+                .into_iter()
+                .chain(data_dirs())
+                .map(|dir| dir.join("icons")),
+        )
+        .chain(Some(PathBuf::from("/usr/share/pixmaps")))
+        .collect()
+}
+
+/// The themes to look for icons in for `theme`, in the
+/// [`icon_dirs`](fn.icon_dirs.html).  See
+/// [`theme_chain_in`](fn.theme_chain_in.html).
+pub fn theme_chain(theme: &str) -> Vec<String> {
+    theme_chain_in(&icon_dirs(), theme)
+}
+
+/// The themes to look for icons in for `theme`, with its `index.theme`
+/// looked up in `base_dirs`: `theme` followed by the themes it `Inherits`,
+/// depth first, and `hicolor` at the end.
+///
+/// Each theme is only listed once, so inheritance cycles are fine.  Themes
+/// without an `index.theme` are skipped.
+pub fn theme_chain_in<P: AsRef<Path>>(
+    base_dirs: &[P],
+    theme: &str,
+) -> Vec<String> {
+    theme_chain_with(&mut FileSystem, base_dirs, theme)
+}
+
+pub(crate) fn theme_chain_with<P: AsRef<Path>>(
+    source: &mut impl Source,
+    base_dirs: &[P],
+    theme: &str,
+) -> Vec<String> {
+    let mut chain = Vec::new();
+    add_theme(source, base_dirs, theme, &mut chain);
+    if !chain.iter().any(|name| name == FALLBACK_THEME) {
+        chain.push(FALLBACK_THEME.to_owned());
+    }
+    chain
+}
+
+fn add_theme<P: AsRef<Path>>(
+    source: &mut impl Source,
+    base_dirs: &[P],
+    theme: &str,
+    chain: &mut Vec<String>,
+) {
+    if chain.iter().any(|name| name == theme) {
+        return;
+    }
+    let index = match load_index(source, base_dirs, theme) {
+        Some(index) => index,
+        None => return,
+    };
+    chain.push(theme.to_owned());
+    for parent in index.icon_theme().inherits() {
+        add_theme(source, base_dirs, parent, chain);
+    }
+}
+
+/// Find the icon `icon` for `size` at `scale` in `theme` or the themes it
+/// inherits from, in the [`icon_dirs`](fn.icon_dirs.html).  See
+/// [`find_icon_in`](fn.find_icon_in.html).
+pub fn find_icon(
+    icon: &str,
+    size: u32,
+    scale: u32,
+    theme: &str,
+) -> Option<PathBuf> {
+    find_icon_in(&icon_dirs(), icon, size, scale, theme)
+}
+
+/// Find the icon `icon` for `size` at `scale` with themes in `base_dirs`,
+/// following the lookup algorithm from the spec.
+///
+/// Each theme in the [`theme_chain_in`](fn.theme_chain_in.html) is tried in
+/// turn.  A theme's icon in a directory that
+/// [matches](struct.IconDirectory.html#method.matches_size) the size is
+/// preferred, otherwise the one closest to it is used.  If no theme has
+/// the icon, it's looked up directly in `base_dirs`, like
+/// `/usr/share/pixmaps/icon.png`.
+///
+/// An `icon` that's an absolute path, which desktop entries can use for
+/// `Icon`, is returned as is if the file exists, without looking in any
+/// theme.
+pub fn find_icon_in<P: AsRef<Path>>(
+    base_dirs: &[P],
+    icon: &str,
+    size: u32,
+    scale: u32,
+    theme: &str,
+) -> Option<PathBuf> {
+    find_icon_match_in(base_dirs, icon, size, scale, theme)
+        .map(|icon| icon.path)
+}
+
+/// Like [`find_icon`](fn.find_icon.html), but also returns the size and
+/// scale of the icon that was found.  See
+/// [`find_icon_match_in`](fn.find_icon_match_in.html).
+pub fn find_icon_match(
+    icon: &str,
+    size: u32,
+    scale: u32,
+    theme: &str,
+) -> Option<FoundIcon> {
+    find_icon_match_in(&icon_dirs(), icon, size, scale, theme)
+}
+
+/// Like [`find_icon_in`](fn.find_icon_in.html), but also returns the size
+/// and scale of the icon that was found, so toolkits on HiDPI screens know
+/// whether they need to scale it.
+///
+/// Example:
+/// ```
+/// use freedesktop_entry_parser::icon_theme::find_icon_match_in;
+///
+/// let dirs = ["./test_data/icons"];
+/// let icon = find_icon_match_in(&dirs, "viewer", 24, 2, "Foo").unwrap();
+/// assert_eq!((icon.size, icon.scale), (Some(24), 2));
+/// let icon = find_icon_match_in(&dirs, "editor", 24, 2, "Foo").unwrap();
+/// assert_eq!((icon.size, icon.scale), (Some(48), 1));
+/// ```
+pub fn find_icon_match_in<P: AsRef<Path>>(
+    base_dirs: &[P],
+    icon: &str,
+    size: u32,
+    scale: u32,
+    theme: &str,
+) -> Option<FoundIcon> {
+    find_icon_with(&mut FileSystem, base_dirs, icon, size, scale, theme)
+}
+
+pub(crate) fn find_icon_with<P: AsRef<Path>>(
+    source: &mut impl Source,
+    base_dirs: &[P],
+    icon: &str,
+    size: u32,
+    scale: u32,
+    theme: &str,
+) -> Option<FoundIcon> {
+    let path = Path::new(icon);
+    if path.is_absolute() {
+        let found = match (path.parent(), path.file_name()) {
+            (Some(dir), Some(name)) => {
+                source.has_file(dir, &name.to_string_lossy())
+            }
+            _ => false,
+        };
+        return if found {
+            Some(FoundIcon::unthemed(path.to_owned()))
+        } else {
+            None
+        };
+    }
+    for theme in theme_chain_with(source, base_dirs, theme) {
+        let found = lookup_icon(source, base_dirs, &theme, icon, size, scale);
+        if found.is_some() {
+            return found;
+        }
+    }
+    base_dirs
+        .iter()
+        .find_map(|base| icon_file(source, base.as_ref(), icon))
+        .map(FoundIcon::unthemed)
+}
+
+/// The cursor themes to look for cursors in for `theme`, in the
+/// [`icon_dirs`](fn.icon_dirs.html).  See
+/// [`cursor_theme_chain_in`](fn.cursor_theme_chain_in.html).
+pub fn cursor_theme_chain(theme: &str) -> Vec<String> {
+    cursor_theme_chain_in(&icon_dirs(), theme)
+}
+
+/// The cursor themes to look for cursors in for `theme`, with themes
+/// looked up in `base_dirs`: `theme` followed by the themes it `Inherits`,
+/// depth first.
+///
+/// Unlike icon themes, a cursor theme only needs a `cursors` directory and
+/// there's no fallback theme.  Each theme is only listed once, so
+/// inheritance cycles are fine.
+pub fn cursor_theme_chain_in<P: AsRef<Path>>(
+    base_dirs: &[P],
+    theme: &str,
+) -> Vec<String> {
+    let mut chain = Vec::new();
+    add_cursor_theme(base_dirs, theme, &mut chain);
+    chain
+}
+
+fn add_cursor_theme<P: AsRef<Path>>(
+    base_dirs: &[P],
+    theme: &str,
+    chain: &mut Vec<String>,
+) {
+    if chain.iter().any(|name| name == theme) {
+        return;
+    }
+    let index = load_index(&mut FileSystem, base_dirs, theme);
+    let has_cursors = base_dirs
+        .iter()
+        .any(|base| base.as_ref().join(theme).join("cursors").is_dir());
+    if index.is_none() && !has_cursors {
+        return;
+    }
+    chain.push(theme.to_owned());
+    if let Some(index) = index {
+        for parent in index.cursor_theme().inherits() {
+            add_cursor_theme(base_dirs, parent, chain);
+        }
+    }
+}
+
+/// Find the cursor file `cursor` in `theme` or the themes it inherits
+/// from, in the [`icon_dirs`](fn.icon_dirs.html).  See
+/// [`find_cursor_in`](fn.find_cursor_in.html).
+pub fn find_cursor(cursor: &str, theme: &str) -> Option<PathBuf> {
+    find_cursor_in(&icon_dirs(), cursor, theme)
+}
+
+/// Find the cursor file `cursor`, like `left_ptr`, in the `cursors`
+/// directory of the first theme in the
+/// [`cursor_theme_chain_in`](fn.cursor_theme_chain_in.html) that has it.
+pub fn find_cursor_in<P: AsRef<Path>>(
+    base_dirs: &[P],
+    cursor: &str,
+    theme: &str,
+) -> Option<PathBuf> {
+    cursor_theme_chain_in(base_dirs, theme)
+        .iter()
+        .find_map(|theme| {
+            base_dirs
+                .iter()
+                .map(|base| {
+                    base.as_ref().join(theme).join("cursors").join(cursor)
+                })
+                .find(|path| path.is_file())
+        })
+}
+
+/// The icon themes installed in the [`icon_dirs`](fn.icon_dirs.html).  See
+/// [`installed_themes_in`](fn.installed_themes_in.html).
+pub fn installed_themes() -> BTreeMap<String, InstalledTheme> {
+    installed_themes_in(&icon_dirs())
+}
+
+/// The icon themes in `base_dirs`, by internal name: every directory with
+/// an `index.theme` that can be parsed.  If a theme is in more than one of
+/// `base_dirs`, the `index.theme` from the first one is used, like
+/// [`find_icon_in`](fn.find_icon_in.html) does.
+///
+/// Themes that are [`hidden`](struct.IconTheme.html#method.hidden) are
+/// included, theme pickers should filter them out.
+pub fn installed_themes_in<P: AsRef<Path>>(
+    base_dirs: &[P],
+) -> BTreeMap<String, InstalledTheme> {
+    let mut themes = BTreeMap::new();
+    for base in base_dirs {
+        let dir = match fs::read_dir(base) {
+            Ok(dir) => dir,
+            Err(_) => continue,
+        };
+        for theme_dir in dir.flatten() {
+            let name = match theme_dir.file_name().into_string() {
+                Ok(name) => name,
+                Err(_) => continue,
+            };
+            if themes.contains_key(&name) {
+                continue;
+            }
+            let path = theme_dir.path().join("index.theme");
+            if let Ok(entry) = Entry::parse_file(&path) {
+                themes
+                    .insert(name.clone(), InstalledTheme { name, path, entry });
+            }
+        }
+    }
+    themes
+}
+
+/// Find `icon` in a single theme
+fn lookup_icon<P: AsRef<Path>>(
+    source: &mut impl Source,
+    base_dirs: &[P],
+    theme: &str,
+    icon: &str,
+    size: u32,
+    scale: u32,
+) -> Option<FoundIcon> {
+    let index = load_index(source, base_dirs, theme)?;
+    let dirs = index.icon_theme().icon_directories();
+    let mut find = |dir: &IconDirectory<'_>| {
+        base_dirs
+            .iter()
+            .find_map(|base| {
+                let dir = base.as_ref().join(theme).join(dir.name);
+                icon_file(source, &dir, icon)
+            })
+            .map(|path| FoundIcon {
+                path,
+                size: Some(dir.size),
+                scale: dir.scale,
+                scalable: dir.ty == IconDirectoryType::Scalable,
+            })
+    };
+    let exact = dirs
+        .iter()
+        .filter(|dir| dir.matches_size(size, scale))
+        .find_map(&mut find);
+    if exact.is_some() {
+        return exact;
+    }
+    let mut closest: Option<(u32, FoundIcon)> = None;
+    for dir in &dirs {
+        let distance = dir.size_distance(size, scale);
+        let closer = match &closest {
+            Some((min, _)) => distance < *min,
+            None => true,
+        };
+        if closer {
+            if let Some(found) = find(dir) {
+                closest = Some((distance, found));
+            }
+        }
+    }
+    closest.map(|(_, found)| found)
+}
+
+/// The file of `icon` in `dir` with one of the [`EXTENSIONS`]
+fn icon_file(
+    source: &mut impl Source,
+    dir: &Path,
+    icon: &str,
+) -> Option<PathBuf> {
+    EXTENSIONS
+        .iter()
+        .map(|ext| format!("{}.{}", icon, ext))
+        .find(|name| source.has_file(dir, name))
+        .map(|name| dir.join(name))
+}
+
+/// Parse the `index.theme` of `theme` from the first of `base_dirs` that
+/// has one
+fn load_index<P: AsRef<Path>>(
+    source: &mut impl Source,
+    base_dirs: &[P],
+    theme: &str,
+) -> Option<Entry> {
+    base_dirs.iter().find_map(|base| {
+        source.index(&base.as_ref().join(theme).join("index.theme"))
+    })
+}
+
+/// Where the icon lookup reads themes from, the file system or an
+/// [`IconCache`](struct.IconCache.html)
+pub(crate) trait Source {
+    /// Parse the `index.theme` at `path`, `None` if there's none or it's
+    /// invalid
+    fn index(&mut self, path: &Path) -> Option<Entry>;
+    /// Whether `dir` has a file `name`
+    fn has_file(&mut self, dir: &Path, name: &str) -> bool;
+}
+
+/// Reads everything from the file system every time
+struct FileSystem;
+
+impl Source for FileSystem {
+    fn index(&mut self, path: &Path) -> Option<Entry> {
+        Entry::parse_file(path).ok()
+    }
+
+    fn has_file(&mut self, dir: &Path, name: &str) -> bool {
+        dir.join(name).is_file()
+    }
+}
+
+/// Items of a comma separated list, which the icon theme spec uses instead
+/// of `;`
+fn comma_list(value: Option<&str>) -> Vec<&str> {
+    value
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Entry;
+
+    #[test]
+    fn typed() {
+        let entry = Entry::parse(
+            &b"[Icon Theme]\nName=Foo\nName[de]=Fu\nInherits=Bar, hicolor\n\
+               Directories=16x16/apps,\nScaledDirectories=16x16@2/apps\n\
+               Hidden=true"[..],
+        )
+        .unwrap();
+        let theme = entry
+            .icon_theme()
+            .with_locales(vec!["de_DE".parse().unwrap()]);
+        assert_eq!(theme.localized_name(), Some("Fu"));
+        assert_eq!(theme.inherits(), ["Bar", "hicolor"]);
+        assert_eq!(theme.directories(), ["16x16/apps"]);
+        assert_eq!(theme.scaled_directories(), ["16x16@2/apps"]);
+        assert!(theme.hidden());
+        assert_eq!(theme.comment(), None);
+        match theme.check() {
+            Err(EntryError::MissingKey { attr, .. }) => {
+                assert_eq!(attr, "Comment")
+            }
+            _ => panic!("Expected a missing key error"),
+        }
+    }
+
+    #[test]
+    fn directories() {
+        let entry = Entry::parse(
+            &b"[Icon Theme]\nDirectories=16x16/apps,scalable/apps,bad\n\
+               ScaledDirectories=16x16@2/apps\n\
+               [16x16/apps]\nSize=16\nContext=Applications\nType=Fixed\n\
+               [16x16@2/apps]\nSize=16\nScale=2\n\
+               [scalable/apps]\nSize=48\nType=Scalable\nMinSize=8\n\
+               MaxSize=512\n[bad]\nSize=big"[..],
+        )
+        .unwrap();
+        let theme = entry.icon_theme();
+        let dirs = theme.icon_directories();
+        let names: Vec<_> = dirs.iter().map(|dir| dir.name).collect();
+        assert_eq!(names, ["16x16/apps", "scalable/apps", "16x16@2/apps"]);
+        assert_eq!(dirs[0].context, Some("Applications"));
+        assert_eq!(dirs[0].ty, IconDirectoryType::Fixed);
+        assert_eq!(
+            dirs[2],
+            IconDirectory {
+                name: "16x16@2/apps",
+                size: 16,
+                scale: 2,
+                context: None,
+                ty: IconDirectoryType::Threshold,
+                min_size: 16,
+                max_size: 16,
+                threshold: 2,
+            }
+        );
+        assert!(dirs[0].matches_size(16, 1));
+        assert!(!dirs[0].matches_size(16, 2));
+        assert!(dirs[1].matches_size(256, 1));
+        assert!(dirs[2].matches_size(18, 2));
+        assert_eq!(dirs[0].size_distance(24, 1), 8);
+        assert_eq!(dirs[1].size_distance(4, 1), 4);
+        assert_eq!(dirs[2].size_distance(16, 1), 12);
+    }
+
+    #[test]
+    fn validate() {
+        let findings = |input: &str| {
+            let entry = Entry::parse(input.as_bytes()).unwrap();
+            let findings: Vec<_> = entry
+                .icon_theme()
+                .validate()
+                .into_iter()
+                .map(|f| (f.section, f.attr, f.severity))
+                .collect();
+            findings
+        };
+        let finding = |section: &str, attr: Option<&str>, severity| {
+            (section.to_owned(), attr.map(ToOwned::to_owned), severity)
+        };
+        assert!(findings(
+            "[Icon Theme]\nName=A\nComment=B\nDirectories=16,scalable\n\
+             [16]\nSize=16\nThreshold=4\n\
+             [scalable]\nSize=48\nType=Scalable\nMaxSize=512"
+        )
+        .is_empty());
+        assert_eq!(
+            findings(
+                "[Icon Theme]\nName=A\nDirectories=16,32\n[16]\nType=Fixed\n\
+                 Threshold=2\n[48]\nSize=-48\nType=Huge"
+            ),
+            [
+                finding("Icon Theme", Some("Comment"), Severity::Error),
+                finding("Icon Theme", Some("Directories"), Severity::Error),
+                finding("16", Some("Size"), Severity::Error),
+                finding("16", Some("Threshold"), Severity::Warning),
+                finding("48", None, Severity::Warning),
+                finding("48", Some("Size"), Severity::Error),
+                finding("48", Some("Type"), Severity::Error),
+            ]
+        );
+        assert_eq!(
+            findings("[Desktop Entry]"),
+            [finding("Icon Theme", None, Severity::Error)]
+        );
+    }
+
+    #[test]
+    fn context() {
+        let themes = installed_themes_in(&["./test_data/icons"]);
+        assert_eq!(
+            themes["Foo"].icons_in_context(&Context::Applications),
+            ["editor", "viewer"]
+        );
+        assert!(themes["Foo"].icons_in_context(&Context::Places).is_empty());
+        assert_eq!("Foo".parse(), Ok(Context::Other("Foo".to_owned())));
+        assert_eq!(Context::MimeTypes.to_string(), "MimeTypes");
+    }
+
+    #[test]
+    fn chain() {
+        let dirs = ["./test_data/missing", "./test_data/icons"];
+        assert_eq!(theme_chain_in(&dirs, "Bar"), ["Bar", "Foo", "hicolor"]);
+        assert_eq!(theme_chain_in(&dirs, "Missing"), ["hicolor"]);
+    }
+
+    #[test]
+    fn installed() {
+        let themes = installed_themes_in(&["./test_data/icons"]);
+        let names: Vec<_> = themes.keys().collect();
+        assert_eq!(names, ["Bar", "Foo", "hicolor"]);
+        let hicolor = &themes["hicolor"];
+        assert_eq!(hicolor.icon_theme().name(), Some("Hicolor"));
+        assert_eq!(
+            hicolor.path,
+            Path::new("./test_data/icons/hicolor/index.theme")
+        );
+    }
+
+    #[test]
+    fn cursors() {
+        let dirs = ["./test_data/cursors"];
+        assert_eq!(
+            cursor_theme_chain_in(&dirs, "Pointer"),
+            ["Pointer", "Arrows"]
+        );
+        assert_eq!(cursor_theme_chain_in(&dirs, "Missing"), [] as [&str; 0]);
+        let entry =
+            Entry::parse_file("./test_data/cursors/Pointer/index.theme")
+                .unwrap();
+        assert_eq!(entry.cursor_theme().name(), Some("Pointer"));
+        let find = |cursor: &str| {
+            find_cursor_in(&dirs, cursor, "Pointer")
+                .map(|path| path.strip_prefix(dirs[0]).unwrap().to_owned())
+        };
+        assert_eq!(find("watch"), Some(PathBuf::from("Pointer/cursors/watch")));
+        assert_eq!(
+            find("left_ptr"),
+            Some(PathBuf::from("Arrows/cursors/left_ptr"))
+        );
+        assert_eq!(find("missing"), None);
+    }
+
+    #[test]
+    fn new_directory() {
+        let entry = Entry::parse(
+            &b"[Icon Theme]\nName=Foo\nDirectories=32x32/apps\n\
+               [32x32/apps]\nSize=32"[..],
+        )
+        .unwrap();
+        let parsed = entry.icon_theme().directory("32x32/apps").unwrap();
+        let dir = IconDirectory::new("32x32/apps", 32);
+        assert_eq!(dir, parsed);
+        assert_eq!(dir.ty, IconDirectoryType::Threshold);
+        assert_eq!((dir.scale, dir.threshold), (1, 2));
+        assert!(dir.matches_size(30, 1));
+        assert!(dir.matches_size(34, 1));
+        assert!(!dir.matches_size(35, 1));
+        assert!(!dir.matches_size(32, 2));
+        assert_eq!(dir.size_distance(40, 1), 6);
+        assert_eq!(dir.size_distance(8, 2), 14);
+    }
+
+    #[test]
+    fn found_icon() {
+        let dirs = ["./test_data/icons"];
+        let found = |icon: &str, size: u32, scale: u32| {
+            find_icon_match_in(&dirs, icon, size, scale, "Foo")
+        };
+        let base = Path::new(dirs[0]);
+        assert_eq!(
+            found("viewer", 24, 2),
+            Some(FoundIcon {
+                path: base.join("Foo/24x24@2/apps/viewer.png"),
+                size: Some(24),
+                scale: 2,
+                scalable: false,
+            })
+        );
+        assert_eq!(
+            found("editor", 16, 1),
+            Some(FoundIcon {
+                path: base.join("Foo/scalable/apps/editor.svg"),
+                size: Some(48),
+                scale: 1,
+                scalable: true,
+            })
+        );
+        assert_eq!(
+            found("player", 16, 1),
+            Some(FoundIcon {
+                path: base.join("Bar/16x16/apps/player.png"),
+                size: Some(16),
+                scale: 1,
+                scalable: false,
+            })
+        );
+        assert_eq!(
+            found("fallback", 16, 1),
+            Some(FoundIcon {
+                path: base.join("fallback.xpm"),
+                size: None,
+                scale: 1,
+                scalable: false,
+            })
+        );
+        assert_eq!(found("missing", 16, 1), None);
+    }
+
+    #[test]
+    fn desktop_entry_icon_path() {
+        let icon_path = |icon: Option<&Path>| {
+            let mut input = "[Desktop Entry]\nExec=editor\n".to_owned();
+            if let Some(icon) = icon {
+                input.push_str(&format!("Icon={}\n", icon.display()));
+            }
+            let entry = Entry::parse(input).unwrap();
+            entry.desktop_entry().icon_path(48, 1, "hicolor")
+        };
+        let absolute = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("test_data/icons/Foo/scalable/apps/editor.svg");
+        assert_eq!(icon_path(Some(&absolute)), Some(absolute.clone()));
+        let missing = absolute.with_file_name("missing.svg");
+        assert_eq!(icon_path(Some(&missing)), None);
+        assert_eq!(icon_path(None), None);
+    }
+
+    #[test]
+    fn oversized() {
+        let dir = IconDirectory {
+            threshold: u32::MAX,
+            scale: u32::MAX,
+            ..IconDirectory::new("huge", u32::MAX)
+        };
+        assert!(dir.matches_size(48, u32::MAX));
+        assert_eq!(dir.size_distance(u32::MAX, 2), 0);
+
+        let entry = Entry::parse(
+            &b"[Icon Theme]\nName=Huge\nDirectories=huge\n\
+               [huge]\nSize=4294967295\nScale=4294967295\nThreshold=2\n"[..],
+        )
+        .unwrap();
+        let dir = entry.icon_theme().directory("huge").unwrap();
+        assert!(!dir.matches_size(48, 1));
+        assert_eq!(dir.size_distance(48, 1), u32::MAX - 48);
+    }
+
+    #[test]
+    fn lookup() {
+        let dirs = ["./test_data/icons"];
+        let find = |icon: &str, size: u32, scale: u32| {
+            find_icon_in(&dirs, icon, size, scale, "Bar")
+                .map(|path| path.strip_prefix(dirs[0]).unwrap().to_owned())
+        };
+        let path = |path: &str| Some(PathBuf::from(path));
+        assert_eq!(find("viewer", 48, 1), path("Foo/48x48/apps/viewer.png"));
+        assert_eq!(find("viewer", 32, 1), path("Foo/scalable/apps/viewer.svg"));
+        assert_eq!(
+            find("editor", 512, 1),
+            path("Foo/scalable/apps/editor.svg")
+        );
+        assert_eq!(find("player", 48, 1), path("Bar/16x16/apps/player.png"));
+        assert_eq!(
+            find("terminal", 48, 2),
+            path("hicolor/48x48/apps/terminal.png")
+        );
+        assert_eq!(find("fallback", 48, 1), path("fallback.xpm"));
+        assert_eq!(find("missing", 48, 1), None);
+    }
+}
@@ -0,0 +1,149 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::{
+    file_id::{data_dirs, data_home},
+    DesktopEntry,
+};
+use std::{
+    ffi::OsString,
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    process,
+};
+
+/// Where [`DesktopEntry::install`](struct.DesktopEntry.html#method.install)
+/// puts an entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstallScope {
+    /// The `applications` directory in `XDG_DATA_HOME`, usually
+    /// `~/.local/share/applications`
+    User,
+    /// The `applications` directory in the first of the `XDG_DATA_DIRS`,
+    /// usually `/usr/local/share/applications`
+    System,
+}
+
+impl InstallScope {
+    /// The `applications` directory for this scope, `None` if neither the
+    /// XDG variable nor `HOME` are set.
+    pub fn applications_dir(self) -> Option<PathBuf> {
+        match self {
+            InstallScope::User => data_home(),
+            InstallScope::System => data_dirs().into_iter().next(),
+        }
+        .map(|dir| dir.join("applications"))
+    }
+}
+
+impl<'a> DesktopEntry<'a> {
+    /// Write the entry as `file_id` in the `applications` directory of
+    /// `scope`, creating the directory if needed, and return the path of
+    /// the file.  See [`install_to`](#method.install_to).
+    ///
+    /// The crate doesn't keep any caches of installed entries, so there's
+    /// nothing to refresh afterwards; run `update-desktop-database` if the
+    /// MIME cache should pick up the entry's `MimeType`.
+    pub fn install(
+        &self,
+        file_id: &str,
+        scope: InstallScope,
+    ) -> io::Result<PathBuf> {
+        let dir = scope.applications_dir().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                "no applications directory for the install scope",
+            )
+        })?;
+        self.install_to(dir, file_id)
+    }
+
+    /// Write the entry as `file_id` in `dir`, creating `dir` if needed, and
+    /// return the path of the file.
+    ///
+    /// The entry is written to a temporary file in `dir` which is then
+    /// renamed over `file_id`, so readers never see a partially written
+    /// entry.  `file_id` must be a file name ending in `.desktop`, not a
+    /// path.
+    pub fn install_to(
+        &self,
+        dir: impl AsRef<Path>,
+        file_id: &str,
+    ) -> io::Result<PathBuf> {
+        if !file_id.ends_with(".desktop")
+            || file_id.len() == ".desktop".len()
+            || file_id.contains('/')
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid desktop file ID: {}", file_id),
+            ));
+        }
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+        let path = dir.join(file_id);
+        write_atomic(&path, |writer| self.entry.write_to(writer))?;
+        Ok(path)
+    }
+}
+
+/// Write the file at `path` with `write` through a temporary file next to
+/// it, which is renamed over `path` once it's complete.
+pub(crate) fn write_atomic(
+    path: &Path,
+    write: impl FnOnce(&mut BufWriter<File>) -> io::Result<()>,
+) -> io::Result<()> {
+    let name = path.file_name().ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidInput, "path has no file name")
+    })?;
+    let mut tmp_name = OsString::from(".");
+    tmp_name.push(name);
+    tmp_name.push(format!(".{}.tmp", process::id()));
+    let tmp = path.with_file_name(tmp_name);
+    let result = write_file(&tmp, write).and_then(|()| fs::rename(&tmp, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result
+}
+
+fn write_file(
+    path: &Path,
+    write: impl FnOnce(&mut BufWriter<File>) -> io::Result<()>,
+) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write(&mut writer)?;
+    writer.flush()?;
+    writer.get_ref().sync_all()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Entry;
+
+    #[test]
+    fn install_to() {
+        let dir = std::env::temp_dir()
+            .join(format!("freedesktop-entry-install-{}", process::id()));
+        let entry =
+            Entry::parse(&b"[Desktop Entry]\nType=Application\nName=Foo\n"[..])
+                .unwrap();
+        let desktop_entry = entry.desktop_entry();
+        let path = desktop_entry.install_to(&dir, "foo.desktop").unwrap();
+        assert_eq!(path, dir.join("foo.desktop"));
+        let installed = Entry::parse_file(&path).unwrap();
+        assert_eq!(
+            installed.section("Desktop Entry").attr("Name"),
+            Some("Foo")
+        );
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        for id in &["foo", ".desktop", "apps/foo.desktop"] {
+            let err = desktop_entry.install_to(&dir, id).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,251 +1,667 @@
 //! Internal entry map
 //!
-//! The map uses unsafe code and this module provides a safe but
-//! unergonomic API for use by the nicer API.
-use crate::{parser::parse_entry, ParseError};
+//! Strings in the map either borrow from the input buffer or are owned. This
+//! module provides an unergonomic API for use by the nicer API.
+use crate::{
+    parser::parse_entry_with, Diagnostic, Duplicates, Locale, ParseError,
+    ParserOptions, Severity,
+};
+use indexmap::{map::Keys, IndexMap};
 use std::{
-    collections::{hash_map::Keys, HashMap},
-    fmt::{Debug, Formatter},
-    hash::Hash,
-    intrinsics::transmute,
-    marker::PhantomPinned,
-    pin::Pin,
-    ptr::NonNull,
+    borrow::Cow,
+    collections::BTreeMap,
+    hash::{Hash, Hasher},
 };
 
-pub struct AttrValue {
-    value: Option<SP>,
-    param_map: Option<ParamMap>,
+#[derive(Clone)]
+pub(crate) struct AttrValue<'a> {
+    /// Every value the attribute was assigned without a param, in order.
+    values: Vec<Cow<'a, str>>,
+    /// When each of `values` was assigned, counted across all attributes of
+    /// the section.
+    order: Vec<usize>,
+    param_map: Option<ParamMap<'a>>,
 }
 
-/// <section, <attribute, {value, <param, param_vale>}>>
-type InternalMap = HashMap<SP, AttrMap>;
+/// A section and its attributes.
+///
+/// Returned by indexing an entry, like `entry["Desktop Entry"]`.  It can be
+/// indexed by attribute name in turn.
+// <attribute, {value, <param, param_vale>}>
+//
+// All the maps keep the order things were inserted in, which is the order
+// they appear in the file.
+#[derive(Clone)]
+pub struct Section<'a> {
+    name: Cow<'a, str>,
+    attrs: AttrMap<'a>,
+}
 
-pub(crate) struct AttrMap(HashMap<SP, AttrValue>);
-pub(crate) struct ParamMap(HashMap<SP, SP>);
+#[derive(Clone)]
+pub(crate) struct AttrMap<'a> {
+    attrs: IndexMap<Cow<'a, str>, AttrValue<'a>>,
+    /// Number of values assigned without a param so far
+    assigned: usize,
+    /// Ignore ASCII case when looking up attributes
+    ignore_case: bool,
+}
 
-pub(crate) struct Internal {
-    map: Option<InternalMap>,
-    data: Vec<u8>,
-    _pin: PhantomPinned,
+#[derive(Clone)]
+pub(crate) struct ParamMap<'a>(IndexMap<Cow<'a, str>, Cow<'a, str>>);
+
+/// Sections in the order they appear in the file.  A name can appear more
+/// than once, lookups by name find the last section with that name.
+#[derive(Clone)]
+pub(crate) struct Internal<'a> {
+    sections: Vec<Section<'a>>,
+    /// Ignore ASCII case when looking up sections and attributes
+    ignore_case: bool,
 }
 
-impl Internal {
-    pub(crate) fn new(data: Vec<u8>) -> Result<Pin<Box<Self>>, ParseError> {
-        let this = Self {
-            map: None,
-            data,
-            _pin: PhantomPinned,
-        };
-        let mut boxed = Box::pin(this);
+impl<'a> Internal<'a> {
+    pub(crate) fn empty() -> Self {
+        Self {
+            sections: Vec::new(),
+            ignore_case: false,
+        }
+    }
+
+    pub(crate) fn new(
+        data: &'a [u8],
+        options: &ParserOptions,
+    ) -> Result<Self, ParseError> {
+        Self::with_diagnostics(data, options).map(|(internal, _)| internal)
+    }
 
-        let entry_bytes =
-            parse_entry(&boxed.data).collect::<Result<Vec<_>, _>>()?;
+    /// Parse `data` and also return the problems that were skipped.
+    pub(crate) fn with_diagnostics(
+        data: &'a [u8],
+        options: &ParserOptions,
+    ) -> Result<(Self, Vec<Diagnostic>), ParseError> {
+        let mut iter = parse_entry_with(data, options);
+        let entry_bytes = (&mut iter).collect::<Result<Vec<_>, _>>()?;
+        let mut diagnostics = iter.into_diagnostics();
 
-        let mut sections: InternalMap = HashMap::new();
+        let mut sections = Vec::with_capacity(entry_bytes.len());
+
+        let allow_duplicates = options.duplicates == Duplicates::Allow;
+        let keep_params = options.locales.params();
 
         for section_bytes in entry_bytes {
-            let section = parse_str(section_bytes.title)?;
-            let mut map: HashMap<SP, AttrValue> = HashMap::new();
+            let section = decode(section_bytes.title, None, options)?;
+            let ignore_case = options.ignore_case;
+            if sections
+                .iter()
+                .any(|s: &Section| name_eq(ignore_case, &s.name, &section))
+            {
+                if !allow_duplicates {
+                    return Err(ParseError::DuplicateSection {
+                        section: section.into_owned(),
+                    });
+                }
+                diagnostics.push(Diagnostic::new(
+                    Severity::Warning,
+                    data,
+                    section_bytes.title,
+                    format!("Duplicate section `{}`", section),
+                ));
+            }
+            let mut map = AttrMap::new(options.ignore_case);
             for attr_bytes in section_bytes.attrs {
-                let value = parse_str(attr_bytes.value)?;
+                if let (Some(param), Some(keep)) =
+                    (&attr_bytes.param, &keep_params)
+                {
+                    if !keep_param(param.param, keep) {
+                        continue;
+                    }
+                }
+                let locale = attr_bytes.param.as_ref().map(|param| param.param);
+                let value = decode(attr_bytes.value, locale, options)?;
 
-                match attr_bytes.param {
+                let duplicate = match attr_bytes.param {
                     Some(param) => {
-                        let name = parse_str(param.attr_name)?;
-                        let param = parse_str(param.param)?;
-                        map.entry(SP::from(name))
-                            .and_modify(|attr| {
-                                attr.param_map
-                                    .get_or_insert_with(ParamMap::new)
-                                    .0
-                                    .insert(SP::from(param), SP::from(value));
-                            })
-                            .or_insert(AttrValue {
-                                value: None,
-                                param_map: {
-                                    let mut map = HashMap::new();
-                                    map.insert(
-                                        SP::from(param),
-                                        SP::from(value),
-                                    );
-                                    Some(ParamMap(map))
-                                },
-                            });
+                        let name = decode_name(param.attr_name, options)?;
+                        let param = decode_name(param.param, options)?;
+                        let other_case =
+                            map.has_other_case(&name, Some(&param));
+                        let replaced = map
+                            .attrs
+                            .entry(name)
+                            .or_insert_with(AttrValue::new)
+                            .param_map
+                            .get_or_insert_with(ParamMap::new)
+                            .0
+                            .insert(param, value)
+                            .is_some();
+                        other_case || replaced
                     }
                     None => {
-                        let name = parse_str(attr_bytes.name)?;
-                        map.entry(SP::from(name))
-                            .and_modify(|attr| {
-                                attr.value = Some(SP::from(value))
-                            })
-                            .or_insert(AttrValue {
-                                value: Some(SP::from(value)),
-                                param_map: None,
-                            });
+                        let name = decode_name(attr_bytes.name, options)?;
+                        let order = map.next_order();
+                        let other_case = map.has_other_case(&name, None);
+                        let attr = map
+                            .attrs
+                            .entry(name)
+                            .or_insert_with(AttrValue::new);
+                        attr.push(value, order);
+                        other_case || attr.values.len() > 1
+                    }
+                };
+                if duplicate {
+                    let attr = decode(attr_bytes.name, None, options)?;
+                    if !allow_duplicates {
+                        return Err(ParseError::DuplicateAttr {
+                            section: section.to_string(),
+                            attr: attr.into_owned(),
+                        });
                     }
+                    diagnostics.push(Diagnostic::new(
+                        Severity::Warning,
+                        data,
+                        attr_bytes.name,
+                        format!("Duplicate attribute `{}`", attr),
+                    ));
                 }
             }
-            sections.insert(SP::from(section), AttrMap(map));
+            sections.push(Section {
+                name: section,
+                attrs: map,
+            });
         }
-        // SAFETY: we know this is safe because modifying a field doesn't move the whole struct
-        unsafe {
-            let mut_ref: Pin<&mut Internal> = Pin::as_mut(&mut boxed);
-            Pin::get_unchecked_mut(mut_ref).map = Some(sections);
+        let internal = Self {
+            sections,
+            ignore_case: options.ignore_case,
+        };
+        Ok((internal, diagnostics))
+    }
+
+    /// Copy all borrowed strings so the map no longer borrows the input.
+    pub(crate) fn into_owned(self) -> Internal<'static> {
+        Internal {
+            sections: self
+                .sections
+                .into_iter()
+                .map(|section| Section {
+                    name: owned(section.name),
+                    attrs: section.attrs.into_owned(),
+                })
+                .collect(),
+            ignore_case: self.ignore_case,
         }
-        Ok(boxed)
     }
 
-    fn get_section<'a>(
-        self: &'a Pin<Box<Self>>,
+    /// Compare section names, ignoring case if enabled.
+    pub(crate) fn name_eq(&self, a: &str, b: &str) -> bool {
+        name_eq(self.ignore_case, a, b)
+    }
+
+    pub(crate) fn get_section(
+        &self,
         section_name: &str,
-    ) -> Option<&'a AttrMap> {
-        self.map.as_ref().unwrap().get(&SP::from(section_name))
+    ) -> Option<&AttrMap<'a>> {
+        self.sections
+            .iter()
+            .rev()
+            .find(|section| self.name_eq(&section.name, section_name))
+            .map(|section| &section.attrs)
     }
 
-    pub(crate) fn get<'a>(
-        self: &'a Pin<Box<Self>>,
+    fn get_section_mut(
+        &mut self,
+        section_name: &str,
+    ) -> Option<&mut AttrMap<'a>> {
+        let ignore_case = self.ignore_case;
+        self.sections
+            .iter_mut()
+            .rev()
+            .find(|section| name_eq(ignore_case, &section.name, section_name))
+            .map(|section| &mut section.attrs)
+    }
+
+    pub(crate) fn has_section(&self, section_name: &str) -> bool {
+        self.get_section(section_name).is_some()
+    }
+
+    pub(crate) fn sections(&self) -> std::slice::Iter<'_, Section<'a>> {
+        self.sections.iter()
+    }
+
+    pub(crate) fn into_sections(self) -> std::vec::IntoIter<Section<'a>> {
+        self.sections.into_iter()
+    }
+
+    pub(crate) fn add_section(&mut self, section_name: &str) {
+        if self.has_section(section_name) {
+            return;
+        }
+        let section = Section {
+            name: Cow::Owned(section_name.to_owned()),
+            attrs: AttrMap::new(self.ignore_case),
+        };
+        // The attributes before the first header have to stay in front
+        if section_name.is_empty() {
+            self.sections.insert(0, section);
+        } else {
+            self.sections.push(section);
+        }
+    }
+
+    /// Remove every section named `section_name`.
+    pub(crate) fn remove_section(&mut self, section_name: &str) -> bool {
+        let len = self.sections.len();
+        let ignore_case = self.ignore_case;
+        self.sections.retain(|section| {
+            !name_eq(ignore_case, &section.name, section_name)
+        });
+        self.sections.len() != len
+    }
+
+    pub(crate) fn set_value(
+        &mut self,
         section_name: &str,
         attr_name: &str,
         param_name: Option<&str>,
-    ) -> Option<&'a str> {
-        let section_map = self.get_section(section_name)?;
-        let attr_val = section_map.get_attr(attr_name)?;
+        value: &str,
+    ) {
+        self.add_section(section_name);
+        let section_map = self.get_section_mut(section_name).unwrap();
+        let order = section_map.next_order();
+        if section_map.index_of(attr_name).is_none() {
+            section_map
+                .attrs
+                .insert(Cow::Owned(attr_name.to_owned()), AttrValue::new());
+        }
+        let attr = section_map.get_attr_mut(attr_name).unwrap();
+        let value = Cow::Owned(value.to_owned());
         match param_name {
             Some(param_name) => {
-                let param_map = attr_val.param_map.as_ref()?;
-                param_map.get_param(param_name)
+                let param_map =
+                    attr.param_map.get_or_insert_with(ParamMap::new);
+                match param_map.0.get_mut(param_name) {
+                    Some(param_value) => *param_value = value,
+                    None => {
+                        param_map
+                            .0
+                            .insert(Cow::Owned(param_name.to_owned()), value);
+                    }
+                }
+            }
+            None => {
+                attr.values = vec![value];
+                attr.order = vec![order];
             }
-            None => attr_val.get_value(),
         }
     }
 
-    pub(crate) fn get_attr<'a>(
-        self: &'a Pin<Box<Self>>,
+    pub(crate) fn add_value(
+        &mut self,
         section_name: &str,
         attr_name: &str,
-    ) -> Option<&'a AttrValue> {
-        let section_map = self.get_section(section_name)?;
-        section_map.get_attr(attr_name)
+        value: &str,
+    ) {
+        let existing = self
+            .get_section(section_name)
+            .and_then(|section| section.get_attr(attr_name))
+            .map_or(0, |attr| attr.values.len());
+        if existing == 0 {
+            return self.set_value(section_name, attr_name, None, value);
+        }
+        let section_map = self.get_section_mut(section_name).unwrap();
+        let order = section_map.next_order();
+        let attr = section_map.get_attr_mut(attr_name).unwrap();
+        attr.push(Cow::Owned(value.to_owned()), order);
     }
 
-    pub(crate) fn has_section(
-        self: &Pin<Box<Self>>,
+    pub(crate) fn remove_value(
+        &mut self,
         section_name: &str,
+        attr_name: &str,
+        param_name: Option<&str>,
     ) -> bool {
-        self.get_section(section_name).is_some()
+        let section_map = match self.get_section_mut(section_name) {
+            Some(section_map) => section_map,
+            None => return false,
+        };
+        let param_name = match param_name {
+            Some(param_name) => param_name,
+            None => return section_map.remove(attr_name),
+        };
+        let attr = match section_map.get_attr_mut(attr_name) {
+            Some(attr) => attr,
+            None => return false,
+        };
+        let (removed, params_empty) = match attr.param_map.as_mut() {
+            Some(param_map) => (
+                param_map.0.shift_remove(param_name).is_some(),
+                param_map.0.is_empty(),
+            ),
+            None => (false, false),
+        };
+        if params_empty {
+            attr.param_map = None;
+        }
+        if attr.values.is_empty() && attr.param_map.is_none() {
+            section_map.remove(attr_name);
+        }
+        removed
     }
+}
+
+/// Sections sorted by name, with their attributes and params sorted by name.
+/// Repeated values of an attribute keep their order.
+type Canonical<'s, 'a> = Vec<(
+    &'s str,
+    BTreeMap<&'s str, (&'s [Cow<'a, str>], BTreeMap<&'s str, &'s str>)>,
+)>;
 
-    // Clippy is wrong here
-    #[allow(clippy::needless_lifetimes)]
-    pub(crate) fn section_names_iter<'a>(
-        self: &'a Pin<Box<Self>>,
-    ) -> SectionNamesIter<'a> {
-        KeysIter(self.map.as_ref().unwrap().keys())
+impl<'a> Internal<'a> {
+    /// A form of the entry that doesn't depend on the order things are in.
+    fn canonical(&self) -> Canonical<'_, 'a> {
+        let mut sections: Canonical = self
+            .sections
+            .iter()
+            .map(|section| {
+                let attrs = section
+                    .attrs
+                    .attrs
+                    .iter()
+                    .map(|(name, attr)| {
+                        let params = attr
+                            .param_map
+                            .iter()
+                            .flat_map(|params| params.0.iter())
+                            .map(|(param, value)| (&**param, &**value))
+                            .collect();
+                        (&**name, (&attr.values[..], params))
+                    })
+                    .collect();
+                (&*section.name, attrs)
+            })
+            .collect();
+        sections.sort();
+        sections
     }
+}
 
-    pub(crate) fn attr_names_iter<'a>(
-        self: &'a Pin<Box<Self>>,
-        section_name: &str,
-    ) -> Option<AttrNamesIter<'a>> {
-        Some(KeysIter(self.get_section(section_name)?.0.keys()))
+impl<'a> PartialEq for Internal<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.canonical() == other.canonical()
     }
+}
 
-    pub(crate) fn param_names_iter<'a>(
-        self: &'a Pin<Box<Self>>,
-        section_name: &str,
-        attr_name: &str,
-    ) -> Option<ParamNamesIter<'a>> {
-        let section_map = self.get_section(section_name)?;
-        let attr_val = section_map.get_attr(attr_name)?;
-        let param_map = attr_val.param_map.as_ref()?;
-        Some(KeysIter(param_map.0.keys()))
+impl<'a> Eq for Internal<'a> {}
+
+impl<'a> Hash for Internal<'a> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.canonical().hash(state)
     }
 }
 
-impl AttrMap {
-    pub(crate) fn get_attr(&self, attr_name: &str) -> Option<&AttrValue> {
-        self.0.get(&SP::from(attr_name))
+impl<'a> Section<'a> {
+    /// Get this section's name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub(crate) fn attrs(&self) -> &AttrMap<'a> {
+        &self.attrs
     }
 }
 
-impl AttrValue {
+impl<'a> AttrMap<'a> {
+    fn new(ignore_case: bool) -> Self {
+        AttrMap {
+            attrs: IndexMap::new(),
+            assigned: 0,
+            ignore_case,
+        }
+    }
+
+    /// Whether case is ignored and there's an attribute with `attr_name` in
+    /// another case that has a value for `param_name`.
+    fn has_other_case(
+        &self,
+        attr_name: &str,
+        param_name: Option<&str>,
+    ) -> bool {
+        self.ignore_case
+            && self.attrs.iter().any(|(name, attr)| {
+                name != attr_name
+                    && name.eq_ignore_ascii_case(attr_name)
+                    && match param_name {
+                        Some(param_name) => attr
+                            .param_map
+                            .as_ref()
+                            .and_then(|params| params.get_param(param_name))
+                            .is_some(),
+                        None => !attr.values.is_empty(),
+                    }
+            })
+    }
+
+    fn next_order(&mut self) -> usize {
+        self.assigned += 1;
+        self.assigned
+    }
+
+    /// Find an attribute, preferring an exact match if case is ignored.
+    fn index_of(&self, attr_name: &str) -> Option<usize> {
+        match self.attrs.get_index_of(attr_name) {
+            Some(i) => Some(i),
+            None if self.ignore_case => self
+                .attrs
+                .keys()
+                .position(|name| name.eq_ignore_ascii_case(attr_name)),
+            None => None,
+        }
+    }
+
+    fn get_attr_mut(&mut self, attr_name: &str) -> Option<&mut AttrValue<'a>> {
+        let i = self.index_of(attr_name)?;
+        self.attrs.get_index_mut(i).map(|(_, attr)| attr)
+    }
+
+    fn remove(&mut self, attr_name: &str) -> bool {
+        match self.index_of(attr_name) {
+            Some(i) => self.attrs.shift_remove_index(i).is_some(),
+            None => false,
+        }
+    }
+
+    pub(crate) fn get(
+        &self,
+        attr_name: &str,
+        param_name: Option<&str>,
+    ) -> Option<&str> {
+        let attr_val = self.get_attr(attr_name)?;
+        match param_name {
+            Some(param_name) => {
+                attr_val.param_map.as_ref()?.get_param(param_name)
+            }
+            None => attr_val.get_value(),
+        }
+    }
+
+    pub(crate) fn get_attr(&self, attr_name: &str) -> Option<&AttrValue<'a>> {
+        let i = self.index_of(attr_name)?;
+        self.attrs.get_index(i).map(|(_, attr)| attr)
+    }
+
+    pub(crate) fn attr_names_iter(&self) -> AttrNamesIter<'_> {
+        KeysIter(self.attrs.keys())
+    }
+
+    /// Every value assigned without a param with the name of its attribute,
+    /// in the order they were assigned in, even across attributes.
+    pub(crate) fn values_in_order(&self) -> Vec<(&str, &str)> {
+        let mut values: Vec<_> = self
+            .attrs
+            .iter()
+            .flat_map(|(name, attr)| {
+                attr.order
+                    .iter()
+                    .zip(&attr.values)
+                    .map(move |(order, value)| (*order, &**name, &**value))
+            })
+            .collect();
+        values.sort_by_key(|(order, _, _)| *order);
+        values
+            .into_iter()
+            .map(|(_, name, value)| (name, value))
+            .collect()
+    }
+
+    fn into_owned(self) -> AttrMap<'static> {
+        AttrMap {
+            attrs: self
+                .attrs
+                .into_iter()
+                .map(|(name, attr)| (owned(name), attr.into_owned()))
+                .collect(),
+            assigned: self.assigned,
+            ignore_case: self.ignore_case,
+        }
+    }
+}
+
+impl<'a> AttrValue<'a> {
+    fn new() -> Self {
+        AttrValue {
+            values: Vec::new(),
+            order: Vec::new(),
+            param_map: None,
+        }
+    }
+
+    fn push(&mut self, value: Cow<'a, str>, order: usize) {
+        self.values.push(value);
+        self.order.push(order);
+    }
+
+    /// The last value assigned without a param.
     pub(crate) fn get_value(&self) -> Option<&str> {
-        // SAFETY: This is safe because the string has the same lifetime as Entry
-        self.value
-            .as_ref()
-            .map(|s| unsafe { transmute(&*s.0.as_ptr()) })
+        self.values.last().map(|s| s.as_ref())
+    }
+
+    pub(crate) fn get_values(&self) -> &[Cow<'a, str>] {
+        &self.values
     }
 
-    pub(crate) fn get_params(&self) -> Option<&ParamMap> {
+    pub(crate) fn get_params(&self) -> Option<&ParamMap<'a>> {
         self.param_map.as_ref()
     }
+
+    fn into_owned(self) -> AttrValue<'static> {
+        AttrValue {
+            values: self.values.into_iter().map(owned).collect(),
+            order: self.order,
+            param_map: self.param_map.map(ParamMap::into_owned),
+        }
+    }
 }
 
-impl ParamMap {
-    fn new() -> ParamMap {
-        ParamMap(HashMap::new())
+impl<'a> ParamMap<'a> {
+    fn new() -> Self {
+        ParamMap(IndexMap::new())
+    }
+
+    pub(crate) fn get_param(&self, param_val: &str) -> Option<&str> {
+        self.0.get(param_val).map(|s| s.as_ref())
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.0.len()
     }
 
-    pub(crate) fn get_param<'a>(&'a self, param_val: &str) -> Option<&'a str> {
-        self.0
-            .get(&SP::from(param_val))
-            // SAFETY: This is safe because the string has the same lifetime as Entry
-            .map(|s| unsafe { transmute(&*s.0.as_ptr()) })
+    pub(crate) fn param_names_iter(&self) -> ParamNamesIter<'_> {
+        KeysIter(self.0.keys())
+    }
+
+    fn into_owned(self) -> ParamMap<'static> {
+        ParamMap(
+            self.0
+                .into_iter()
+                .map(|(param, value)| (owned(param), owned(value)))
+                .collect(),
+        )
     }
 }
 
-pub(crate) struct KeysIter<'a, T>(Keys<'a, SP, T>);
+pub(crate) struct KeysIter<'a, T>(Keys<'a, Cow<'a, str>, T>);
 
 impl<'a, T> Iterator for KeysIter<'a, T> {
     type Item = &'a str;
     fn next(&mut self) -> Option<Self::Item> {
-        self.0.next().map(|sp| unsafe { transmute(&*sp.0.as_ptr()) })
+        self.0.next().map(|s| s.as_ref())
     }
 }
 
-pub(crate) type SectionNamesIter<'a> = KeysIter<'a, AttrMap>;
-pub(crate) type AttrNamesIter<'a> = KeysIter<'a, AttrValue>;
-pub(crate) type ParamNamesIter<'a> = KeysIter<'a, SP>;
-
-/// str pointer
-#[derive(Eq)]
-pub(crate) struct SP(NonNull<str>);
+pub(crate) type AttrNamesIter<'a> = KeysIter<'a, AttrValue<'a>>;
+pub(crate) type ParamNamesIter<'a> = KeysIter<'a, Cow<'a, str>>;
 
-impl SP {
-    fn from(s: &str) -> Self {
-        SP(NonNull::from(s))
+fn name_eq(ignore_case: bool, a: &str, b: &str) -> bool {
+    if ignore_case {
+        a.eq_ignore_ascii_case(b)
+    } else {
+        a == b
     }
 }
 
-impl PartialEq for SP {
-    fn eq(&self, other: &Self) -> bool {
-        // SAFETY: This is safe because both references are dropped at the end
-        // of the fn
-        unsafe { *self.0.as_ref() == *other.0.as_ref() }
-    }
+fn owned(s: Cow<'_, str>) -> Cow<'static, str> {
+    Cow::Owned(s.into_owned())
 }
 
-impl Hash for SP {
-    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        // SAFETY: This is safe because the reference is dropped at the end
-        // of the fn
-        unsafe { self.0.as_ref().hash(state) }
+/// Decode `input` as UTF-8.  If that fails fall back to the legacy encoding
+/// for `locale` or replacement characters, depending on `options`.
+#[inline]
+fn decode<'a>(
+    input: &'a [u8],
+    #[cfg_attr(not(feature = "encoding_rs"), allow(unused_variables))]
+    locale: Option<&[u8]>,
+    options: &ParserOptions,
+) -> Result<Cow<'a, str>, ParseError> {
+    let e = match std::str::from_utf8(input) {
+        Ok(s) => return Ok(Cow::Borrowed(s)),
+        Err(e) => e,
+    };
+    #[cfg(feature = "encoding_rs")]
+    {
+        if options.legacy_encoding {
+            return Ok(crate::legacy::decode(input, locale));
+        }
     }
+    if options.lossy_utf8 {
+        return Ok(String::from_utf8_lossy(input));
+    }
+    Err(ParseError::Utf8Error {
+        bytes: input.to_owned(),
+        source: e,
+    })
 }
 
-impl Debug for SP {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        // SAFETY: This is safe because the reference is dropped at the end of
-        // the fn
-        unsafe { self.0.as_ref().fmt(f) }
+/// Decode an attribute name or param, normalizing it if `options` say so.
+#[inline]
+fn decode_name<'a>(
+    input: &'a [u8],
+    options: &ParserOptions,
+) -> Result<Cow<'a, str>, ParseError> {
+    let name = decode(input, None, options)?;
+    #[cfg(feature = "unicode-normalization")]
+    {
+        use unicode_normalization::{is_nfc, UnicodeNormalization};
+        if options.normalize_names && !is_nfc(&name) {
+            return Ok(Cow::Owned(name.nfc().collect()));
+        }
     }
+    Ok(name)
 }
 
-#[inline]
-fn parse_str(input: &[u8]) -> Result<&str, ParseError> {
-    std::str::from_utf8(input).map_err(|e| ParseError::Utf8Error {
-        bytes: input.to_owned(),
-        source: e,
-    })
+/// Whether the value for `param` is kept when only the params in `keep` are.
+/// The encoding of the locale is ignored.
+fn keep_param(param: &[u8], keep: &[String]) -> bool {
+    std::str::from_utf8(param)
+        .ok()
+        .and_then(|param| param.parse::<Locale>().ok())
+        .map(|locale| keep.contains(&locale.key()))
+        .unwrap_or(false)
 }
@@ -0,0 +1,55 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Decoding of files written before the spec required UTF-8.
+//!
+//! With `Encoding=Legacy-Mixed` every localized value is encoded in the
+//! charset of its locale, like `Name[ru_RU.KOI8-R]`.  Values without a
+//! charset in their locale are assumed to be Latin-1.
+use encoding_rs::{Encoding, WINDOWS_1252};
+use std::borrow::Cow;
+
+/// Decode a value that isn't valid UTF-8.  `locale` is the param of the
+/// attribute, if it has one.
+pub(crate) fn decode<'a>(
+    input: &'a [u8],
+    locale: Option<&[u8]>,
+) -> Cow<'a, str> {
+    let encoding = locale
+        .and_then(charset)
+        .and_then(Encoding::for_label)
+        .unwrap_or(WINDOWS_1252);
+    encoding.decode_without_bom_handling(input).0
+}
+
+/// The charset part of a locale, `lang_COUNTRY.CHARSET@MODIFIER`
+fn charset(locale: &[u8]) -> Option<&[u8]> {
+    let start = locale.iter().position(|c| *c == b'.')? + 1;
+    let end = locale[start..]
+        .iter()
+        .position(|c| *c == b'@')
+        .map_or(locale.len(), |i| start + i);
+    Some(&locale[start..end])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn charset_from_locale() {
+        assert_eq!(charset(b"ru_RU.KOI8-R"), Some(&b"KOI8-R"[..]));
+        assert_eq!(charset(b"sr_YU.ISO-8859-2@Latn"), Some(&b"ISO-8859-2"[..]));
+        assert_eq!(charset(b"de"), None);
+    }
+
+    #[test]
+    fn decode_legacy() {
+        assert_eq!(decode(b"Caf\xE9", None), "Café");
+        assert_eq!(
+            decode(b"\xF0\xD2\xC9\xD7\xC5\xD4", Some(b"ru_RU.KOI8-R")),
+            "Привет"
+        );
+    }
+}
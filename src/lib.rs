@@ -89,6 +89,9 @@
 //! ```
 //! There are more examples in the [`examples`]() directory.
 //!
+//! [`EntryRef`](struct.EntryRef.html) provides the same API over a borrowed
+//! buffer, without copying it.
+//!
 //! ## Lower Level API
 //!
 //! The lower level api is byte oriented and simply provides an iterator over
@@ -115,60 +118,266 @@
 //! # Ok::<(), freedesktop_entry_parser::ParseError>(())
 //! ```
 
+pub mod autostart;
+/// Entry builder
+mod builder;
+/// Calendar events of timer units
+mod calendar;
+/// Registered categories
+mod categories;
+/// Unit conditions and assertions
+mod condition;
+/// D-Bus activation
+#[cfg(feature = "zbus")]
+mod dbus;
 /// `Debug` trait impls
 mod debug;
+/// `Desktop Entry` section accessors
+mod desktop_entry;
+/// Problems found while parsing
+mod diagnostic;
+/// `.directory` files
+mod directory_entry;
 /// Error types
 pub mod errors;
+pub mod exec;
+/// `X-` extension keys
+mod extension;
+/// Desktop file IDs
+mod file_id;
+/// Icon lookup cache
+mod icon_cache;
+pub mod icon_theme;
+/// Installing entries
+mod install;
 /// Entry map implementation
 mod internal;
+/// Legacy encodings
+#[cfg(feature = "encoding_rs")]
+mod legacy;
+pub mod lint;
+/// Locales of localized attributes
+mod locale;
+/// `entry!` macro
+mod macros;
+/// Parser options
+mod options;
+/// Owned sections
+mod owned;
 /// Low level parser
 mod parser;
+pub mod schema;
+/// Socket units
+mod socket;
+/// Source locations
+mod span;
+pub mod systemd;
+pub mod validate;
+/// Typed attribute values
+mod values;
+/// Serialization to the keyfile format
+mod writer;
 
 /// Low level API
 pub mod low_level {
     pub use crate::parser::parse_entry;
+    pub use crate::parser::parse_entry_with;
     pub use crate::parser::AttrBytes;
     pub use crate::parser::EntryIter;
     pub use crate::parser::SectionBytes;
+    pub use crate::span::Span;
 }
-pub use errors::{ParseError, Result};
+pub use builder::{EntryBuilder, SectionBuilder};
+pub use categories::Category;
+pub use desktop_entry::{
+    DesktopAction, DesktopEntry, DesktopEntryType, SpecVersion, StartupInfo,
+    VisibilityChecks,
+};
+pub use diagnostic::{Diagnostic, Severity};
+pub use directory_entry::DirectoryEntry;
+pub use errors::{EntryError, ParseError, Result};
+pub use extension::{Extension, ExtensionIter};
+pub use file_id::{
+    applications_dirs, dbus_bus_name, dbus_object_path, desktop_file_id,
+    find_desktop_file, find_desktop_file_in,
+};
+pub use install::InstallScope;
+pub use internal::Section;
 use internal::{
-    AttrNamesIter, AttrValue, Internal, ParamMap, ParamNamesIter,
-    SectionNamesIter,
+    AttrMap, AttrNamesIter, AttrValue, Internal, ParamMap, ParamNamesIter,
 };
+pub use locale::{Locale, VariantsIter};
+pub use options::{
+    Duplicates, InvalidNames, LeadingAttrs, LocaleFilter, ParserOptions,
+};
+pub use owned::{IntoSections, OwnedAttr, OwnedSection};
 use std::{
+    borrow::Cow,
     fs::File,
-    io::{self, Read},
+    io::Read,
+    ops::{Deref, Index},
     path::Path,
-    pin::Pin,
+    str::FromStr,
 };
+pub use values::{escape, unescape, ListIter};
 
 /// Parse a FreeDesktop entry file.
 ///
-/// If there is a parse error it'll be return an crate::Error wrapped io::Error
-/// with the ErrorKind::Other.
-pub fn parse_entry(input: impl AsRef<Path>) -> io::Result<Entry> {
+/// Errors reading the file are returned as
+/// [`ParseError::Io`](errors/enum.ParseError.html#variant.Io).
+pub fn parse_entry(input: impl AsRef<Path>) -> Result<Entry> {
     Entry::parse_file(input)
 }
 
-/// Parse a Freedesktop entry.
-pub struct Entry(Pin<Box<Internal>>);
+/// A parsed Freedesktop entry that owns its data.
+///
+/// All the read methods come from [`EntryRef`](struct.EntryRef.html), which
+/// this dereferences to.
+///
+/// Entries are equal if they have the same sections, attributes and values,
+/// regardless of the order they're in.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Entry(EntryRef<'static>);
 
 impl Entry {
+    /// Create an entry with no sections.
+    pub fn new() -> Self {
+        Entry(EntryRef(Internal::empty()))
+    }
+
     /// Parse an entry from byte buffer.
     pub fn parse(input: impl Into<Vec<u8>>) -> Result<Self> {
-        Ok(Entry(Internal::new(input.into())?))
+        Self::parse_with(input, &ParserOptions::default())
+    }
+
+    /// Parse an entry from byte buffer with `options`.
+    pub fn parse_with(
+        input: impl Into<Vec<u8>>,
+        options: &ParserOptions,
+    ) -> Result<Self> {
+        let input = input.into();
+        Ok(Entry(EntryRef(
+            Internal::new(&input, options)?.into_owned(),
+        )))
+    }
+
+    /// Parse an entry from byte buffer with `options` and return the problems
+    /// found along the way.  See [`Diagnostic`](struct.Diagnostic.html).
+    pub fn parse_with_diagnostics(
+        input: impl Into<Vec<u8>>,
+        options: &ParserOptions,
+    ) -> Result<(Self, Vec<Diagnostic>)> {
+        let input = input.into();
+        let (internal, diagnostics) =
+            Internal::with_diagnostics(&input, options)?;
+        Ok((Entry(EntryRef(internal.into_owned())), diagnostics))
+    }
+
+    /// Parse as much of an entry as possible.
+    ///
+    /// Uses [`ParserOptions::lenient`](struct.ParserOptions.html#method.lenient).
+    /// Lines that can't be parsed are skipped and returned as diagnostics
+    /// instead of stopping at the first error.  Should parsing fail anyway,
+    /// the entry is empty and the error is the only diagnostic.
+    pub fn parse_lenient(input: impl Into<Vec<u8>>) -> (Self, Vec<Diagnostic>) {
+        let input = input.into();
+        match Internal::with_diagnostics(&input, &ParserOptions::lenient()) {
+            Ok((internal, diagnostics)) => {
+                (Entry(EntryRef(internal.into_owned())), diagnostics)
+            }
+            // Recovering turns parse errors into diagnostics, duplicates are
+            // allowed and invalid UTF-8 is replaced, so this shouldn't happen
+            Err(e) => (Entry::new(), vec![Diagnostic::from_error(&e)]),
+        }
+    }
+
+    /// Parse an entry from a string.
+    pub fn parse_str(input: impl Into<String>) -> Result<Self> {
+        Self::parse(input.into().into_bytes())
     }
 
     /// Parse entry from file.
     ///
-    /// If there is a parse error it'll be return an crate::Error wrapped io::Error
-    /// with the ErrorKind::Other.
-    pub fn parse_file(path: impl AsRef<Path>) -> io::Result<Self> {
+    /// Errors reading the file are returned as
+    /// [`ParseError::Io`](errors/enum.ParseError.html#variant.Io).
+    pub fn parse_file(path: impl AsRef<Path>) -> Result<Self> {
         let mut file = File::open(path)?;
         let mut buf = Vec::new();
         file.read_to_end(&mut buf)?;
-        Self::parse(buf).map_err(|e| io::Error::new(io::ErrorKind::Other, e))
+        Self::parse(buf)
+    }
+
+    /// Add an empty section with `name`.  Does nothing if the section
+    /// already exists.  The `""` section for attributes before the first
+    /// header is added in front of the other sections.
+    ///
+    /// # Panics
+    /// If `name` contains `[`, `]` or control characters.
+    pub fn add_section(&mut self, name: impl AsRef<str>) {
+        let name = name.as_ref();
+        check_section_name(name);
+        (self.0).0.add_section(name)
+    }
+
+    /// Remove the section with `name` and all of its attributes.
+    ///
+    /// Returns `true` if the section existed.
+    pub fn remove_section(&mut self, name: impl AsRef<str>) -> bool {
+        (self.0).0.remove_section(name.as_ref())
+    }
+
+    /// Get section with `name` for modification.
+    ///
+    /// The section doesn't have to exist, it'll be created when an attribute
+    /// is set.
+    pub fn section_mut<T: AsRef<str>>(
+        &mut self,
+        name: T,
+    ) -> AttrSelectorMut<'_, T> {
+        AttrSelectorMut { name, entry: self }
+    }
+}
+
+impl Deref for Entry {
+    type Target = EntryRef<'static>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// A parsed Freedesktop entry that borrows its input.
+///
+/// This avoids copying the input when it's already in memory, for example
+/// when it's memory mapped or cached.  It's compared and hashed the same way
+/// as [`Entry`](struct.Entry.html).
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct EntryRef<'a>(Internal<'a>);
+
+impl<'a> EntryRef<'a> {
+    /// Parse an entry from a borrowed byte buffer.
+    pub fn parse(input: &'a [u8]) -> Result<Self> {
+        Self::parse_with(input, &ParserOptions::default())
+    }
+
+    /// Parse an entry from a borrowed byte buffer with `options`.
+    pub fn parse_with(
+        input: &'a [u8],
+        options: &ParserOptions,
+    ) -> Result<Self> {
+        Ok(EntryRef(Internal::new(input, options)?))
+    }
+
+    /// Parse an entry from a borrowed byte buffer with `options` and return
+    /// the problems found along the way.
+    /// See [`Diagnostic`](struct.Diagnostic.html).
+    pub fn parse_with_diagnostics(
+        input: &'a [u8],
+        options: &ParserOptions,
+    ) -> Result<(Self, Vec<Diagnostic>)> {
+        let (internal, diagnostics) =
+            Internal::with_diagnostics(input, options)?;
+        Ok((EntryRef(internal), diagnostics))
     }
 
     /// Check if the entry has a section with a `name`.
@@ -177,58 +386,194 @@ impl Entry {
     }
 
     /// Get section with `name`.
-    pub fn section<T: AsRef<str>>(&self, name: T) -> AttrSelector<T> {
-        AttrSelector { name, entry: self }
+    ///
+    /// If there are multiple sections with `name`, this is the last one.
+    pub fn section<T: AsRef<str>>(&self, name: T) -> AttrSelector<'_, T> {
+        let section = self.0.get_section(name.as_ref());
+        AttrSelector { name, section }
     }
 
     /// Iterator over sections.
-    pub fn sections(&self) -> SectionIter {
+    pub fn sections(&self) -> SectionIter<'_> {
         SectionIter {
-            iter: self.0.section_names_iter(),
-            entry: self,
+            iter: self.0.sections(),
+        }
+    }
+
+    /// Iterator over all the sections with `name` in the order they appear.
+    ///
+    /// Some files, like systemd-networkd's `.network` files, can have
+    /// multiple sections with the same name.
+    pub fn sections_named<T: AsRef<str>>(
+        &self,
+        name: T,
+    ) -> SectionsNamedIter<'_, T> {
+        SectionsNamedIter {
+            name,
+            iter: self.0.sections(),
+            entry: &self.0,
         }
     }
 }
 
+impl FromStr for Entry {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse_str(s)
+    }
+}
+
+impl Default for Entry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Get the section `name`, like `entry["Desktop Entry"]["Name"]`.
+///
+/// # Panics
+/// If there's no section `name`.
+impl<'a> Index<&str> for EntryRef<'a> {
+    type Output = Section<'a>;
+
+    fn index(&self, name: &str) -> &Self::Output {
+        self.0
+            .sections()
+            .rev()
+            .find(|section| self.0.name_eq(section.name(), name))
+            .unwrap_or_else(|| panic!("No section `{}` in entry", name))
+    }
+}
+
+/// Get the value of the attribute `name`.
+///
+/// # Panics
+/// If the section has no attribute `name` with a value.
+impl<'a> Index<&str> for Section<'a> {
+    type Output = str;
+
+    fn index(&self, name: &str) -> &Self::Output {
+        self.attrs().get(name, None).unwrap_or_else(|| {
+            panic!("No attribute `{}` in section `{}`", name, self.name())
+        })
+    }
+}
+
+/// Get the value of the attribute `name`.
+///
+/// # Panics
+/// If the section has no attribute `name` with a value.
+impl<'a, T: AsRef<str>> Index<&str> for AttrSelector<'a, T> {
+    type Output = str;
+
+    fn index(&self, name: &str) -> &Self::Output {
+        self.attr(name).unwrap_or_else(|| {
+            panic!("No attribute `{}` in section `{}`", name, self.name())
+        })
+    }
+}
+
 /// Iterate over the sections in an entry.
 ///
-/// Created from [`Entry::sections`](struct.Entry.html#method.sections)
+/// Created from [`EntryRef::sections`](struct.EntryRef.html#method.sections)
 /// Outputs [`AttrSelector`](struct.AttrSelector.html)
 pub struct SectionIter<'a> {
-    iter: SectionNamesIter<'a>,
-    entry: &'a Entry,
+    iter: std::slice::Iter<'a, Section<'a>>,
 }
 
 impl<'a> Iterator for SectionIter<'a> {
     type Item = AttrSelector<'a, &'a str>;
     fn next(&mut self) -> Option<Self::Item> {
-        self.iter.next().map(|name| AttrSelector {
-            name,
-            entry: self.entry,
+        self.iter.next().map(|section| AttrSelector {
+            name: section.name(),
+            section: Some(section.attrs()),
         })
     }
 }
 
+/// Iterate over the sections in an entry with a given name.
+///
+/// Created from [`EntryRef::sections_named`](struct.EntryRef.html#method.sections_named)
+/// Outputs [`AttrSelector`](struct.AttrSelector.html)
+pub struct SectionsNamedIter<'a, T: AsRef<str>> {
+    name: T,
+    iter: std::slice::Iter<'a, Section<'a>>,
+    entry: &'a Internal<'a>,
+}
+
+impl<'a, T: AsRef<str>> Iterator for SectionsNamedIter<'a, T> {
+    type Item = AttrSelector<'a, &'a str>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let name = self.name.as_ref();
+        let entry = self.entry;
+        self.iter
+            .find(|section| entry.name_eq(section.name(), name))
+            .map(|section| AttrSelector {
+                name: section.name(),
+                section: Some(section.attrs()),
+            })
+    }
+}
+
 /// Get attributes and their values from a given section.
 ///
-/// Created from [`Entry::section`](struct.Entry.html#method.section) or
+/// Created from [`EntryRef::section`](struct.EntryRef.html#method.section) or
 /// [`SectionIter`](struct.SectionIter.html)
 pub struct AttrSelector<'a, T: AsRef<str>> {
     name: T,
-    entry: &'a Entry,
+    section: Option<&'a AttrMap<'a>>,
 }
 
 impl<'a, T: AsRef<str>> AttrSelector<'a, T> {
     /// Get the value of the attribute `name`.
     pub fn attr(&self, name: impl AsRef<str>) -> Option<&'a str> {
-        self.entry.0.get(self.name.as_ref(), name.as_ref(), None)
+        self.section?.get(name.as_ref(), None)
+    }
+
+    /// Get the value of the attribute `name` or `default` if it's missing.
+    pub fn attr_or<'b>(
+        &self,
+        name: impl AsRef<str>,
+        default: &'b str,
+    ) -> &'b str
+    where
+        'a: 'b,
+    {
+        self.attr(name).unwrap_or(default)
+    }
+
+    /// Get the value of the attribute `name` or an error naming the section
+    /// and attribute if it's missing.
+    pub fn require(
+        &self,
+        name: impl AsRef<str>,
+    ) -> std::result::Result<&'a str, EntryError> {
+        let name = name.as_ref();
+        self.attr(name).ok_or_else(|| EntryError::MissingKey {
+            section: self.name().to_owned(),
+            attr: name.to_owned(),
+        })
+    }
+
+    /// Iterator over every value of the attribute `name` in the order they
+    /// appear.
+    ///
+    /// Some files, like systemd units, assign the same attribute multiple
+    /// times.  [`attr`](#method.attr) only returns the last value.
+    pub fn attr_values(&self, name: impl AsRef<str>) -> ValueIter<'a> {
+        ValueIter {
+            iter: self
+                .section
+                .and_then(|section| section.get_attr(name.as_ref()))
+                .map(|attr| attr.get_values().iter()),
+        }
     }
 
     /// Check if this section has an attribute with `name`.
     pub fn has_attr(&self, name: impl AsRef<str>) -> bool {
-        self.entry
-            .0
-            .get_attr(self.name.as_ref(), name.as_ref())
+        self.section
+            .and_then(|section| section.get_attr(name.as_ref()))
             .is_some()
     }
 
@@ -237,11 +582,22 @@ impl<'a, T: AsRef<str>> AttrSelector<'a, T> {
         &self,
         name: impl AsRef<str>,
         param_val: impl AsRef<str>,
-    ) -> Option<&str> {
-        let section = self.name.as_ref();
-        self.entry
-            .0
-            .get(section, name.as_ref(), Some(param_val.as_ref()))
+    ) -> Option<&'a str> {
+        self.section?.get(name.as_ref(), Some(param_val.as_ref()))
+    }
+
+    /// Get the value of the attribute `name` and param value `param_val`,
+    /// or the value without a param if there's none for `param_val`.
+    pub fn attr_with_param_or_default(
+        &self,
+        name: impl AsRef<str>,
+        param_val: impl AsRef<str>,
+    ) -> Option<&'a str> {
+        let section = self.section?;
+        let name = name.as_ref();
+        section
+            .get(name, Some(param_val.as_ref()))
+            .or_else(|| section.get(name, None))
     }
 
     /// Check if this section has an attribute with `name` and param value `param_val`.
@@ -250,11 +606,7 @@ impl<'a, T: AsRef<str>> AttrSelector<'a, T> {
         name: impl AsRef<str>,
         param_val: impl AsRef<str>,
     ) -> bool {
-        let section = self.name.as_ref();
-        self.entry
-            .0
-            .get(section, name.as_ref(), Some(param_val.as_ref()))
-            .is_some()
+        self.attr_with_param(name, param_val).is_some()
     }
 
     /// Get this section's name.
@@ -266,8 +618,116 @@ impl<'a, T: AsRef<str>> AttrSelector<'a, T> {
     pub fn attrs(&'a self) -> AttrIter<'a> {
         AttrIter {
             section_name: self.name.as_ref(),
-            iter: self.entry.0.attr_names_iter(self.name.as_ref()),
-            entry: &self.entry,
+            iter: self.section.map(AttrMap::attr_names_iter),
+            section: self.section,
+        }
+    }
+}
+
+/// Modify attributes and their values in a given section.
+///
+/// Values are taken as they're written in a file, with escape sequences like
+/// `\s`, the same way [`AttrSelector::attr`](struct.AttrSelector.html#method.attr)
+/// returns them.  Use [`escape`](fn.escape.html) to set any text.  Line breaks
+/// are escaped when the entry is written, so a value can't add lines.
+///
+/// Created from [`Entry::section_mut`](struct.Entry.html#method.section_mut)
+///
+/// # Panics
+/// The methods setting values panic if the section name contains `[`, `]` or
+/// control characters, or if the attribute name or param value contains
+/// `[`, `]`, `=` or control characters.  The attribute name also can't be
+/// empty.
+pub struct AttrSelectorMut<'a, T: AsRef<str>> {
+    name: T,
+    entry: &'a mut Entry,
+}
+
+impl<'a, T: AsRef<str>> AttrSelectorMut<'a, T> {
+    /// Set the value of the attribute `name`, replacing any previous value.
+    pub fn set_attr(&mut self, name: impl AsRef<str>, value: impl AsRef<str>) {
+        let section = self.name.as_ref();
+        check_names(section, name.as_ref(), None);
+        (self.entry.0)
+            .0
+            .set_value(section, name.as_ref(), None, value.as_ref())
+    }
+
+    /// Add another value to the attribute `name`, keeping the previous ones.
+    ///
+    /// See [`AttrSelector::attr_values`](struct.AttrSelector.html#method.attr_values).
+    pub fn add_attr(&mut self, name: impl AsRef<str>, value: impl AsRef<str>) {
+        let section = self.name.as_ref();
+        check_names(section, name.as_ref(), None);
+        (self.entry.0)
+            .0
+            .add_value(section, name.as_ref(), value.as_ref())
+    }
+
+    /// Set the value of the attribute `name` with param value `param_val`,
+    /// replacing any previous value.
+    pub fn set_attr_with_param(
+        &mut self,
+        name: impl AsRef<str>,
+        param_val: impl AsRef<str>,
+        value: impl AsRef<str>,
+    ) {
+        let section = self.name.as_ref();
+        check_names(section, name.as_ref(), Some(param_val.as_ref()));
+        (self.entry.0).0.set_value(
+            section,
+            name.as_ref(),
+            Some(param_val.as_ref()),
+            value.as_ref(),
+        )
+    }
+
+    /// Remove the attribute `name`, including all of its param values.
+    ///
+    /// Returns `true` if the attribute existed.
+    pub fn remove_attr(&mut self, name: impl AsRef<str>) -> bool {
+        let section = self.name.as_ref();
+        (self.entry.0).0.remove_value(section, name.as_ref(), None)
+    }
+
+    /// Remove the value of the attribute `name` with param value `param_val`.
+    ///
+    /// Returns `true` if the value existed.
+    pub fn remove_attr_with_param(
+        &mut self,
+        name: impl AsRef<str>,
+        param_val: impl AsRef<str>,
+    ) -> bool {
+        let section = self.name.as_ref();
+        (self.entry.0).0.remove_value(
+            section,
+            name.as_ref(),
+            Some(param_val.as_ref()),
+        )
+    }
+
+    /// Get this section's name.
+    pub fn name(&self) -> &str {
+        self.name.as_ref()
+    }
+}
+
+fn check_section_name(name: &str) {
+    if !parser::writable_title(name) {
+        panic!("Invalid section name `{}`", name.escape_debug());
+    }
+}
+
+/// Panic if the names can't be written out without changing the structure of
+/// the file.
+fn check_names(section: &str, name: &str, param_val: Option<&str>) {
+    check_section_name(section);
+    if name.is_empty() || !parser::writable_name(name) {
+        panic!("Invalid attribute name `{}`", name.escape_debug());
+    }
+    if let Some(param_val) = param_val {
+        if !parser::writable_name(param_val) {
+            panic!("Invalid param value `{}`", param_val.escape_debug());
         }
     }
 }
@@ -284,8 +744,7 @@ pub struct Attr<'a> {
     pub name: &'a str,
     /// Value of the attribute if it exists.
     pub value: Option<&'a str>,
-    attr: &'a AttrValue,
-    entry: &'a Entry,
+    attr: &'a AttrValue<'a>,
 }
 
 impl<'a> Attr<'a> {
@@ -299,39 +758,90 @@ impl<'a> Attr<'a> {
         self.attr.get_params().is_some()
     }
 
-    /// Iterator over params
-    pub fn params(&self) -> ParamIter<'a> {
-        ParamIter {
+    /// The params of this attribute, for looking them up by name.
+    pub fn param_map(&self) -> Params<'a> {
+        Params {
             section_name: self.section_name,
             attr_name: self.name,
-            iter: self.entry.0.param_names_iter(self.section_name, self.name),
             params: self.attr.get_params(),
         }
     }
+
+    /// Iterator over every value of this attribute with its param.  The
+    /// values without a param come first, with `None` as the param.
+    pub fn values(&self) -> AttrValuesIter<'a> {
+        AttrValuesIter {
+            values: ValueIter {
+                iter: Some(self.attr.get_values().iter()),
+            },
+            params: self.params(),
+        }
+    }
+
+    /// Iterator over params in the order they appear in the file.  Params
+    /// added later come after them.
+    pub fn params(&self) -> ParamIter<'a> {
+        self.param_map().iter()
+    }
 }
 
 /// Iterates over attributes in a section
 pub struct AttrIter<'a> {
     section_name: &'a str,
     iter: Option<AttrNamesIter<'a>>,
-    entry: &'a Entry,
+    section: Option<&'a AttrMap<'a>>,
 }
 
 impl<'a> Iterator for AttrIter<'a> {
     type Item = Attr<'a>;
     fn next(&mut self) -> Option<Self::Item> {
         let attr_name = self.iter.as_mut()?.next()?;
-        let attr = self.entry.0.get_attr(self.section_name, attr_name)?;
+        let attr = self.section?.get_attr(attr_name)?;
         Some(Attr {
             attr,
             name: attr_name,
             section_name: self.section_name,
-            entry: self.entry,
             value: attr.get_value(),
         })
     }
 }
 
+/// Iterator over the values of an attribute that was assigned multiple
+/// times.
+///
+/// Created from [`AttrSelector::attr_values`](struct.AttrSelector.html#method.attr_values)
+pub struct ValueIter<'a> {
+    iter: Option<std::slice::Iter<'a, Cow<'a, str>>>,
+}
+
+impl<'a> Iterator for ValueIter<'a> {
+    type Item = &'a str;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.as_mut()?.next().map(|s| s.as_ref())
+    }
+}
+
+/// Iterator over all the values of an attribute and their params.
+///
+/// Created from [`Attr::values`](struct.Attr.html#method.values)
+pub struct AttrValuesIter<'a> {
+    values: ValueIter<'a>,
+    params: ParamIter<'a>,
+}
+
+impl<'a> Iterator for AttrValuesIter<'a> {
+    type Item = (Option<&'a str>, &'a str);
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.values.next() {
+            Some(value) => Some((None, value)),
+            None => self
+                .params
+                .next()
+                .map(|param| (Some(param.param_val), param.value)),
+        }
+    }
+}
+
 /// Value of an attribute with a param.
 pub struct AttrParam<'a> {
     /// Section this param is from
@@ -349,7 +859,7 @@ pub struct ParamIter<'a> {
     section_name: &'a str,
     attr_name: &'a str,
     iter: Option<ParamNamesIter<'a>>,
-    params: Option<&'a ParamMap>,
+    params: Option<&'a ParamMap<'a>>,
 }
 
 impl<'a> Iterator for ParamIter<'a> {
@@ -366,6 +876,76 @@ impl<'a> Iterator for ParamIter<'a> {
     }
 }
 
+/// The params of an attribute and their values.
+///
+/// Created from [`Attr::param_map`](struct.Attr.html#method.param_map)
+#[derive(Clone, Copy)]
+pub struct Params<'a> {
+    section_name: &'a str,
+    attr_name: &'a str,
+    params: Option<&'a ParamMap<'a>>,
+}
+
+impl<'a> Params<'a> {
+    /// Get the value for param value `param_val`.
+    pub fn get(&self, param_val: impl AsRef<str>) -> Option<&'a str> {
+        self.params?.get_param(param_val.as_ref())
+    }
+
+    /// Check if there's a value for param value `param_val`.
+    pub fn contains(&self, param_val: impl AsRef<str>) -> bool {
+        self.get(param_val).is_some()
+    }
+
+    /// Number of params
+    pub fn len(&self) -> usize {
+        self.params.map_or(0, ParamMap::len)
+    }
+
+    /// Check if there are no params.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Iterator over params in the order they appear in the file.
+    pub fn iter(&self) -> ParamIter<'a> {
+        ParamIter {
+            section_name: self.section_name,
+            attr_name: self.attr_name,
+            iter: self.params.map(ParamMap::param_names_iter),
+            params: self.params,
+        }
+    }
+}
+
+impl<'a> IntoIterator for Params<'a> {
+    type Item = AttrParam<'a>;
+    type IntoIter = ParamIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> Index<&str> for Params<'a> {
+    type Output = str;
+
+    /// Get the value for a param value.
+    ///
+    /// # Panics
+    ///
+    /// If there's no value for the param value.
+    fn index(&self, param_val: &str) -> &str {
+        match self.get(param_val) {
+            Some(value) => value,
+            None => panic!(
+                "No param `{}` for attribute `{}` in section `{}`",
+                param_val, self.attr_name, self.section_name
+            ),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -379,6 +959,458 @@ mod test {
         );
     }
 
+    #[test]
+    fn modify() {
+        let mut entry = Entry::parse_file("./test_data/sshd.service").unwrap();
+        entry.section_mut("Unit").set_attr("Description", "SSH");
+        entry
+            .section_mut("Desktop Entry")
+            .set_attr_with_param("Name", "de", "Hallo");
+        assert!(entry.section_mut("Service").remove_attr("KillMode"));
+        assert!(!entry.section_mut("Service").remove_attr("KillMode"));
+        assert!(entry.remove_section("Install"));
+
+        assert_eq!(entry.section("Unit").attr("Description"), Some("SSH"));
+        assert_eq!(
+            entry.section("Desktop Entry").attr_with_param("Name", "de"),
+            Some("Hallo")
+        );
+        assert!(!entry.section("Service").has_attr("KillMode"));
+        assert!(!entry.has_section("Install"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid attribute name `Name]\\n[Evil`")]
+    fn set_invalid_name() {
+        Entry::new()
+            .section_mut("Desktop Entry")
+            .set_attr("Name]\n[Evil", "foo");
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid param value `de]=x`")]
+    fn set_invalid_param() {
+        Entry::new()
+            .section_mut("Desktop Entry")
+            .set_attr_with_param("Name", "de]=x", "foo");
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid section name `A]`")]
+    fn add_invalid_section() {
+        Entry::new().add_section("A]");
+    }
+
+    #[test]
+    fn set_names() {
+        let mut entry = Entry::new();
+        entry.add_section("");
+        let mut section = entry.section_mut("Desktop Action new-window");
+        section.set_attr("X-Foo_Bar", "1");
+        section.set_attr_with_param("Name", "sr@latin", "2");
+        let section = entry.section("Desktop Action new-window");
+        assert_eq!(section.attr("X-Foo_Bar"), Some("1"));
+        assert_eq!(section.attr_with_param("Name", "sr@latin"), Some("2"));
+    }
+
+    #[test]
+    fn remove_param() {
+        let mut entry = Entry::new();
+        let mut section = entry.section_mut("Desktop Entry");
+        section.set_attr_with_param("Name", "de", "Hallo");
+        assert!(section.remove_attr_with_param("Name", "de"));
+        assert!(!entry.section("Desktop Entry").has_attr("Name"));
+        assert!(entry.has_section("Desktop Entry"));
+    }
+
+    #[test]
+    fn from_str() {
+        let entry: Entry = "[Desktop Entry]\nName=Firefox".parse().unwrap();
+        assert_eq!(
+            entry.section("Desktop Entry").attr("Name"),
+            Some("Firefox")
+        );
+    }
+
+    #[test]
+    fn borrowed() {
+        let input = b"[Desktop Entry]\nName=Firefox";
+        let entry = EntryRef::parse(input).unwrap();
+        assert_eq!(
+            entry.section("Desktop Entry").attr("Name"),
+            Some("Firefox")
+        );
+    }
+
+    #[test]
+    fn file_order() {
+        let entry = Entry::parse_file("./test_data/sshd.service").unwrap();
+        let sections: Vec<_> =
+            entry.sections().map(|s| s.name().to_owned()).collect();
+        assert_eq!(sections, ["Unit", "Service", "Install"]);
+        let service = entry.section("Service");
+        let attrs: Vec<_> = service.attrs().map(|a| a.name).collect();
+        assert_eq!(attrs, ["ExecStart", "ExecReload", "KillMode", "Restart"]);
+    }
+
+    #[test]
+    fn param_order() {
+        let input =
+            b"[A]\nName[zh]=1\nName=0\nName[de]=2\nName[fr]=3\nName[ar]=4";
+        let mut entry = Entry::parse(&input[..]).unwrap();
+        let params = |entry: &Entry| {
+            let section = entry.section("A");
+            let attr = section.attrs().next().unwrap();
+            attr.params()
+                .map(|p| (p.param_val.to_owned(), p.value.to_owned()))
+                .collect::<Vec<_>>()
+        };
+        let names = |entry: &Entry| {
+            params(entry)
+                .into_iter()
+                .map(|(param, _)| param)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(&entry), ["zh", "de", "fr", "ar"]);
+
+        let mut section = entry.section_mut("A");
+        section.set_attr_with_param("Name", "be", "5");
+        section.set_attr_with_param("Name", "de", "6");
+        assert_eq!(names(&entry), ["zh", "de", "fr", "ar", "be"]);
+        assert_eq!(params(&entry)[1], ("de".to_owned(), "6".to_owned()));
+
+        entry.section_mut("A").remove_attr_with_param("Name", "fr");
+        assert_eq!(names(&entry), ["zh", "de", "ar", "be"]);
+    }
+
+    #[test]
+    fn repeated_attr() {
+        let mut entry = Entry::parse_file("./test_data/sshd.service").unwrap();
+        let unit = entry.section("Unit");
+        assert_eq!(unit.attr("After"), Some("network.target"));
+        assert_eq!(
+            unit.attr_values("After").collect::<Vec<_>>(),
+            ["sshdgenkeys.service", "network.target"]
+        );
+        assert_eq!(unit.attr_values("Before").count(), 0);
+
+        entry
+            .section_mut("Unit")
+            .add_attr("After", "local-fs.target");
+        assert_eq!(entry.section("Unit").attr_values("After").count(), 3);
+        entry
+            .section_mut("Unit")
+            .set_attr("After", "local-fs.target");
+        assert_eq!(entry.section("Unit").attr_values("After").count(), 1);
+    }
+
+    #[test]
+    fn repeated_sections() {
+        let input = b"[Address]\nAddress=10.0.0.1/24\n\n\
+                      [Route]\nGateway=10.0.0.254\n\n\
+                      [Address]\nAddress=10.0.0.2/24\n";
+        let entry = EntryRef::parse(input).unwrap();
+        assert_eq!(entry.sections().count(), 3);
+        let addresses: Vec<_> = entry
+            .sections_named("Address")
+            .filter_map(|section| section.attr("Address"))
+            .collect();
+        assert_eq!(addresses, ["10.0.0.1/24", "10.0.0.2/24"]);
+        assert_eq!(
+            entry.section("Address").attr("Address"),
+            Some("10.0.0.2/24")
+        );
+    }
+
+    #[test]
+    fn empty_sections() {
+        let entry = EntryRef::parse(b"[Icon Theme]\n[apps]\nSize=48\n[places]")
+            .unwrap();
+        let sections: Vec<_> =
+            entry.sections().map(|s| s.name().to_owned()).collect();
+        assert_eq!(sections, ["Icon Theme", "apps", "places"]);
+        assert_eq!(entry.section("places").attrs().count(), 0);
+    }
+
+    #[test]
+    fn strict_duplicates() {
+        let strict = ParserOptions::strict();
+        let input = b"[Unit]\nAfter=a\nAfter=b";
+        assert!(EntryRef::parse_with(input, &ParserOptions::lenient()).is_ok());
+        match EntryRef::parse_with(input, &strict) {
+            Err(ParseError::DuplicateAttr { section, attr }) => {
+                assert_eq!(section, "Unit");
+                assert_eq!(attr, "After");
+            }
+            _ => panic!("Expected duplicate attribute error"),
+        }
+        match EntryRef::parse_with(b"[A]\nB=1\n[A]\nC=1", &strict) {
+            Err(ParseError::DuplicateSection { section }) => {
+                assert_eq!(section, "A")
+            }
+            _ => panic!("Expected duplicate section error"),
+        }
+        assert!(EntryRef::parse_with(b"[A]\nB[de]=1\nB=1", &strict).is_ok());
+    }
+
+    #[test]
+    fn lossy_utf8() {
+        let input = b"[A]\nName=Caf\xE9\nExec=foo";
+        assert!(Entry::parse(&input[..]).is_err());
+        let options = ParserOptions::default().lossy_utf8(true);
+        let entry = EntryRef::parse_with(input, &options).unwrap();
+        assert_eq!(entry.section("A").attr("Name"), Some("Caf\u{FFFD}"));
+        assert_eq!(entry.section("A").attr("Exec"), Some("foo"));
+    }
+
+    #[test]
+    fn parse_lenient() {
+        let input = b"[A]\nB=1\nnot an attribute\nC=2\n[D\nE=3\n[F]\nG=4";
+        assert!(Entry::parse(&input[..]).is_err());
+        let (entry, diagnostics) = Entry::parse_lenient(&input[..]);
+        assert_eq!(entry.section("A").attr("B"), Some("1"));
+        assert_eq!(entry.section("A").attr("C"), Some("2"));
+        assert!(!entry.has_section("D"));
+        assert_eq!(entry.section("F").attr("G"), Some("4"));
+        let lines: Vec<_> = diagnostics.iter().map(|d| d.line).collect();
+        assert_eq!(lines, [3, 5]);
+        assert!(diagnostics.iter().all(|d| d.severity == Severity::Error));
+        let inputs: [&[u8]; 5] = [
+            b"",
+            b"A=1\n[B]\nC=\xff\n[B]\nC=2\nC=3",
+            b"[",
+            b"]\n=\n[\xff]\n[]",
+            b"\xEF\xBB\xBF[A]\r\n[A\r\nB[=1",
+        ];
+        for input in &inputs {
+            Entry::parse_lenient(*input);
+        }
+    }
+
+    #[test]
+    fn warnings() {
+        let input = b"[A]\n[A]\nB=1 \nB=2\nC=a\\sb\\xc\n";
+        let (entry, diagnostics) =
+            EntryRef::parse_with_diagnostics(input, &ParserOptions::default())
+                .unwrap();
+        assert_eq!(entry.section("A").attr_values("B").count(), 2);
+        let found: Vec<_> = diagnostics
+            .iter()
+            .map(|d| (d.severity, d.line, d.column))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (Severity::Warning, 3, 3),
+                (Severity::Warning, 5, 7),
+                (Severity::Warning, 2, 2),
+                (Severity::Warning, 4, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn required() {
+        let entry = Entry::parse(&b"[A]\nB=1"[..]).unwrap();
+        let section = entry.section("A");
+        assert_eq!(section.attr_or("B", "2"), "1");
+        assert_eq!(section.attr_or("C", "2"), "2");
+        assert_eq!(section.require("B").unwrap(), "1");
+        match entry.section("D").require("B") {
+            Err(EntryError::MissingKey { section, attr }) => {
+                assert_eq!((&*section, &*attr), ("D", "B"))
+            }
+            _ => panic!("Expected a missing key error"),
+        }
+    }
+
+    #[test]
+    fn param_or_default() {
+        let entry = Entry::parse(&b"[A]\nB=1\nB[de]=2\nC[de]=3"[..]).unwrap();
+        let section = entry.section("A");
+        assert_eq!(section.attr_with_param_or_default("B", "de"), Some("2"));
+        assert_eq!(section.attr_with_param_or_default("B", "zz"), Some("1"));
+        assert_eq!(section.attr_with_param_or_default("C", "zz"), None);
+    }
+
+    #[test]
+    fn locale_filter() {
+        let input = b"[A]\nName=C\nName[de]=de\nName[de_AT]=de_AT\n\
+                      Name[sr@latin]=sr\nName[x y]=bad";
+        let options = ParserOptions::default()
+            .locales(LocaleFilter::Only(vec!["de_AT.UTF-8".parse().unwrap()]));
+        let entry = Entry::parse_with(&input[..], &options).unwrap();
+        let section = entry.section("A");
+        assert_eq!(section.attr_with_param("Name", "de"), Some("de"));
+        assert_eq!(section.attr_with_param("Name", "de_AT"), Some("de_AT"));
+        assert_eq!(section.attr_with_param("Name", "sr@latin"), None);
+        assert_eq!(section.attr_with_param("Name", "x y"), None);
+
+        let options = ParserOptions::default().locales(LocaleFilter::None);
+        let entry = Entry::parse_with(&input[..], &options).unwrap();
+        let section = entry.section("A");
+        assert_eq!(section.attr_with_param("Name", "de"), None);
+        assert_eq!(section.attr("Name"), Some("C"));
+    }
+
+    #[cfg(feature = "unicode-normalization")]
+    #[test]
+    fn normalize_names() {
+        // `é` as `e` and a combining acute accent
+        let input = "[A]\nName[caf\u{65}\u{301}]=1\nKe\u{301}y=2";
+        let options = ParserOptions::default().normalize_names(true);
+        let entry = Entry::parse_with(input.as_bytes(), &options).unwrap();
+        let section = entry.section("A");
+        assert_eq!(section.attr_with_param("Name", "caf\u{e9}"), Some("1"));
+        assert_eq!(section.attr("K\u{e9}y"), Some("2"));
+    }
+
+    #[test]
+    fn param_map() {
+        let entry =
+            Entry::parse(&b"[A]\nName[de]=1\nName[fr]=2\nB=3"[..]).unwrap();
+        let section = entry.section("A");
+        let mut attrs = section.attrs();
+        let params = attrs.next().unwrap().param_map();
+        assert_eq!(params.get("fr"), Some("2"));
+        assert!(params.contains("de"));
+        assert!(!params.contains("it"));
+        assert_eq!(&params["de"], "1");
+        assert_eq!(params.len(), 2);
+        assert_eq!(params.into_iter().count(), 2);
+        let params = attrs.next().unwrap().param_map();
+        assert!(params.is_empty());
+        assert_eq!(params.get("de"), None);
+    }
+
+    #[test]
+    fn attr_values() {
+        let entry =
+            Entry::parse(&b"[A]\nName[de]=1\nName=2\nName[fr]=3\nName=4"[..])
+                .unwrap();
+        let section = entry.section("A");
+        let attr = section.attrs().next().unwrap();
+        let values: Vec<_> = attr.values().collect();
+        assert_eq!(
+            values,
+            [
+                (None, "2"),
+                (None, "4"),
+                (Some("de"), "1"),
+                (Some("fr"), "3")
+            ]
+        );
+    }
+
+    #[test]
+    fn ignore_case() {
+        let input = b"[Desktop entry]\nname=Foo\nname[de]=Bar";
+        let entry = Entry::parse(&input[..]).unwrap();
+        assert!(!entry.has_section("Desktop Entry"));
+
+        let options = ParserOptions::default().ignore_case(true);
+        let mut entry = Entry::parse_with(&input[..], &options).unwrap();
+        let section = entry.section("Desktop Entry");
+        assert_eq!(section.attr("Name"), Some("Foo"));
+        assert_eq!(section.attr_with_param("NAME", "de"), Some("Bar"));
+        assert_eq!(entry.sections_named("desktop ENTRY").count(), 1);
+        entry.section_mut("Desktop Entry").set_attr("NAME", "Baz");
+        assert_eq!(entry.section("desktop entry").attr("name"), Some("Baz"));
+        assert_eq!(entry.sections().next().unwrap().attrs().count(), 1);
+    }
+
+    #[test]
+    fn ignore_case_duplicates() {
+        let options = ParserOptions::default()
+            .ignore_case(true)
+            .duplicates(Duplicates::Error);
+        let duplicate =
+            |input: &str| match Entry::parse_with(input.as_bytes(), &options) {
+                Err(ParseError::DuplicateSection { section }) => section,
+                Err(ParseError::DuplicateAttr { attr, .. }) => attr,
+                Err(e) => panic!("Unexpected error {}", e),
+                Ok(_) => String::new(),
+            };
+        assert_eq!(
+            duplicate("[Desktop Entry]\nA=1\n[Desktop entry]\nB=2"),
+            "Desktop entry"
+        );
+        assert_eq!(duplicate("[A]\nName=1\nname=2"), "name");
+        assert_eq!(duplicate("[A]\nName[de]=1\nNAME[de]=2"), "NAME[de]");
+        assert_eq!(duplicate("[A]\nName[de]=1\nNAME[DE]=2\nname=3"), "");
+
+        let input = b"[A]\nName=1\n[a]\nname=2";
+        let options = ParserOptions::lenient().ignore_case(true);
+        let (_, diagnostics) =
+            Entry::parse_with_diagnostics(&input[..], &options).unwrap();
+        let lines: Vec<_> = diagnostics.iter().map(|d| d.line).collect();
+        assert_eq!(lines, [3]);
+    }
+
+    #[test]
+    fn index() {
+        let entry = Entry::parse_file("./test_data/sshd.service").unwrap();
+        assert_eq!(&entry["Unit"]["Description"], "OpenSSH Daemon");
+        assert_eq!(&entry.section("Service")["KillMode"], "process");
+        assert_eq!(entry["Install"].name(), "Install");
+    }
+
+    #[test]
+    #[should_panic(expected = "No attribute `Foo` in section `Unit`")]
+    fn index_missing() {
+        let entry = Entry::parse_file("./test_data/sshd.service").unwrap();
+        let _ = &entry["Unit"]["Foo"];
+    }
+
+    #[test]
+    fn clone() {
+        let mut entry = Entry::parse_file("./test_data/sshd.service").unwrap();
+        let copy = entry.clone();
+        entry.section_mut("Unit").set_attr("Description", "Changed");
+        assert_eq!(
+            copy.section("Unit").attr("Description"),
+            Some("OpenSSH Daemon")
+        );
+        assert_eq!(
+            copy.to_string(),
+            Entry::parse_file("./test_data/sshd.service")
+                .unwrap()
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn eq_hash() {
+        use std::collections::HashSet;
+
+        let a =
+            Entry::parse(&b"[A]\nB=1\nC[de]=2\nC[fr]=3\n[D]\nE=4"[..]).unwrap();
+        let b =
+            Entry::parse(&b"[D]\nE=4\n[A]\nC[fr]=3\nC[de]=2\nB=1"[..]).unwrap();
+        let c = Entry::parse(&b"[A]\nB=1\n[D]\nE=4"[..]).unwrap();
+        assert!(a == b);
+        assert!(a != c);
+        let set: HashSet<_> = vec![a, b, c].into_iter().collect();
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Entry>();
+        assert_send_sync::<EntryRef>();
+    }
+
+    #[test]
+    fn missing_file() {
+        match Entry::parse_file("./test_data/does_not_exist.desktop") {
+            Err(ParseError::Io(e)) => {
+                assert_eq!(e.kind(), std::io::ErrorKind::NotFound)
+            }
+            _ => panic!("Expected an I/O error"),
+        }
+    }
+
     #[test]
     fn drop() {
         let entry = Entry::parse_file("./test_data/sshd.service").unwrap();
@@ -0,0 +1,306 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Style checks for desktop entries, on top of the ones from
+//! [`validate`](../validate/index.html).
+//!
+//! Each check is a [`Rule`](enum.Rule.html) with a name and a severity, so
+//! tools can turn rules off or change their severity and match on the
+//! results.
+//!
+//! Example:
+//! ```
+//! use freedesktop_entry_parser::{lint::{Linter, Rule}, Entry};
+//!
+//! let entry = Entry::parse(
+//!     &b"[Desktop Entry]\nIcon=foo.png\nCategories=Utility\nExec=foo %m"[..],
+//! )?;
+//! let lints = Linter::default().disable(Rule::IconExtension).lint(&entry);
+//! let rules: Vec<_> = lints.iter().map(|lint| lint.rule.name()).collect();
+//! assert_eq!(rules, ["list-terminator", "deprecated-field-code"]);
+//! # Ok::<(), freedesktop_entry_parser::ParseError>(())
+//! ```
+use crate::{
+    validate::{Kind, ACTION_KEYS, DEPRECATED_KEYS, ENTRY_KEYS},
+    AttrSelector, EntryRef, Severity,
+};
+use std::fmt::{self, Display, Formatter};
+
+/// A style check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rule {
+    /// A key the spec deprecates, like `Encoding`
+    DeprecatedKey,
+    /// A list, like `Categories`, that doesn't end with `;`
+    ListTerminator,
+    /// An `Icon` name with a file extension, like `foo.png`.  Only absolute
+    /// paths should have one.
+    IconExtension,
+    /// An `Exec` with a deprecated field code, like `%m`
+    DeprecatedFieldCode,
+}
+
+impl Rule {
+    /// All the rules
+    pub const ALL: &'static [Rule] = &[
+        Rule::DeprecatedKey,
+        Rule::ListTerminator,
+        Rule::IconExtension,
+        Rule::DeprecatedFieldCode,
+    ];
+
+    /// The name of the rule, like `deprecated-key`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Rule::DeprecatedKey => "deprecated-key",
+            Rule::ListTerminator => "list-terminator",
+            Rule::IconExtension => "icon-extension",
+            Rule::DeprecatedFieldCode => "deprecated-field-code",
+        }
+    }
+
+    /// The severity of the rule unless it's changed with
+    /// [`Linter::rule`](struct.Linter.html#method.rule).  None of the
+    /// current rules are about things the spec requires, so they're all
+    /// warnings.
+    pub fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+}
+
+impl Display for Rule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A problem found by a [`Rule`](enum.Rule.html).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lint {
+    /// The rule that found the problem
+    pub rule: Rule,
+    /// How serious the problem is
+    pub severity: Severity,
+    /// Section the problem is in
+    pub section: String,
+    /// Attribute the problem is with
+    pub attr: Option<String>,
+    /// Description of the problem
+    pub message: String,
+}
+
+impl Display for Lint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}]", self.section)?;
+        if let Some(attr) = &self.attr {
+            write!(f, " {}", attr)?;
+        }
+        write!(f, ": {}: {} ({})", self.severity, self.message, self.rule)
+    }
+}
+
+/// Runs a set of rules.  By default all rules run with their default
+/// severity.
+#[derive(Debug, Clone)]
+pub struct Linter {
+    rules: Vec<(Rule, Severity)>,
+}
+
+impl Default for Linter {
+    fn default() -> Self {
+        Self {
+            rules: Rule::ALL
+                .iter()
+                .map(|rule| (*rule, rule.default_severity()))
+                .collect(),
+        }
+    }
+}
+
+impl Linter {
+    /// Run `rule` with `severity`.
+    pub fn rule(mut self, rule: Rule, severity: Severity) -> Self {
+        self = self.disable(rule);
+        self.rules.push((rule, severity));
+        self
+    }
+
+    /// Don't run `rule`.
+    pub fn disable(mut self, rule: Rule) -> Self {
+        self.rules.retain(|(r, _)| *r != rule);
+        self
+    }
+
+    /// Check `entry` with the rules.  Problems are ordered by section, then
+    /// by attribute.
+    pub fn lint(&self, entry: &EntryRef<'_>) -> Vec<Lint> {
+        let mut lints = Vec::new();
+        for section in entry.sections() {
+            let name = section.name();
+            let keys = if name == "Desktop Entry" {
+                ENTRY_KEYS
+            } else if name.starts_with("Desktop Action ") {
+                ACTION_KEYS
+            } else {
+                continue;
+            };
+            self.section(&section, keys, &mut lints);
+        }
+        lints
+    }
+
+    fn section(
+        &self,
+        section: &AttrSelector<'_, &str>,
+        keys: &[(&str, Kind)],
+        lints: &mut Vec<Lint>,
+    ) {
+        for attr in section.attrs() {
+            let kind = keys
+                .iter()
+                .find(|(key, _)| *key == attr.name)
+                .map(|(_, kind)| *kind);
+            for (rule, severity) in &self.rules {
+                let message = match rule {
+                    Rule::DeprecatedKey => deprecated_key(attr.name),
+                    Rule::ListTerminator => match kind {
+                        Some(Kind::Strings) | Some(Kind::LocaleStrings) => attr
+                            .values()
+                            .find_map(|(_, value)| list_terminator(value)),
+                        _ => None,
+                    },
+                    Rule::IconExtension if attr.name == "Icon" => {
+                        attr.value.and_then(icon_extension)
+                    }
+                    Rule::DeprecatedFieldCode if attr.name == "Exec" => {
+                        attr.value.and_then(deprecated_field_code)
+                    }
+                    _ => None,
+                };
+                if let Some(message) = message {
+                    lints.push(Lint {
+                        rule: *rule,
+                        severity: *severity,
+                        section: section.name().to_owned(),
+                        attr: Some(attr.name.to_owned()),
+                        message,
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Check `entry` with all the rules, see [`Linter`](struct.Linter.html).
+pub fn lint(entry: &EntryRef<'_>) -> Vec<Lint> {
+    Linter::default().lint(entry)
+}
+
+fn deprecated_key(key: &str) -> Option<String> {
+    if DEPRECATED_KEYS.contains(&key) {
+        Some(format!("`{}` is deprecated", key))
+    } else {
+        None
+    }
+}
+
+fn list_terminator(value: &str) -> Option<String> {
+    // `\;` is an escaped `;`, not the end of an item
+    let terminated = value.ends_with(';') && !value.ends_with(r"\;");
+    if value.is_empty() || terminated {
+        None
+    } else {
+        Some("List should end with `;`".to_owned())
+    }
+}
+
+fn icon_extension(icon: &str) -> Option<String> {
+    if icon.starts_with('/') {
+        return None;
+    }
+    let extension = icon.rsplit_once('.').map(|(_, ext)| ext)?;
+    if ["png", "svg", "xpm"].contains(&extension) {
+        Some(format!(
+            "Icon name has the extension `.{}`, which should only be used \
+             with absolute paths",
+            extension
+        ))
+    } else {
+        None
+    }
+}
+
+fn deprecated_field_code(exec: &str) -> Option<String> {
+    let mut chars = exec.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            continue;
+        }
+        if let Some(code @ ('d' | 'D' | 'n' | 'N' | 'v' | 'm')) = chars.next() {
+            return Some(format!("Field code `%{}` is deprecated", code));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Entry;
+
+    fn rules(input: &str, linter: &Linter) -> Vec<(Rule, Severity)> {
+        let entry = Entry::parse_str(input).unwrap();
+        linter
+            .lint(&entry)
+            .into_iter()
+            .map(|lint| (lint.rule, lint.severity))
+            .collect()
+    }
+
+    #[test]
+    fn rules_found() {
+        let input = "[Desktop Entry]\nEncoding=UTF-8\nIcon=foo.svg\n\
+                     MimeType=text/plain\nKeywords[de]=a\\;\n\
+                     Categories=Utility;\nExec=foo 100%%m\n\
+                     [Desktop Action a]\nExec=foo %d\nIcon=/foo.png\n\
+                     [X-Foo]\nExec=foo %d";
+        let linter = Linter::default();
+        let warning = Severity::Warning;
+        assert_eq!(
+            rules(input, &linter),
+            [
+                (Rule::DeprecatedKey, warning),
+                (Rule::IconExtension, warning),
+                (Rule::ListTerminator, warning),
+                (Rule::ListTerminator, warning),
+                (Rule::DeprecatedFieldCode, warning),
+            ]
+        );
+        let linter = Linter::default()
+            .rule(Rule::DeprecatedFieldCode, Severity::Error)
+            .disable(Rule::ListTerminator)
+            .disable(Rule::DeprecatedKey);
+        assert_eq!(
+            rules(input, &linter),
+            [
+                (Rule::IconExtension, warning),
+                (Rule::DeprecatedFieldCode, Severity::Error),
+            ]
+        );
+    }
+
+    #[test]
+    fn repeated_section() {
+        let input = "[Desktop Entry]\nIcon=foo.svg\n[Desktop Entry]\nIcon=foo";
+        assert_eq!(
+            rules(input, &Linter::default()),
+            [(Rule::IconExtension, Severity::Warning)]
+        );
+        let input = "[Desktop Entry]\nIcon=foo\n[Desktop Entry]\nIcon=foo.svg";
+        assert_eq!(
+            rules(input, &Linter::default()),
+            [(Rule::IconExtension, Severity::Warning)]
+        );
+    }
+}
@@ -0,0 +1,619 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::{errors::InvalidLocale, AttrSelector};
+use std::{
+    env,
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+/// A locale in the `lang_COUNTRY.ENCODING@MODIFIER` form used by the params
+/// of localized attributes, like `Name[sr_RS@latin]`.  Everything but `lang`
+/// is optional.
+///
+/// A `Locale` can be used anywhere a param value is expected, for example
+/// with [`AttrSelector::attr_with_param`](struct.AttrSelector.html#method.attr_with_param).
+///
+/// Example:
+/// ```
+/// use freedesktop_entry_parser::{Entry, Locale};
+///
+/// let locale: Locale = "sr_RS@latin".parse()?;
+/// assert_eq!(locale.lang(), "sr");
+/// assert_eq!(locale.country(), Some("RS"));
+/// assert_eq!(locale.modifier(), Some("latin"));
+///
+/// let entry = Entry::parse(&b"[Desktop Entry]\nName[sr_RS@latin]=Foo"[..])?;
+/// let name = entry.section("Desktop Entry").attr_with_param("Name", &locale);
+/// assert_eq!(name, Some("Foo"));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Locale {
+    text: String,
+    lang: usize,
+    country: Option<(usize, usize)>,
+    encoding: Option<(usize, usize)>,
+    modifier: Option<usize>,
+}
+
+impl Locale {
+    /// Build a locale from its parts.
+    pub fn new(
+        lang: &str,
+        country: Option<&str>,
+        encoding: Option<&str>,
+        modifier: Option<&str>,
+    ) -> Result<Self, InvalidLocale> {
+        let mut text = lang.to_owned();
+        if let Some(country) = country {
+            text.push('_');
+            text.push_str(country);
+        }
+        if let Some(encoding) = encoding {
+            text.push('.');
+            text.push_str(encoding);
+        }
+        if let Some(modifier) = modifier {
+            text.push('@');
+            text.push_str(modifier);
+        }
+        text.parse()
+    }
+
+    /// The language, like `sr`
+    pub fn lang(&self) -> &str {
+        &self.text[..self.lang]
+    }
+
+    /// The country, like `RS`
+    pub fn country(&self) -> Option<&str> {
+        self.country.map(|(start, end)| &self.text[start..end])
+    }
+
+    /// The encoding, like `UTF-8`
+    pub fn encoding(&self) -> Option<&str> {
+        self.encoding.map(|(start, end)| &self.text[start..end])
+    }
+
+    /// The modifier, like `latin`
+    pub fn modifier(&self) -> Option<&str> {
+        self.modifier.map(|start| &self.text[start..])
+    }
+
+    /// The locale as it's written in a param.
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// The user's preferred locales, from the most to the least preferred.
+    ///
+    /// Like gettext, this uses the `:` separated list in `LANGUAGE`
+    /// followed by the first of `LC_ALL`, `LC_MESSAGES` and `LANG` that is
+    /// set.  Values that aren't valid locales and the `C` and `POSIX`
+    /// locales, which have no translations, are left out.  Pass the result
+    /// to [`AttrSelector::attr_preferred`](struct.AttrSelector.html#method.attr_preferred).
+    pub fn from_env() -> Vec<Locale> {
+        from_vars(|var| env::var(var).ok())
+    }
+
+    /// The locale without its encoding, which is what's compared when
+    /// matching locales.
+    pub(crate) fn key(&self) -> String {
+        let mut key = self.lang().to_owned();
+        if let Some(country) = self.country() {
+            key.push('_');
+            key.push_str(country);
+        }
+        if let Some(modifier) = self.modifier() {
+            key.push('@');
+            key.push_str(modifier);
+        }
+        key
+    }
+
+    /// The params to try when looking up a value for this locale, from the
+    /// best match to the worst.  The encoding is ignored, as the spec
+    /// requires.
+    pub(crate) fn fallbacks(&self) -> Vec<String> {
+        let lang = self.lang();
+        let mut fallbacks = Vec::with_capacity(4);
+        match (self.country(), self.modifier()) {
+            (Some(country), Some(modifier)) => {
+                fallbacks.push(self.key());
+                fallbacks.push(format!("{}_{}", lang, country));
+                fallbacks.push(format!("{}@{}", lang, modifier));
+            }
+            (Some(country), None) => {
+                fallbacks.push(format!("{}_{}", lang, country))
+            }
+            (None, Some(modifier)) => {
+                fallbacks.push(format!("{}@{}", lang, modifier))
+            }
+            (None, None) => {}
+        }
+        fallbacks.push(lang.to_owned());
+        fallbacks
+    }
+}
+
+impl<'a, T: AsRef<str>> AttrSelector<'a, T> {
+    /// Get the value of the localized attribute `name` that best matches
+    /// `locale`.
+    ///
+    /// Following the spec, for `lang_COUNTRY@MODIFIER` the params
+    /// `lang_COUNTRY@MODIFIER`, `lang_COUNTRY`, `lang@MODIFIER` and `lang`
+    /// are tried in that order, then the value without a param.  The
+    /// encodings of `locale` and of the params are ignored.
+    ///
+    /// Example:
+    /// ```
+    /// use freedesktop_entry_parser::{Entry, Locale};
+    ///
+    /// let entry = Entry::parse(
+    ///     &b"[A]\nName=Files\nName[de]=Dateien\nName[sr@latin]=Datoteke"[..],
+    /// )?;
+    /// let section = entry.section("A");
+    /// let name = |locale: &str| {
+    ///     section.attr_localized("Name", &locale.parse().unwrap())
+    /// };
+    /// assert_eq!(name("de_AT.UTF-8"), Some("Dateien"));
+    /// assert_eq!(name("sr_RS@latin"), Some("Datoteke"));
+    /// assert_eq!(name("fr_FR"), Some("Files"));
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn attr_localized(
+        &self,
+        name: impl AsRef<str>,
+        locale: &Locale,
+    ) -> Option<&'a str> {
+        self.attr_preferred(name, std::slice::from_ref(locale))
+    }
+
+    /// Get the value of the localized attribute `name` for the first of
+    /// `locales` that has one, like the list from
+    /// [`Locale::from_env`](struct.Locale.html#method.from_env).  Each
+    /// locale is matched as in [`attr_localized`](#method.attr_localized).
+    /// Falls back to the value without a param.
+    ///
+    /// Example:
+    /// ```no_run
+    /// use freedesktop_entry_parser::{parse_entry, Locale};
+    ///
+    /// let entry = parse_entry("/usr/share/applications/firefox.desktop")?;
+    /// let locales = Locale::from_env();
+    /// let name = entry.section("Desktop Entry").attr_preferred("Name", &locales);
+    /// # Ok::<(), freedesktop_entry_parser::ParseError>(())
+    /// ```
+    pub fn attr_preferred(
+        &self,
+        name: impl AsRef<str>,
+        locales: &[Locale],
+    ) -> Option<&'a str> {
+        let name = name.as_ref();
+        self.localized_value(name, locales)
+            .or_else(|| self.attr(name))
+    }
+
+    /// Get both the value of the attribute `name` without a param and its
+    /// localized value for the first of `locales` that has one, matched as
+    /// in [`attr_preferred`](#method.attr_preferred).  The localized value
+    /// is `None` if none of `locales` has a translation.
+    ///
+    /// Useful to index an entry by both its untranslated and translated
+    /// names.
+    ///
+    /// Example:
+    /// ```
+    /// use freedesktop_entry_parser::Entry;
+    ///
+    /// let entry = Entry::parse(&b"[A]\nName=Files\nName[de]=Dateien"[..])?;
+    /// let section = entry.section("A");
+    /// assert_eq!(
+    ///     section.attr_pair("Name", &["de_DE".parse()?]),
+    ///     (Some("Files"), Some("Dateien"))
+    /// );
+    /// assert_eq!(
+    ///     section.attr_pair("Name", &["fr".parse()?]),
+    ///     (Some("Files"), None)
+    /// );
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn attr_pair(
+        &self,
+        name: impl AsRef<str>,
+        locales: &[Locale],
+    ) -> (Option<&'a str>, Option<&'a str>) {
+        let name = name.as_ref();
+        (self.attr(name), self.localized_value(name, locales))
+    }
+
+    /// The value of `name` with the param that best matches `locales`.  The
+    /// encoding of the params is ignored like that of the locales, so
+    /// `Name[de_DE.UTF-8]` matches `de_DE`.
+    fn localized_value(
+        &self,
+        name: &str,
+        locales: &[Locale],
+    ) -> Option<&'a str> {
+        let params = self.section?.get_attr(name)?.get_params()?;
+        let keys: Vec<(String, &'a str)> = params
+            .param_names_iter()
+            .filter_map(|param| {
+                let key = match param.parse::<Locale>() {
+                    Ok(locale) => locale.key(),
+                    Err(_) => param.to_owned(),
+                };
+                Some((key, params.get_param(param)?))
+            })
+            .collect();
+        locales
+            .iter()
+            .flat_map(Locale::fallbacks)
+            .find_map(|fallback| {
+                params.get_param(&fallback).or_else(|| {
+                    keys.iter()
+                        .find(|(key, _)| *key == fallback)
+                        .map(|(_, value)| *value)
+                })
+            })
+    }
+
+    /// Iterator over all values of the localized attribute `name` with
+    /// their locale, `None` for the value without a param.
+    ///
+    /// Values are ordered by how well they match `locales`, best first, as
+    /// in [`attr_preferred`](#method.attr_preferred).  Then comes the value
+    /// without a param, then the other values in file order.  Params that
+    /// aren't valid locales are skipped.
+    ///
+    /// Example:
+    /// ```
+    /// use freedesktop_entry_parser::{Entry, Locale};
+    ///
+    /// let entry =
+    ///     Entry::parse(&b"[A]\nName=Files\nName[fr]=Fichiers\nName[de]=Dateien"[..])?;
+    /// let locales = vec!["de_DE".parse()?];
+    /// let names: Vec<_> = entry
+    ///     .section("A")
+    ///     .attr_variants("Name", &locales)
+    ///     .map(|(_, name)| name)
+    ///     .collect();
+    /// assert_eq!(names, ["Dateien", "Files", "Fichiers"]);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn attr_variants(
+        &self,
+        name: impl AsRef<str>,
+        locales: &[Locale],
+    ) -> VariantsIter<'a> {
+        let attr = self
+            .section
+            .and_then(|section| section.get_attr(name.as_ref()));
+        let mut variants = Vec::new();
+        if let Some(value) = attr.and_then(|attr| attr.get_value()) {
+            variants.push((None, value));
+        }
+        if let Some(params) = attr.and_then(|attr| attr.get_params()) {
+            for param in params.param_names_iter() {
+                let (locale, value) =
+                    match (param.parse::<Locale>(), params.get_param(param)) {
+                        (Ok(locale), Some(value)) => (locale, value),
+                        _ => continue,
+                    };
+                variants.push((Some(locale), value));
+            }
+        }
+        let fallbacks: Vec<String> =
+            locales.iter().flat_map(Locale::fallbacks).collect();
+        // Sorting is stable, so unmatched values keep the file order
+        variants.sort_by_key(|(locale, _)| match locale {
+            Some(locale) => {
+                let key = locale.key();
+                fallbacks
+                    .iter()
+                    .position(|fallback| *fallback == key)
+                    .unwrap_or(fallbacks.len() + 1)
+            }
+            None => fallbacks.len(),
+        });
+        VariantsIter {
+            iter: variants.into_iter(),
+        }
+    }
+}
+
+impl FromStr for Locale {
+    type Err = InvalidLocale;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidLocale(s.to_owned());
+        let (rest, modifier) = match s.find('@') {
+            Some(i) => (&s[..i], Some(i + 1)),
+            None => (s, None),
+        };
+        let (rest, encoding) = match rest.find('.') {
+            Some(i) => (&rest[..i], Some((i + 1, rest.len()))),
+            None => (rest, None),
+        };
+        let (lang, country) = match rest.find('_') {
+            Some(i) => (&rest[..i], Some((i + 1, rest.len()))),
+            None => (rest, None),
+        };
+        let locale = Locale {
+            text: s.to_owned(),
+            lang: lang.len(),
+            country,
+            encoding,
+            modifier,
+        };
+        let valid = valid_part(Some(locale.lang()), false)
+            && valid_part(locale.country(), false)
+            && valid_part(locale.encoding(), true)
+            && valid_part(locale.modifier(), false);
+        if valid {
+            Ok(locale)
+        } else {
+            Err(invalid())
+        }
+    }
+}
+
+/// Iterator over the localized variants of an attribute.
+///
+/// Created by [`AttrSelector::attr_variants`](struct.AttrSelector.html#method.attr_variants)
+pub struct VariantsIter<'a> {
+    iter: std::vec::IntoIter<(Option<Locale>, &'a str)>,
+}
+
+impl<'a> Iterator for VariantsIter<'a> {
+    type Item = (Option<Locale>, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+/// The preferred locales with `var` used to read the environment variables.
+fn from_vars(var: impl Fn(&str) -> Option<String>) -> Vec<Locale> {
+    let var = |name| var(name).filter(|value| !value.is_empty());
+    let default = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .find_map(|v| var(v));
+    // gettext ignores LANGUAGE when the locale is C
+    let default = match default {
+        Some(default) if !is_c(&default) => default,
+        _ => return Vec::new(),
+    };
+    let mut locales: Vec<Locale> = Vec::new();
+    let language = var("LANGUAGE").unwrap_or_default();
+    for value in language.split(':').chain(Some(&*default)) {
+        if is_c(value) {
+            continue;
+        }
+        if let Ok(locale) = value.parse() {
+            if !locales.contains(&locale) {
+                locales.push(locale);
+            }
+        }
+    }
+    locales
+}
+
+fn is_c(locale: &str) -> bool {
+    let lang = locale.split(&['.', '@'][..]).next().unwrap_or("");
+    lang == "C" || lang == "POSIX"
+}
+
+/// Parts are ASCII letters and digits, encodings can also have `-` and `_`.
+/// Missing parts are valid.
+fn valid_part(part: Option<&str>, encoding: bool) -> bool {
+    let part = match part {
+        Some(part) => part,
+        None => return true,
+    };
+    !part.is_empty()
+        && part.bytes().all(|c| {
+            c.is_ascii_alphanumeric() || (encoding && (c == b'-' || c == b'_'))
+        })
+}
+
+impl AsRef<str> for Locale {
+    fn as_ref(&self) -> &str {
+        &self.text
+    }
+}
+
+impl Display for Locale {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parts(
+        s: &str,
+    ) -> (String, Option<String>, Option<String>, Option<String>) {
+        let locale: Locale = s.parse().unwrap();
+        (
+            locale.lang().to_owned(),
+            locale.country().map(str::to_owned),
+            locale.encoding().map(str::to_owned),
+            locale.modifier().map(str::to_owned),
+        )
+    }
+
+    #[test]
+    fn parse() {
+        let s = |s: &str| Some(s.to_owned());
+        assert_eq!(parts("de"), ("de".to_owned(), None, None, None));
+        assert_eq!(
+            parts("sr_RS@latin"),
+            ("sr".to_owned(), s("RS"), None, s("latin"))
+        );
+        assert_eq!(
+            parts("ca.UTF-8"),
+            ("ca".to_owned(), None, s("UTF-8"), None)
+        );
+        assert_eq!(
+            parts("sr_YU.ISO-8859-2@Latn"),
+            ("sr".to_owned(), s("YU"), s("ISO-8859-2"), s("Latn"))
+        );
+        for bad in &["", "_US", "en_", "en@", "en US", "de_DE@x y"] {
+            assert!(bad.parse::<Locale>().is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn localized() {
+        let entry = crate::Entry::parse(
+            &b"[A]\nName=C\nName[sr]=sr\nName[sr_RS]=sr_RS\n\
+               Name[sr@latin]=sr@latin\nName[sr_RS@latin]=sr_RS@latin"[..],
+        )
+        .unwrap();
+        let section = entry.section("A");
+        let name = |locale: &str| {
+            section.attr_localized("Name", &locale.parse().unwrap())
+        };
+        assert_eq!(name("sr_RS.UTF-8@latin"), Some("sr_RS@latin"));
+        assert_eq!(name("sr_RS"), Some("sr_RS"));
+        assert_eq!(name("sr_ME@latin"), Some("sr@latin"));
+        assert_eq!(name("sr_ME"), Some("sr"));
+        assert_eq!(name("sr@ijekavian"), Some("sr"));
+        assert_eq!(name("de_DE"), Some("C"));
+        let locale = "de".parse().unwrap();
+        assert_eq!(section.attr_localized("Missing", &locale), None);
+    }
+
+    #[test]
+    fn env() {
+        let locales = |vars: &[(&str, &str)]| {
+            from_vars(|name| {
+                vars.iter()
+                    .find(|(var, _)| *var == name)
+                    .map(|(_, value)| value.to_string())
+            })
+            .iter()
+            .map(Locale::to_string)
+            .collect::<Vec<_>>()
+        };
+        assert_eq!(locales(&[("LANG", "de_DE.UTF-8")]), ["de_DE.UTF-8"]);
+        assert_eq!(
+            locales(&[
+                ("LANGUAGE", "sr@latin:fr::de_DE.UTF-8"),
+                ("LC_ALL", ""),
+                ("LC_MESSAGES", "de_DE.UTF-8"),
+                ("LANG", "en_US.UTF-8"),
+            ]),
+            ["sr@latin", "fr", "de_DE.UTF-8"]
+        );
+        assert!(locales(&[("LANGUAGE", "fr"), ("LANG", "C.UTF-8")]).is_empty());
+        assert!(locales(&[("LANGUAGE", "fr")]).is_empty());
+    }
+
+    #[test]
+    fn preferred() {
+        let entry =
+            crate::Entry::parse(&b"[A]\nName=C\nName[fr]=fr\nName[de]=de"[..])
+                .unwrap();
+        let section = entry.section("A");
+        let locales: Vec<Locale> = vec![
+            "it_IT".parse().unwrap(),
+            "de_AT".parse().unwrap(),
+            "fr".parse().unwrap(),
+        ];
+        assert_eq!(section.attr_preferred("Name", &locales), Some("de"));
+        assert_eq!(section.attr_preferred("Name", &locales[..1]), Some("C"));
+        assert_eq!(section.attr_preferred("Name", &[]), Some("C"));
+    }
+
+    #[test]
+    fn pair() {
+        let entry = crate::Entry::parse(
+            &b"[A]\nName=Files\nName[de]=Dateien\nName[pt_BR]=Arquivos\n\
+               [B]\nName[de]=Dateien"[..],
+        )
+        .unwrap();
+        let pair = |section: &str, locales: &[&str]| {
+            let locales: Vec<Locale> =
+                locales.iter().map(|l| l.parse().unwrap()).collect();
+            entry.section(section).attr_pair("Name", &locales)
+        };
+        assert_eq!(pair("A", &["de_AT"]), (Some("Files"), Some("Dateien")));
+        assert_eq!(
+            pair("A", &["fr", "pt_BR"]),
+            (Some("Files"), Some("Arquivos"))
+        );
+        assert_eq!(pair("A", &["pt"]), (Some("Files"), None));
+        assert_eq!(pair("A", &[]), (Some("Files"), None));
+        assert_eq!(pair("B", &["de"]), (None, Some("Dateien")));
+        assert_eq!(pair("C", &["de"]), (None, None));
+    }
+
+    #[test]
+    fn param_encoding() {
+        let entry = crate::Entry::parse(
+            &b"[A]\nName=Hello\nName[de_DE.UTF-8]=Hallo\n\
+               Name[sr_RS.UTF-8@latin]=Zdravo"[..],
+        )
+        .unwrap();
+        let section = entry.section("A");
+        for locale in &["de_DE.UTF-8", "de_DE", "de_DE.ISO-8859-1"] {
+            let locales = [locale.parse().unwrap()];
+            assert_eq!(section.attr_preferred("Name", &locales), Some("Hallo"));
+            assert_eq!(
+                section.attr_variants("Name", &locales).next().unwrap().1,
+                "Hallo"
+            );
+        }
+        let locale = "sr_RS@latin".parse().unwrap();
+        assert_eq!(section.attr_localized("Name", &locale), Some("Zdravo"));
+        let locales = ["de".parse().unwrap()];
+        assert_eq!(section.attr_pair("Name", &locales), (Some("Hello"), None));
+    }
+
+    #[test]
+    fn variants() {
+        let entry = crate::Entry::parse(
+            &b"[A]\nName[sr@latin]=sr@latin\nName[xx yy]=bad\nName[fr]=fr\n\
+               Name=C\nName[sr_RS.UTF-8]=sr_RS\nName[de]=de"[..],
+        )
+        .unwrap();
+        let section = entry.section("A");
+        let locales: Vec<Locale> =
+            vec!["sr_RS@latin".parse().unwrap(), "de_DE".parse().unwrap()];
+        let variants: Vec<_> = section
+            .attr_variants("Name", &locales)
+            .map(|(locale, value)| (locale.map(|l| l.to_string()), value))
+            .collect();
+        assert_eq!(
+            variants,
+            [
+                (Some("sr_RS.UTF-8".to_owned()), "sr_RS"),
+                (Some("sr@latin".to_owned()), "sr@latin"),
+                (Some("de".to_owned()), "de"),
+                (None, "C"),
+                (Some("fr".to_owned()), "fr"),
+            ]
+        );
+        assert_eq!(section.attr_variants("Missing", &locales).count(), 0);
+    }
+
+    #[test]
+    fn new() {
+        let locale =
+            Locale::new("sr", Some("RS"), None, Some("latin")).unwrap();
+        assert_eq!(locale.to_string(), "sr_RS@latin");
+        assert_eq!(locale, "sr_RS@latin".parse().unwrap());
+        assert!(Locale::new("sr", Some(""), None, None).is_err());
+    }
+}
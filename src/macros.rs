@@ -0,0 +1,89 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+/// Declare an [`Entry`](struct.Entry.html) inline.
+///
+/// Each section is a name followed by `=>` and a block of attributes.
+/// Attributes are written as `name => value` or `name[param] => value`.
+///
+/// Example:
+/// ```
+/// use freedesktop_entry_parser::entry;
+///
+/// let entry = entry! {
+///     "Desktop Entry" => {
+///         "Name" => "Firefox",
+///         "Name"["de"] => "Feuerfuchs",
+///         "Exec" => "firefox %u",
+///     },
+///     "Desktop Action new-window" => {
+///         "Name" => "New Window",
+///     },
+/// };
+///
+/// assert_eq!(
+///     entry.section("Desktop Entry").attr_with_param("Name", "de"),
+///     Some("Feuerfuchs")
+/// );
+/// ```
+#[macro_export]
+macro_rules! entry {
+    ($(
+        $section:expr => {
+            $($name:literal $([$param:expr])? => $value:expr),* $(,)?
+        }
+    ),* $(,)?) => {{
+        #[allow(unused_mut)]
+        let mut entry = $crate::Entry::new();
+        $(
+            entry.add_section($section);
+            $(
+                $crate::entry!(
+                    @set entry.section_mut($section), $name, $($param,)? $value
+                );
+            )*
+        )*
+        entry
+    }};
+    (@set $section:expr, $name:expr, $value:expr) => {
+        $section.set_attr($name, $value)
+    };
+    (@set $section:expr, $name:expr, $param:expr, $value:expr) => {
+        $section.set_attr_with_param($name, $param, $value)
+    };
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn entry_macro() {
+        let entry = crate::entry! {
+            "Unit" => {
+                "Description" => "OpenSSH Daemon",
+            },
+            "Service" => {
+                "ExecStart" => "/usr/bin/sshd -D",
+                "ExecStart"["test"] => "/bin/true"
+            },
+            "Install" => {},
+        };
+        assert_eq!(
+            entry.section("Unit").attr("Description"),
+            Some("OpenSSH Daemon")
+        );
+        assert_eq!(
+            entry
+                .section("Service")
+                .attr_with_param("ExecStart", "test"),
+            Some("/bin/true")
+        );
+        assert!(entry.has_section("Install"));
+    }
+
+    #[test]
+    fn empty() {
+        let entry = crate::entry! {};
+        assert_eq!(entry.sections().count(), 0);
+    }
+}
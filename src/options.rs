@@ -0,0 +1,291 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::Locale;
+
+/// Options controlling how entries are parsed.
+///
+/// The defaults match the behavior of [`parse_entry`](fn.parse_entry.html)
+/// and [`Entry::parse`](struct.Entry.html#method.parse).
+///
+/// Example:
+/// ```
+/// use freedesktop_entry_parser::{Entry, LeadingAttrs, ParserOptions};
+///
+/// let options = ParserOptions::default().leading_attrs(LeadingAttrs::Global);
+/// let entry = Entry::parse_with(&b"Name=Foo\n[Bar]\nBaz=1"[..], &options)?;
+/// assert_eq!(entry.section("").attr("Name"), Some("Foo"));
+/// # Ok::<(), freedesktop_entry_parser::ParseError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct ParserOptions {
+    pub(crate) leading_attrs: LeadingAttrs,
+    pub(crate) duplicates: Duplicates,
+    pub(crate) trim_whitespace: bool,
+    pub(crate) inline_comments: bool,
+    pub(crate) comment_chars: Vec<u8>,
+    pub(crate) lossy_utf8: bool,
+    pub(crate) recover: bool,
+    pub(crate) key_names: InvalidNames,
+    pub(crate) section_names: InvalidNames,
+    pub(crate) ignore_case: bool,
+    pub(crate) locales: LocaleFilter,
+    #[cfg(feature = "encoding_rs")]
+    pub(crate) legacy_encoding: bool,
+    #[cfg(feature = "unicode-normalization")]
+    pub(crate) normalize_names: bool,
+}
+
+impl Default for ParserOptions {
+    fn default() -> Self {
+        Self {
+            leading_attrs: LeadingAttrs::default(),
+            duplicates: Duplicates::default(),
+            trim_whitespace: true,
+            inline_comments: false,
+            comment_chars: b"#".to_vec(),
+            lossy_utf8: false,
+            recover: false,
+            key_names: InvalidNames::default(),
+            section_names: InvalidNames::default(),
+            ignore_case: false,
+            locales: LocaleFilter::default(),
+            #[cfg(feature = "encoding_rs")]
+            legacy_encoding: false,
+            #[cfg(feature = "unicode-normalization")]
+            normalize_names: false,
+        }
+    }
+}
+
+impl ParserOptions {
+    /// Options for validating files against the spec.  Anything the spec
+    /// doesn't allow is an error.
+    pub fn strict() -> Self {
+        Self {
+            leading_attrs: LeadingAttrs::Error,
+            duplicates: Duplicates::Error,
+            key_names: InvalidNames::Error,
+            section_names: InvalidNames::Error,
+            ..Self::default()
+        }
+    }
+
+    /// Options for reading as much as possible out of real world files, for
+    /// example in a launcher.
+    pub fn lenient() -> Self {
+        Self {
+            leading_attrs: LeadingAttrs::Skip,
+            duplicates: Duplicates::Allow,
+            lossy_utf8: true,
+            recover: true,
+            key_names: InvalidNames::Warn,
+            section_names: InvalidNames::Warn,
+            ..Self::default()
+        }
+    }
+
+    /// Ignore spaces and tabs before and after the `=` of an attribute, as
+    /// the spec requires.  On by default.  Turning it off keeps the raw
+    /// bytes, which is mostly useful with the
+    /// [low level API](low_level/index.html).
+    pub fn trim_whitespace(mut self, trim_whitespace: bool) -> Self {
+        self.trim_whitespace = trim_whitespace;
+        self
+    }
+
+    /// Strip comments at the end of values, like `Key=value # comment`.
+    ///
+    /// The spec only allows comments on their own line so this is off by
+    /// default.  A comment has to start at the beginning of the value or
+    /// after whitespace, so `Key=a#b` is kept as is.
+    pub fn inline_comments(mut self, inline_comments: bool) -> Self {
+        self.inline_comments = inline_comments;
+        self
+    }
+
+    /// Characters that start a comment.  Defaults to `#`, the only one the
+    /// spec allows.  Other ini style files often use `;` as well, which can
+    /// be enabled with `comment_chars(&b"#;"[..])`.
+    ///
+    /// These are also used for
+    /// [`inline_comments`](#method.inline_comments).
+    pub fn comment_chars(mut self, comment_chars: impl Into<Vec<u8>>) -> Self {
+        self.comment_chars = comment_chars.into();
+        self
+    }
+
+    /// Replace invalid UTF-8 with `U+FFFD` instead of failing with
+    /// [`ParseError::Utf8Error`](enum.ParseError.html#variant.Utf8Error).
+    /// Off by default, on for [`lenient`](#method.lenient).
+    pub fn lossy_utf8(mut self, lossy_utf8: bool) -> Self {
+        self.lossy_utf8 = lossy_utf8;
+        self
+    }
+
+    /// Decode values that aren't valid UTF-8 with the charset of their
+    /// locale, as used by `Encoding=Legacy-Mixed` files.  Values without a
+    /// charset in their locale are decoded as Latin-1.  This takes precedence
+    /// over [`lossy_utf8`](#method.lossy_utf8).
+    ///
+    /// Requires the `encoding_rs` feature.
+    #[cfg(feature = "encoding_rs")]
+    pub fn legacy_encoding(mut self, legacy_encoding: bool) -> Self {
+        self.legacy_encoding = legacy_encoding;
+        self
+    }
+
+    /// Convert attribute names and params to Unicode Normalization Form C,
+    /// so they match names written in a different normal form by another
+    /// tool.  Names passed to lookups should be in NFC as well, which string
+    /// literals almost always are.  Off by default.
+    ///
+    /// Requires the `unicode-normalization` feature.
+    #[cfg(feature = "unicode-normalization")]
+    pub fn normalize_names(mut self, normalize_names: bool) -> Self {
+        self.normalize_names = normalize_names;
+        self
+    }
+
+    /// Skip lines that can't be parsed instead of returning an error.  The
+    /// skipped lines are reported as
+    /// [`Diagnostic`](struct.Diagnostic.html)s.  Off by default, on for
+    /// [`lenient`](#method.lenient).
+    pub fn recover(mut self, recover: bool) -> Self {
+        self.recover = recover;
+        self
+    }
+
+    /// What to do with keys that contain characters other than `A-Za-z0-9-`,
+    /// which is all the spec allows.  The param of a key isn't checked.
+    pub fn key_names(mut self, key_names: InvalidNames) -> Self {
+        self.key_names = key_names;
+        self
+    }
+
+    /// What to do with section names that contain `[`, `]` or control
+    /// characters, which the spec doesn't allow.
+    pub fn section_names(mut self, section_names: InvalidNames) -> Self {
+        self.section_names = section_names;
+        self
+    }
+
+    /// Ignore ASCII case when looking up sections and attributes in the
+    /// parsed entry, so `section("Desktop Entry")` also finds
+    /// `[Desktop entry]`.  An exact match is still preferred.  Off by
+    /// default.  Params are always case sensitive.
+    ///
+    /// [`Duplicates`](enum.Duplicates.html) are found ignoring case as well.
+    pub fn ignore_case(mut self, ignore_case: bool) -> Self {
+        self.ignore_case = ignore_case;
+        self
+    }
+
+    /// Which localized values to keep.  Dropping the ones that won't be
+    /// read keeps the parsed entries small when reading many files.
+    ///
+    /// Example:
+    /// ```
+    /// use freedesktop_entry_parser::{Entry, LocaleFilter, ParserOptions};
+    ///
+    /// let options = ParserOptions::default()
+    ///     .locales(LocaleFilter::Only(vec!["de_DE".parse()?]));
+    /// let entry = Entry::parse_with(
+    ///     &b"[A]\nName=Files\nName[de]=Dateien\nName[fr]=Fichiers"[..],
+    ///     &options,
+    /// )?;
+    /// let section = entry.section("A");
+    /// assert_eq!(section.attr_with_param("Name", "de"), Some("Dateien"));
+    /// assert_eq!(section.attr_with_param("Name", "fr"), None);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn locales(mut self, locales: LocaleFilter) -> Self {
+        self.locales = locales;
+        self
+    }
+
+    /// What to do with sections and attributes that appear more than once.
+    pub fn duplicates(mut self, duplicates: Duplicates) -> Self {
+        self.duplicates = duplicates;
+        self
+    }
+
+    /// What to do with attributes before the first section header.
+    pub fn leading_attrs(mut self, leading_attrs: LeadingAttrs) -> Self {
+        self.leading_attrs = leading_attrs;
+        self
+    }
+}
+
+/// How to handle attributes that appear before the first section header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LeadingAttrs {
+    /// Skip everything before the first header.  This is the default.
+    #[default]
+    Skip,
+    /// Return an error if anything other than comments and blank lines comes
+    /// before the first header.
+    Error,
+    /// Collect the attributes into a section with an empty name, `""`.
+    /// It's the first section in the entry and is written back out without a
+    /// header.
+    Global,
+}
+
+/// How to handle sections and attributes that appear more than once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Duplicates {
+    /// Keep all of them.  Lookups return the last one, the others are
+    /// available from
+    /// [`EntryRef::sections_named`](struct.EntryRef.html#method.sections_named)
+    /// and
+    /// [`AttrSelector::attr_values`](struct.AttrSelector.html#method.attr_values).
+    /// This is the default.
+    #[default]
+    Allow,
+    /// Return an error.
+    Error,
+}
+
+/// How to handle names with characters the spec doesn't allow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InvalidNames {
+    /// Accept them.  This is the default.
+    #[default]
+    Allow,
+    /// Accept them but report a warning
+    /// [`Diagnostic`](struct.Diagnostic.html).
+    Warn,
+    /// Return an error.  With
+    /// [`ParserOptions::recover`](struct.ParserOptions.html#method.recover)
+    /// they're skipped and reported as an error diagnostic instead.
+    Error,
+}
+
+/// Which localized values, the ones with a param, to keep.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum LocaleFilter {
+    /// Keep all of them.  This is the default.
+    #[default]
+    All,
+    /// Keep the values that would be used for one of these locales, see
+    /// [`AttrSelector::attr_preferred`](struct.AttrSelector.html#method.attr_preferred).
+    /// Params that aren't locales are dropped.
+    Only(Vec<Locale>),
+    /// Drop all of them.
+    None,
+}
+
+impl LocaleFilter {
+    /// The params to keep, `None` if all of them are kept.
+    pub(crate) fn params(&self) -> Option<Vec<String>> {
+        match self {
+            LocaleFilter::All => None,
+            LocaleFilter::Only(locales) => {
+                Some(locales.iter().flat_map(Locale::fallbacks).collect())
+            }
+            LocaleFilter::None => Some(Vec::new()),
+        }
+    }
+}
@@ -0,0 +1,129 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::{internal::Section, Entry};
+
+impl Entry {
+    /// Iterate over the sections in the entry as owned values, so they can
+    /// be kept after the entry is dropped.
+    pub fn into_sections(self) -> IntoSections {
+        IntoSections {
+            iter: ((self.0).0).into_sections(),
+        }
+    }
+}
+
+/// A section that doesn't borrow from an entry.
+///
+/// Created from [`Entry::into_sections`](struct.Entry.html#method.into_sections)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnedSection {
+    /// Name of the section
+    pub name: String,
+    /// Attributes in the order they appear in the file
+    pub attrs: Vec<OwnedAttr>,
+}
+
+/// An attribute that doesn't borrow from an entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnedAttr {
+    /// Name of the attribute
+    pub name: String,
+    /// Every value the attribute was assigned without a param, in order.
+    /// The last one is the value.
+    pub values: Vec<String>,
+    /// Params and their values, like `("de", "Hallo")` for `Name[de]=Hallo`
+    pub params: Vec<(String, String)>,
+}
+
+impl OwnedAttr {
+    /// Value of the attribute without a param, if it has one.
+    pub fn value(&self) -> Option<&str> {
+        self.values.last().map(String::as_str)
+    }
+}
+
+/// Iterator over owned sections.
+///
+/// Created from [`Entry::into_sections`](struct.Entry.html#method.into_sections)
+pub struct IntoSections {
+    iter: std::vec::IntoIter<Section<'static>>,
+}
+
+impl Iterator for IntoSections {
+    type Item = OwnedSection;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let section = self.iter.next()?;
+        let attrs = section.attrs();
+        let attrs = attrs
+            .attr_names_iter()
+            .filter_map(|name| {
+                let attr = attrs.get_attr(name)?;
+                let params =
+                    attr.get_params().map_or_else(Vec::new, |params| {
+                        params
+                            .param_names_iter()
+                            .filter_map(|param| {
+                                let value = params.get_param(param)?;
+                                Some((param.to_owned(), value.to_owned()))
+                            })
+                            .collect()
+                    });
+                Some(OwnedAttr {
+                    name: name.to_owned(),
+                    values: attr
+                        .get_values()
+                        .iter()
+                        .map(|value| value.to_string())
+                        .collect(),
+                    params,
+                })
+            })
+            .collect();
+        Some(OwnedSection {
+            name: section.name().to_owned(),
+            attrs,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn into_sections() {
+        let sections: Vec<_> = {
+            let entry =
+                Entry::parse(&b"[A]\nB=1\nB=2\nC[de]=3\n[D]\n"[..]).unwrap();
+            entry.into_sections().collect()
+        };
+        assert_eq!(
+            sections,
+            [
+                OwnedSection {
+                    name: "A".to_owned(),
+                    attrs: vec![
+                        OwnedAttr {
+                            name: "B".to_owned(),
+                            values: vec!["1".to_owned(), "2".to_owned()],
+                            params: vec![],
+                        },
+                        OwnedAttr {
+                            name: "C".to_owned(),
+                            values: vec![],
+                            params: vec![("de".to_owned(), "3".to_owned())],
+                        },
+                    ],
+                },
+                OwnedSection {
+                    name: "D".to_owned(),
+                    attrs: vec![],
+                },
+            ]
+        );
+        assert_eq!(sections[0].attrs[0].value(), Some("2"));
+    }
+}
@@ -1,8 +1,12 @@
 pub use crate::errors::ParseError;
+use crate::{
+    diagnostic::{Diagnostic, Severity},
+    options::{InvalidNames, LeadingAttrs, ParserOptions},
+};
 use nom::{
     bytes::complete::{tag, take_till, take_till1},
     error::ErrorKind,
-    multi::many1,
+    multi::many0,
     sequence::{delimited, terminated},
     IResult,
 };
@@ -42,48 +46,155 @@ fn not_whitespace(c: u8) -> bool {
 
 /// Parse a header line.  Return the header name
 fn header(input: &[u8]) -> IResult<&[u8], &[u8]> {
-    delimited(tag(b"["), take_till1(|c| c == b']'), tag(b"]"))(input)
+    delimited(
+        tag(b"["),
+        take_till1(|c| c == b']' || c == b'\n'),
+        tag(b"]"),
+    )(input)
 }
 
-/// Find the next line, ignoring comments
-fn next_line(
-    input: &[u8],
-) -> Result<&[u8], nom::Err<nom::error::Error<&[u8]>>> {
+/// Find the next line, ignoring lines starting with one of `comments`
+fn next_line<'a>(
+    input: &'a [u8],
+    comments: &[u8],
+) -> Result<&'a [u8], nom::Err<nom::error::Error<&'a [u8]>>> {
     if input.is_empty() {
         return Ok(b"");
     }
     let (rem, _) = take_till(not_whitespace)(input)?;
-    if rem.get(0) == Some(&(b'#')) {
-        let (rem, _) = take_till(|c| c == b'\n')(rem)?;
-        return next_line(rem);
+    match rem.first() {
+        Some(c) if comments.contains(c) => {
+            let (rem, _) = take_till(|c| c == b'\n')(rem)?;
+            next_line(rem, comments)
+        }
+        _ => Ok(rem),
+    }
+}
+
+fn is_space(c: &u8) -> bool {
+    *c == b' ' || *c == b'\t'
+}
+
+fn trim_start(input: &[u8]) -> &[u8] {
+    let start = input
+        .iter()
+        .position(|c| !is_space(c))
+        .unwrap_or(input.len());
+    &input[start..]
+}
+
+fn trim_end(input: &[u8]) -> &[u8] {
+    let end = input
+        .iter()
+        .rposition(|c| !is_space(c))
+        .map_or(0, |i| i + 1);
+    &input[..end]
+}
+
+/// Remove the whitespace around the `=` in an attribute
+fn trim_attr(attr: AttrBytes<'_>) -> AttrBytes<'_> {
+    let name = trim_end(attr.name);
+    AttrBytes {
+        name,
+        value: trim_start(attr.value),
+        param: params(name).ok().map(|(_, param)| param),
     }
-    Ok(rem)
+}
+
+/// Remove a trailing `# comment` from a value.  The comment has to be at the
+/// start of the value or after whitespace so values like `a#b` are kept.
+fn strip_inline_comment<'a>(value: &'a [u8], comments: &[u8]) -> &'a [u8] {
+    let start = (0..value.len()).find(|&i| {
+        comments.contains(&value[i]) && (i == 0 || is_space(&value[i - 1]))
+    });
+    match start {
+        Some(start) => trim_end(&value[..start]),
+        None => value,
+    }
+}
+
+/// Keys may only contain `A-Za-z0-9-`
+fn valid_key(name: &[u8]) -> bool {
+    !name.is_empty()
+        && name.iter().all(|c| c.is_ascii_alphanumeric() || *c == b'-')
+}
+
+/// Section names may contain any ASCII character except `[`, `]` and
+/// control characters
+fn valid_title(title: &[u8]) -> bool {
+    !title
+        .iter()
+        .any(|c| *c == b'[' || *c == b']' || c.is_ascii_control())
+}
+
+/// Section names that can be written out as a header.  The empty name of
+/// the attributes before the first header is allowed too.
+pub(crate) fn writable_title(title: &str) -> bool {
+    valid_title(title.as_bytes())
+}
+
+/// Keys and param values that can be written out without changing the
+/// structure of the file: no `[`, `]`, `=` or control characters.
+pub(crate) fn writable_name(name: &str) -> bool {
+    !name
+        .bytes()
+        .any(|c| c == b'[' || c == b']' || c == b'=' || c.is_ascii_control())
+}
+
+/// Remove a UTF-8 byte order mark from the start of the input.
+fn strip_bom(input: &[u8]) -> &[u8] {
+    input.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(input)
 }
 
 fn find_start(input: &[u8]) -> IResult<&[u8], &[u8]> {
     take_till(|c| c == b'[')(input)
 }
 
+/// Skip the rest of the current line
+fn skip_line(input: &[u8]) -> &[u8] {
+    let end = input
+        .iter()
+        .position(|c| *c == b'\n')
+        .unwrap_or(input.len());
+    &input[end..]
+}
+
+/// Skip lines until one that starts with `[`
+fn skip_to_header<'a>(mut input: &'a [u8], comments: &[u8]) -> &'a [u8] {
+    loop {
+        input = next_line(skip_line(input), comments).unwrap_or(b"");
+        if input.is_empty() || input.first() == Some(&b'[') {
+            return input;
+        }
+    }
+}
+
 /// Parse attr params
-fn params(input: &[u8]) -> IResult<&[u8], ParamBytes> {
+fn params(input: &[u8]) -> IResult<&[u8], ParamBytes<'_>> {
     let (rem, attr_name) =
         terminated(take_till(|c| c == b'['), tag(b"["))(input)?;
     let (rem, param) = take_till(|c| c == b']')(rem)?;
     Ok((rem, ParamBytes { param, attr_name }))
 }
 
-fn attr(input: &[u8]) -> IResult<&[u8], AttrBytes> {
-    if input.get(0) == Some(&(b'[')) {
+fn attr<'a>(
+    input: &'a [u8],
+    comments: &[u8],
+) -> IResult<&'a [u8], AttrBytes<'a>> {
+    if input.first() == Some(&(b'[')) {
         return Err(nom::Err::Error(nom::error::Error::new(
             input,
             ErrorKind::Complete,
         )));
     }
-    let (rem, name) = terminated(take_till(|c| c == b'='), tag(b"="))(input)?;
+    let (rem, name) =
+        terminated(take_till(|c| c == b'=' || c == b'\n'), tag(b"="))(input)?;
     let (rem, value) = take_till(|c| c == b'\n')(rem)?;
+    // Lines can end with `\r\n`
+    let value = value.strip_suffix(b"\r").unwrap_or(value);
 
     Ok((
-        next_line(rem)?,
+        next_line(rem, comments)?,
         AttrBytes {
             name,
             value,
@@ -92,30 +203,246 @@ fn attr(input: &[u8]) -> IResult<&[u8], AttrBytes> {
     ))
 }
 
-fn section(input: &[u8]) -> IResult<&[u8], SectionBytes> {
+fn section<'a>(
+    input: &'a [u8],
+    comments: &[u8],
+) -> IResult<&'a [u8], SectionBytes<'a>> {
     let (rem, title) = header(input)?;
-    let rem = next_line(rem)?;
-    let (rem, attrs) = many1(attr)(rem)?;
+    let rem = next_line(rem, comments)?;
+    let (rem, attrs) = many0(|i| attr(i, comments))(rem)?;
     Ok((rem, SectionBytes { title, attrs }))
 }
 
 /// An iterator over the sections in a entry file.
 /// Returns [`SectionBytes`](struct.SectionBytes.html)
 pub struct EntryIter<'a> {
+    input: &'a [u8],
     rem: &'a [u8],
     found_start: bool,
+    options: ParserOptions,
+    diagnostics: Vec<Diagnostic>,
 }
 
 impl<'a> EntryIter<'a> {
-    fn next_section(&mut self) -> Result<SectionBytes<'a>, ParseError> {
-        if !self.found_start {
-            self.rem = find_start(self.rem)?.0;
-            self.found_start = true;
+    /// Problems found so far.  Lines that couldn't be parsed are only
+    /// reported here if
+    /// [`ParserOptions::recover`](../struct.ParserOptions.html#method.recover)
+    /// is set, otherwise they're returned as errors.  Things the spec doesn't
+    /// allow but that could be parsed, like trailing whitespace, are always
+    /// reported as warnings.
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// Take the problems found while parsing.
+    pub fn into_diagnostics(self) -> Vec<Diagnostic> {
+        self.diagnostics
+    }
+
+    fn error(&mut self, at: &[u8], message: impl Into<String>) {
+        self.diagnostics.push(Diagnostic::new(
+            Severity::Error,
+            self.input,
+            at,
+            message,
+        ));
+    }
+
+    fn warning(&mut self, at: &[u8], message: impl Into<String>) {
+        self.diagnostics.push(Diagnostic::new(
+            Severity::Warning,
+            self.input,
+            at,
+            message,
+        ));
+    }
+
+    /// Handle anything before the first header.
+    fn leading_attrs(
+        &mut self,
+    ) -> Result<Option<SectionBytes<'a>>, nom::Err<nom::error::Error<&'a [u8]>>>
+    {
+        match self.options.leading_attrs {
+            LeadingAttrs::Skip => {
+                self.rem = find_start(self.rem)?.0;
+                Ok(None)
+            }
+            LeadingAttrs::Error => {
+                let rem = next_line(self.rem, &self.options.comment_chars)?;
+                if !rem.is_empty() && rem.first() != Some(&b'[') {
+                    return Err(nom::Err::Error(nom::error::Error::new(
+                        rem,
+                        ErrorKind::Tag,
+                    )));
+                }
+                self.rem = rem;
+                Ok(None)
+            }
+            LeadingAttrs::Global => {
+                let comments = &self.options.comment_chars;
+                let rem = next_line(self.rem, comments)?;
+                let (rem, attrs) = many0(|i| attr(i, comments))(rem)?;
+                self.rem = find_start(rem)?.0;
+                if attrs.is_empty() {
+                    return Ok(None);
+                }
+                // Empty, but still a slice of the input so it has a span
+                let title = &attrs[0].name[..0];
+                Ok(Some(self.finish(SectionBytes { title, attrs })?))
+            }
         }
+    }
+
+    fn next_section(
+        &mut self,
+    ) -> Result<SectionBytes<'a>, nom::Err<nom::error::Error<&'a [u8]>>> {
         let (rem, _) = find_start(self.rem)?;
-        let (rem, section_bytes) = section(rem)?;
+        let (rem, section_bytes) = section(rem, &self.options.comment_chars)?;
         self.rem = rem;
-        Ok(section_bytes)
+        // Sections are only skipped when recovering
+        self.check_title(section_bytes.title)?;
+        self.finish(section_bytes)
+    }
+
+    /// Check the section name for characters the spec doesn't allow.
+    /// Returns `false` if the section should be skipped.
+    fn check_title(
+        &mut self,
+        title: &'a [u8],
+    ) -> Result<bool, nom::Err<nom::error::Error<&'a [u8]>>> {
+        if self.options.section_names == InvalidNames::Allow
+            || valid_title(title)
+        {
+            return Ok(true);
+        }
+        match self.options.section_names {
+            InvalidNames::Error if !self.options.recover => {
+                Err(nom::Err::Error(nom::error::Error::new(
+                    title,
+                    ErrorKind::Verify,
+                )))
+            }
+            InvalidNames::Error => {
+                self.error(title, "Invalid character in section name");
+                Ok(false)
+            }
+            _ => {
+                self.warning(title, "Invalid character in section name");
+                Ok(true)
+            }
+        }
+    }
+
+    /// Like `next_section` but lines that can't be parsed are skipped and
+    /// recorded as diagnostics.
+    fn recover_section(&mut self) -> Option<SectionBytes<'a>> {
+        let comments = self.options.comment_chars.clone();
+        loop {
+            let rem = next_line(self.rem, &comments).unwrap_or(b"");
+            if rem.is_empty() {
+                self.rem = rem;
+                return None;
+            }
+            if rem.first() != Some(&b'[') {
+                self.error(rem, "Attribute outside of a section");
+                self.rem = skip_to_header(rem, &comments);
+                continue;
+            }
+            let (mut rem, mut section) = match section(rem, &comments) {
+                Ok(ok) => ok,
+                Err(_) => {
+                    self.error(rem, "Invalid section header");
+                    self.rem = skip_to_header(rem, &comments);
+                    continue;
+                }
+            };
+            while !rem.is_empty() && rem.first() != Some(&b'[') {
+                self.error(rem, "Invalid line");
+                rem = next_line(skip_line(rem), &comments).unwrap_or(b"");
+                if let Ok((r, attrs)) = many0(|i| attr(i, &comments))(rem) {
+                    section.attrs.extend(attrs);
+                    rem = r;
+                }
+            }
+            self.rem = rem;
+            if !self.check_title(section.title).unwrap_or(false) {
+                continue;
+            }
+            // Invalid names are reported as diagnostics when recovering
+            return self.finish(section).ok();
+        }
+    }
+
+    /// Apply the options that adjust already parsed attributes and report
+    /// what the spec doesn't allow.
+    fn finish(
+        &mut self,
+        mut section: SectionBytes<'a>,
+    ) -> Result<SectionBytes<'a>, nom::Err<nom::error::Error<&'a [u8]>>> {
+        if self.options.trim_whitespace {
+            section.attrs = section.attrs.into_iter().map(trim_attr).collect();
+        }
+        if self.options.inline_comments {
+            for attr in &mut section.attrs {
+                attr.value = strip_inline_comment(
+                    attr.value,
+                    &self.options.comment_chars,
+                );
+            }
+        }
+        if self.options.key_names != InvalidNames::Allow {
+            let mut attrs = Vec::with_capacity(section.attrs.len());
+            for attr in section.attrs {
+                let name =
+                    attr.param.as_ref().map_or(attr.name, |p| p.attr_name);
+                if valid_key(name) {
+                    attrs.push(attr);
+                    continue;
+                }
+                match self.options.key_names {
+                    InvalidNames::Error if !self.options.recover => {
+                        return Err(nom::Err::Error(nom::error::Error::new(
+                            name,
+                            ErrorKind::Verify,
+                        )));
+                    }
+                    InvalidNames::Error => {
+                        self.error(name, "Invalid character in key");
+                    }
+                    _ => {
+                        self.warning(name, "Invalid character in key");
+                        attrs.push(attr);
+                    }
+                }
+            }
+            section.attrs = attrs;
+        }
+        for attr in &section.attrs {
+            self.check_value(attr.value);
+        }
+        Ok(section)
+    }
+
+    /// Warn about trailing whitespace and unknown escape sequences.
+    fn check_value(&mut self, value: &'a [u8]) {
+        if value.last().map(is_space) == Some(true) {
+            self.warning(value, "Trailing whitespace in value");
+        }
+        let mut i = 0;
+        while i < value.len() {
+            if value[i] == b'\\' {
+                match value.get(i + 1) {
+                    Some(b's') | Some(b'n') | Some(b't') | Some(b'r')
+                    | Some(b'\\') | Some(b';') => {}
+                    _ => self.warning(
+                        &value[i..],
+                        "Unknown escape sequence in value",
+                    ),
+                }
+                i += 1;
+            }
+            i += 1;
+        }
     }
 }
 
@@ -123,10 +450,33 @@ impl<'a> Iterator for EntryIter<'a> {
     type Item = Result<SectionBytes<'a>, ParseError>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        if !self.found_start {
+            self.found_start = true;
+            match self.leading_attrs() {
+                Ok(Some(section)) => return Some(Ok(section)),
+                Ok(None) => {}
+                Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e))
+                    if self.options.recover =>
+                {
+                    self.error(e.input, "Attribute before the first header");
+                    self.rem =
+                        skip_to_header(e.input, &self.options.comment_chars);
+                }
+                Err(e) => {
+                    return Some(Err(ParseError::from_nom(e, self.input)))
+                }
+            }
+        }
+        if self.options.recover {
+            return self.recover_section().map(Ok);
+        }
         if self.rem.is_empty() {
             return None;
         }
-        Some(self.next_section())
+        Some(
+            self.next_section()
+                .map_err(|e| ParseError::from_nom(e, self.input)),
+        )
     }
 }
 
@@ -134,8 +484,30 @@ impl<'a> Iterator for EntryIter<'a> {
 /// Returns and iterator over the sections in the file.
 pub fn parse_entry(input: &[u8]) -> EntryIter<'_> {
     EntryIter {
-        rem: input,
+        input,
+        rem: strip_bom(input),
+        found_start: false,
+        options: ParserOptions::default(),
+        diagnostics: Vec::new(),
+    }
+}
+
+/// Parse a FreeDesktop entry file with `options`.
+/// Returns and iterator over the sections in the file.
+///
+/// If [`LeadingAttrs::Global`](../enum.LeadingAttrs.html#variant.Global) is
+/// used, attributes before the first header are returned as a section with
+/// an empty title, which starts where the first attribute does.
+pub fn parse_entry_with<'a>(
+    input: &'a [u8],
+    options: &ParserOptions,
+) -> EntryIter<'a> {
+    EntryIter {
+        input,
+        rem: strip_bom(input),
         found_start: false,
+        options: options.clone(),
+        diagnostics: Vec::new(),
     }
 }
 
@@ -178,25 +550,28 @@ mod test {
         use super::*;
         #[test]
         fn empty() {
-            assert_eq!(next_line(b""), Ok(&b""[..]));
+            assert_eq!(next_line(b"", b"#"), Ok(&b""[..]));
         }
 
         #[test]
         fn only_whitespace() {
-            assert_eq!(next_line(b" \t \t\n\r\nhello"), Ok(&b"hello"[..]));
+            assert_eq!(
+                next_line(b" \t \t\n\r\nhello", b"#"),
+                Ok(&b"hello"[..])
+            );
         }
 
         #[test]
         fn comment() {
             assert_eq!(
-                next_line(b"   \t\n# Comment\nhello"),
+                next_line(b"   \t\n# Comment\nhello", b"#"),
                 Ok(&b"hello"[..])
             );
         }
 
         #[test]
         fn no_change() {
-            assert_eq!(next_line(b"hello\n"), Ok(&b"hello\n"[..]));
+            assert_eq!(next_line(b"hello\n", b"#"), Ok(&b"hello\n"[..]));
         }
     }
 
@@ -206,7 +581,7 @@ mod test {
         #[test]
         fn ok() {
             assert_eq!(
-                attr(b"hello=world"),
+                attr(b"hello=world", b"#"),
                 Ok((
                     &b""[..],
                     AttrBytes {
@@ -221,7 +596,7 @@ mod test {
         #[test]
         fn with_param() {
             assert_eq!(
-                attr(b"hello[en]=world"),
+                attr(b"hello[en]=world", b"#"),
                 Ok((
                     &b""[..],
                     AttrBytes {
@@ -239,7 +614,7 @@ mod test {
         #[test]
         fn space_in_value() {
             assert_eq!(
-                attr(b"hello=world today"),
+                attr(b"hello=world today", b"#"),
                 Ok((
                     &b""[..],
                     AttrBytes {
@@ -254,7 +629,7 @@ mod test {
         #[test]
         fn no_value() {
             assert_eq!(
-                attr(b"hello="),
+                attr(b"hello=", b"#"),
                 Ok((
                     &b""[..],
                     AttrBytes {
@@ -269,7 +644,7 @@ mod test {
         #[test]
         fn no_name() {
             assert_eq!(
-                attr(b"=world"),
+                attr(b"=world", b"#"),
                 Ok((
                     &b""[..],
                     AttrBytes {
@@ -284,7 +659,7 @@ mod test {
         #[test]
         fn no_eq() {
             assert_eq!(
-                attr(b"hello"),
+                attr(b"hello", b"#"),
                 Err(nom::Err::Error(nom::error::Error {
                     input: &b""[..],
                     code: ErrorKind::Tag
@@ -299,7 +674,7 @@ mod test {
         #[test]
         fn ok() {
             assert_eq!(
-                section(b"[apps]\nSize=48\nScale=1"),
+                section(b"[apps]\nSize=48\nScale=1", b"#"),
                 Ok((
                     &b""[..],
                     SectionBytes {
@@ -324,18 +699,35 @@ mod test {
         #[test]
         fn no_attrs() {
             assert_eq!(
-                section(b"[apps]\n"),
-                Err(nom::Err::Error(nom::error::Error {
-                    input: &b""[..],
-                    code: ErrorKind::Tag
-                }))
+                section(b"[apps]\n", b"#"),
+                Ok((
+                    &b""[..],
+                    SectionBytes {
+                        title: &b"apps"[..],
+                        attrs: vec![],
+                    }
+                ))
+            );
+        }
+
+        #[test]
+        fn no_attrs_before_header() {
+            assert_eq!(
+                section(b"[apps]\n[places]\nSize=48", b"#"),
+                Ok((
+                    &b"[places]\nSize=48"[..],
+                    SectionBytes {
+                        title: &b"apps"[..],
+                        attrs: vec![],
+                    }
+                ))
             );
         }
 
         #[test]
         fn no_header() {
             assert_eq!(
-                section(b"Size=48\nScale=1"),
+                section(b"Size=48\nScale=1", b"#"),
                 Err(nom::Err::Error(nom::error::Error {
                     input: &b"Size=48\nScale=1"[..],
                     code: ErrorKind::Tag
@@ -344,6 +736,205 @@ mod test {
         }
     }
 
+    #[test]
+    fn bom_and_crlf() {
+        let input = b"\xEF\xBB\xBF# Comment\r\n[Bar]\r\nA=1\r\n\r\nB[de]=2\r\n";
+        let options = ParserOptions::strict();
+        let sections = parse_entry_with(input, &options)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(sections[0].title, b"Bar");
+        assert_eq!(sections[0].attrs[0].value, b"1");
+        assert_eq!(sections[0].attrs[1].value, b"2");
+    }
+
+    #[test]
+    fn error_position() {
+        let input = b"[Bar]\nA=1\n[Baz\nB=2\n";
+        match parse_entry(input).nth(1).unwrap() {
+            Err(ParseError::Other {
+                line, column, text, ..
+            }) => {
+                assert_eq!((line, column), (3, 5));
+                assert_eq!(text, "[Baz");
+            }
+            e => panic!("Expected parse error, got {:?}", e.map(|_| ())),
+        }
+    }
+
+    mod fn_parse_entry_with {
+        use super::*;
+
+        #[test]
+        fn section_names() {
+            let input = b"[Foo[1]\nA=1\n[Bar]\nB=2";
+            assert_eq!(parse_entry(input).count(), 2);
+
+            let options =
+                ParserOptions::default().section_names(InvalidNames::Error);
+            match parse_entry_with(input, &options).next().unwrap() {
+                Err(ParseError::Other { line, column, .. }) => {
+                    assert_eq!((line, column), (1, 2))
+                }
+                _ => panic!("Expected an error"),
+            }
+
+            let mut iter = parse_entry_with(input, &options.recover(true));
+            assert_eq!(iter.next().unwrap().unwrap().title, b"Bar");
+            assert_eq!(iter.diagnostics()[0].severity, Severity::Error);
+
+            let (_, diagnostics) = crate::Entry::parse_lenient(&input[..]);
+            assert_eq!(diagnostics[0].severity, Severity::Warning);
+        }
+
+        #[test]
+        fn key_names() {
+            let input = b"[Bar]\nFoo Bar=1\nBaz=2";
+            assert_eq!(
+                parse_entry(input).next().unwrap().unwrap().attrs.len(),
+                2
+            );
+
+            let options =
+                ParserOptions::default().key_names(InvalidNames::Warn);
+            let mut iter = parse_entry_with(input, &options);
+            assert_eq!(iter.next().unwrap().unwrap().attrs.len(), 2);
+            assert_eq!(iter.diagnostics()[0].severity, Severity::Warning);
+
+            let options =
+                ParserOptions::default().key_names(InvalidNames::Error);
+            match parse_entry_with(input, &options).next().unwrap() {
+                Err(ParseError::Other { line, column, .. }) => {
+                    assert_eq!((line, column), (2, 1))
+                }
+                _ => panic!("Expected an error"),
+            }
+
+            let mut iter = parse_entry_with(input, &options.recover(true));
+            assert_eq!(iter.next().unwrap().unwrap().attrs.len(), 1);
+            assert_eq!(iter.diagnostics()[0].severity, Severity::Error);
+        }
+
+        #[test]
+        fn inline_comments() {
+            let input = b"[Bar]\nA=1 # one\nB=a#b\nC=# none\nD=x\t#y";
+            let options = ParserOptions::default().inline_comments(true);
+            let sections = parse_entry_with(input, &options)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            let values: Vec<_> =
+                sections[0].attrs.iter().map(|a| a.value).collect();
+            assert_eq!(values, [&b"1"[..], b"a#b", b"", b"x"]);
+
+            let sections =
+                parse_entry(input).collect::<Result<Vec<_>, _>>().unwrap();
+            assert_eq!(sections[0].attrs[0].value, b"1 # one");
+        }
+
+        #[test]
+        fn comment_chars() {
+            let input = b"; Comment\n[Bar]\n;foo=1\n# bar\nA=1 ; one";
+            let options = ParserOptions::default()
+                .comment_chars(&b"#;"[..])
+                .inline_comments(true);
+            let sections = parse_entry_with(input, &options)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(
+                sections[0].attrs,
+                vec![AttrBytes {
+                    name: &b"A"[..],
+                    value: &b"1"[..],
+                    param: None,
+                }]
+            );
+
+            let sections =
+                parse_entry(input).collect::<Result<Vec<_>, _>>().unwrap();
+            assert_eq!(sections[0].attrs[0].name, b";foo");
+        }
+
+        #[test]
+        fn trim_whitespace() {
+            let input = b"[Bar]\nName[de] =  Foo Bar \nBaz\t=1";
+            let sections =
+                parse_entry(input).collect::<Result<Vec<_>, _>>().unwrap();
+            assert_eq!(
+                sections[0].attrs,
+                vec![
+                    AttrBytes {
+                        name: &b"Name[de]"[..],
+                        value: &b"Foo Bar "[..],
+                        param: Some(ParamBytes {
+                            attr_name: &b"Name"[..],
+                            param: &b"de"[..],
+                        }),
+                    },
+                    AttrBytes {
+                        name: &b"Baz"[..],
+                        value: &b"1"[..],
+                        param: None,
+                    },
+                ]
+            );
+
+            let options = ParserOptions::default().trim_whitespace(false);
+            let sections = parse_entry_with(input, &options)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(sections[0].attrs[1].name, b"Baz\t");
+            assert_eq!(sections[0].attrs[0].value, b"  Foo Bar ");
+        }
+
+        #[test]
+        fn leading_skip() {
+            let sections = parse_entry(b"Name=Foo\n[Bar]\nBaz=1")
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(sections.len(), 1);
+            assert_eq!(sections[0].title, b"Bar");
+        }
+
+        #[test]
+        fn leading_error() {
+            let options =
+                ParserOptions::default().leading_attrs(LeadingAttrs::Error);
+            let mut iter =
+                parse_entry_with(b"Name=Foo\n[Bar]\nBaz=1", &options);
+            assert!(iter.next().unwrap().is_err());
+
+            let sections =
+                parse_entry_with(b"# Comment\n\n[Bar]\nBaz=1", &options)
+                    .collect::<Result<Vec<_>, _>>()
+                    .unwrap();
+            assert_eq!(sections.len(), 1);
+        }
+
+        #[test]
+        fn leading_global() {
+            let options =
+                ParserOptions::default().leading_attrs(LeadingAttrs::Global);
+            let sections = parse_entry_with(
+                b"# Comment\nName=Foo\n[Bar]\nBaz=1",
+                &options,
+            )
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+            assert_eq!(
+                sections[0],
+                SectionBytes {
+                    title: &b""[..],
+                    attrs: vec![AttrBytes {
+                        name: &b"Name"[..],
+                        value: &b"Foo"[..],
+                        param: None,
+                    }],
+                }
+            );
+            assert_eq!(sections[1].title, b"Bar");
+        }
+    }
+
     #[test]
     fn parse_icon_index() {
         let input = include_bytes!("./../test_data/gnome-index.theme");
@@ -0,0 +1,441 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Schemas for keyfiles other than desktop entries, like systemd units,
+//! portal definitions or an application's own config files.
+//!
+//! A [`Schema`](struct.Schema.html) lists the sections of a format, the
+//! keys of each section with the type of their value, and which of them are
+//! required.  Entries can be checked against it, like
+//! [`validate`](../validate/index.html) does for desktop entries, and their
+//! values read with the type from the schema.
+//!
+//! Example:
+//! ```
+//! use freedesktop_entry_parser::{
+//!     schema::{Key, Schema, SectionSchema, Value, ValueType},
+//!     Entry,
+//! };
+//!
+//! let schema = Schema::default().section(
+//!     SectionSchema::new("Unit")
+//!         .required(true)
+//!         .key(Key::new("Description", ValueType::String).required(true))
+//!         .key(Key::new("After", ValueType::Strings)),
+//! );
+//! let entry = Entry::parse(&b"[Unit]\nDescription=Foo\nAfter=a;b;"[..])?;
+//! assert!(schema.validate(&entry).is_empty());
+//! assert_eq!(
+//!     schema.value(&entry, "Unit", "After"),
+//!     Some(Value::Strings(vec!["a".into(), "b".into()]))
+//! );
+//! # Ok::<(), freedesktop_entry_parser::ParseError>(())
+//! ```
+use crate::{
+    validate::Finding, values::parse_bool, AttrSelector, EntryRef, ListIter,
+    Severity,
+};
+use std::borrow::Cow;
+
+/// Types of values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValueType {
+    /// Any string, which can't be localized
+    String,
+    /// A string that can be localized
+    LocaleString,
+    /// `true` or `false`
+    Boolean,
+    /// A whole number
+    Integer,
+    /// A floating point number
+    Number,
+    /// A list of strings separated by `;`
+    Strings,
+    /// A list of strings separated by `;` that can be localized
+    LocaleStrings,
+}
+
+impl ValueType {
+    /// Whether values of this type can have a locale param
+    pub fn is_localized(self) -> bool {
+        matches!(self, ValueType::LocaleString | ValueType::LocaleStrings)
+    }
+}
+
+/// A value read with the type from a [`Schema`](struct.Schema.html).
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value<'a> {
+    /// A `String` or `LocaleString`, with escape sequences decoded
+    String(Cow<'a, str>),
+    /// A `Boolean`
+    Boolean(bool),
+    /// An `Integer`
+    Integer(i64),
+    /// A `Number`
+    Number(f64),
+    /// A `Strings` or `LocaleStrings` list
+    Strings(Vec<Cow<'a, str>>),
+}
+
+/// A key of a [`SectionSchema`](struct.SectionSchema.html).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Key {
+    name: String,
+    value_type: ValueType,
+    required: bool,
+    allowed: Option<Vec<String>>,
+}
+
+impl Key {
+    /// An optional key `name` with values of `value_type`
+    pub fn new(name: impl Into<String>, value_type: ValueType) -> Self {
+        Key {
+            name: name.into(),
+            value_type,
+            required: false,
+            allowed: None,
+        }
+    }
+
+    /// Whether the key has to be in its section.  Defaults to `false`.
+    pub fn required(mut self, required: bool) -> Self {
+        self.required = required;
+        self
+    }
+
+    /// Only allow these values, or items for lists.  Any value of the type
+    /// is allowed by default.
+    pub fn allowed_values<I>(mut self, values: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.allowed = Some(values.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Name of the key
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Type of the key's values
+    pub fn value_type(&self) -> ValueType {
+        self.value_type
+    }
+}
+
+/// A section of a [`Schema`](struct.Schema.html).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SectionSchema {
+    name: String,
+    prefix: bool,
+    required: bool,
+    unknown_keys: bool,
+    keys: Vec<Key>,
+}
+
+impl SectionSchema {
+    /// A section named `name`
+    pub fn new(name: impl Into<String>) -> Self {
+        SectionSchema {
+            name: name.into(),
+            prefix: false,
+            required: false,
+            unknown_keys: false,
+            keys: Vec::new(),
+        }
+    }
+
+    /// Sections with names starting with `prefix`, like `Desktop Action `
+    pub fn prefixed(prefix: impl Into<String>) -> Self {
+        SectionSchema {
+            prefix: true,
+            ..SectionSchema::new(prefix)
+        }
+    }
+
+    /// Whether the section has to be in the entry.  Defaults to `false`,
+    /// and is ignored for [`prefixed`](#method.prefixed) sections.
+    pub fn required(mut self, required: bool) -> Self {
+        self.required = required;
+        self
+    }
+
+    /// Whether keys that aren't in the schema are allowed.  Defaults to
+    /// `false`.
+    pub fn unknown_keys(mut self, unknown_keys: bool) -> Self {
+        self.unknown_keys = unknown_keys;
+        self
+    }
+
+    /// Add a key
+    pub fn key(mut self, key: Key) -> Self {
+        self.keys.push(key);
+        self
+    }
+
+    /// Whether this schema is for the section `name`
+    fn matches(&self, name: &str) -> bool {
+        if self.prefix {
+            name.starts_with(&self.name)
+        } else {
+            name == self.name
+        }
+    }
+
+    fn get_key(&self, name: &str) -> Option<&Key> {
+        self.keys.iter().find(|key| key.name == name)
+    }
+}
+
+/// A keyfile format: its sections and their keys.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Schema {
+    sections: Vec<SectionSchema>,
+    unknown_sections: bool,
+}
+
+impl Schema {
+    /// Add a section.  The first one that matches a section is used for it.
+    pub fn section(mut self, section: SectionSchema) -> Self {
+        self.sections.push(section);
+        self
+    }
+
+    /// Whether sections that aren't in the schema are allowed.  Defaults to
+    /// `false`.
+    pub fn unknown_sections(mut self, unknown_sections: bool) -> Self {
+        self.unknown_sections = unknown_sections;
+        self
+    }
+
+    /// Check `entry` against the schema.  Returns everything that was wrong,
+    /// all of it as errors.
+    pub fn validate(&self, entry: &EntryRef<'_>) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        let mut error = |section: &str, attr: Option<&str>, message: String| {
+            findings.push(Finding {
+                severity: Severity::Error,
+                section: section.to_owned(),
+                attr: attr.map(ToOwned::to_owned),
+                message,
+            })
+        };
+        for schema in &self.sections {
+            if schema.required
+                && !schema.prefix
+                && !entry.has_section(&schema.name)
+            {
+                error(&schema.name, None, "Missing section".to_owned());
+            }
+        }
+        let mut seen: Vec<&str> = Vec::new();
+        for section in entry.sections() {
+            let name = section.name;
+            let schema = match self.get_section(name) {
+                Some(schema) => schema,
+                None => {
+                    if !self.unknown_sections {
+                        error(name, None, "Unknown section".to_owned());
+                    }
+                    continue;
+                }
+            };
+            // A repeated section only needs the required keys in one copy
+            if !seen.contains(&name) {
+                seen.push(name);
+                for key in schema.keys.iter().filter(|key| key.required) {
+                    if !entry
+                        .sections_named(name)
+                        .any(|section| section.has_attr(&key.name))
+                    {
+                        error(
+                            name,
+                            Some(&key.name),
+                            format!("Missing required key `{}`", key.name),
+                        );
+                    }
+                }
+            }
+            for attr in section.attrs() {
+                let key = match schema.get_key(attr.name) {
+                    Some(key) => key,
+                    None => {
+                        if !schema.unknown_keys {
+                            error(
+                                name,
+                                Some(attr.name),
+                                "Unknown key".to_owned(),
+                            );
+                        }
+                        continue;
+                    }
+                };
+                if attr.has_params() && !key.value_type.is_localized() {
+                    error(
+                        name,
+                        Some(attr.name),
+                        "Key can't be localized".to_owned(),
+                    );
+                }
+                // Every assignment of a repeated key and every param
+                for (_, value) in attr.values() {
+                    if let Err(message) = check_value(key, value) {
+                        error(name, Some(attr.name), message);
+                    }
+                }
+            }
+        }
+        findings
+    }
+
+    /// Get the value of `key` in `section` of `entry` with the type from
+    /// the schema.  Returns `None` if the schema doesn't have the key, or
+    /// the value is missing or doesn't have the right type.
+    pub fn value<'a>(
+        &self,
+        entry: &'a EntryRef<'_>,
+        section: &str,
+        key: &str,
+    ) -> Option<Value<'a>> {
+        let key = self.get_section(section)?.get_key(key)?;
+        let section: AttrSelector<'a, &str> = entry.section(section);
+        parse_value(key.value_type, section.attr(&key.name)?)
+    }
+
+    fn get_section(&self, name: &str) -> Option<&SectionSchema> {
+        self.sections.iter().find(|section| section.matches(name))
+    }
+}
+
+fn parse_value(value_type: ValueType, value: &str) -> Option<Value<'_>> {
+    Some(match value_type {
+        ValueType::String | ValueType::LocaleString => {
+            Value::String(crate::unescape(value))
+        }
+        ValueType::Boolean => Value::Boolean(parse_bool(value)?),
+        ValueType::Integer => Value::Integer(value.parse().ok()?),
+        ValueType::Number => Value::Number(value.parse().ok()?),
+        ValueType::Strings | ValueType::LocaleStrings => {
+            Value::Strings(ListIter::new(value).collect())
+        }
+    })
+}
+
+/// Check that `value` has the type of `key` and is one of its allowed
+/// values.
+fn check_value(key: &Key, value: &str) -> Result<(), String> {
+    let parsed = parse_value(key.value_type, value).ok_or_else(|| {
+        format!("`{}` isn't a valid {:?}", value, key.value_type)
+    })?;
+    let allowed = match &key.allowed {
+        Some(allowed) => allowed,
+        None => return Ok(()),
+    };
+    let items = match parsed {
+        Value::Strings(items) => items,
+        _ => vec![Cow::Borrowed(value)],
+    };
+    match items
+        .iter()
+        .find(|item| !allowed.iter().any(|a| a == &**item))
+    {
+        Some(item) => Err(format!("`{}` isn't an allowed value", item)),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Entry;
+
+    fn schema() -> Schema {
+        Schema::default()
+            .section(
+                SectionSchema::new("Service")
+                    .required(true)
+                    .key(
+                        Key::new("Type", ValueType::String)
+                            .required(true)
+                            .allowed_values(vec!["simple", "oneshot"]),
+                    )
+                    .key(Key::new("Restart", ValueType::Boolean))
+                    .key(Key::new("Nice", ValueType::Integer))
+                    .key(Key::new("Name", ValueType::LocaleString)),
+            )
+            .section(SectionSchema::prefixed("Extra ").unknown_keys(true))
+    }
+
+    fn errors(input: &str) -> Vec<(String, Option<String>)> {
+        let entry = Entry::parse(input.as_bytes()).unwrap();
+        schema()
+            .validate(&entry)
+            .into_iter()
+            .map(|finding| (finding.section, finding.attr))
+            .collect()
+    }
+
+    #[test]
+    fn validate() {
+        assert!(errors(
+            "[Service]\nType=simple\nNice=-5\nName[de]=Foo\n[Extra A]\nX=1"
+        )
+        .is_empty());
+        let error = |section: &str, attr: Option<&str>| {
+            (section.to_owned(), attr.map(ToOwned::to_owned))
+        };
+        assert_eq!(
+            errors("[Other]"),
+            [error("Service", None), error("Other", None)]
+        );
+        assert_eq!(
+            errors("[Service]\nType=forking\nRestart=1\nNice[de]=1\nFoo=2"),
+            [
+                error("Service", Some("Type")),
+                error("Service", Some("Restart")),
+                error("Service", Some("Nice")),
+                error("Service", Some("Foo")),
+            ]
+        );
+        assert_eq!(
+            errors("[Service]\nName=Foo"),
+            [error("Service", Some("Type"))]
+        );
+        assert_eq!(
+            errors("[Service]\nRestart=1\n[Service]\nType=simple\nNice=x"),
+            [
+                error("Service", Some("Restart")),
+                error("Service", Some("Nice")),
+            ]
+        );
+        assert_eq!(
+            errors("[Service]\nType=simple\nNice=x\nNice=1\nName[de]=Foo"),
+            [error("Service", Some("Nice"))]
+        );
+        assert_eq!(
+            errors(
+                "[Service]\nType=simple\nRestart=1\nRestart=true\n\
+                 Restart[de]=1"
+            ),
+            vec![error("Service", Some("Restart")); 3]
+        );
+    }
+
+    #[test]
+    fn values() {
+        let entry =
+            Entry::parse(&b"[Service]\nType=simple\nNice=x\nRestart=true"[..])
+                .unwrap();
+        let schema = schema();
+        let value = |key: &str| schema.value(&entry, "Service", key);
+        assert_eq!(value("Type"), Some(Value::String("simple".into())));
+        assert_eq!(value("Restart"), Some(Value::Boolean(true)));
+        assert_eq!(value("Nice"), None);
+        assert_eq!(value("Name"), None);
+        assert_eq!(schema.value(&entry, "Other", "Type"), None);
+    }
+}
@@ -0,0 +1,233 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::{
+    condition::parse_bool, errors::InvalidSocketAddress, systemd::SystemdUnit,
+};
+use std::{
+    fmt::{self, Display, Formatter},
+    net::SocketAddr,
+    path::PathBuf,
+    str::FromStr,
+};
+
+/// An address a socket unit listens on, the value of `ListenStream`,
+/// `ListenDatagram` or `ListenSequentialPacket`.
+///
+/// Example:
+/// ```
+/// use freedesktop_entry_parser::systemd::SocketAddress;
+///
+/// let address: SocketAddress = "[::1]:8080".parse()?;
+/// assert_eq!(address, SocketAddress::Inet("[::1]:8080".parse().unwrap()));
+/// assert_eq!("80".parse(), Ok(SocketAddress::Port(80)));
+/// assert_eq!(
+///     "@/org/example/socket".parse(),
+///     Ok(SocketAddress::Abstract("/org/example/socket".to_owned()))
+/// );
+/// # Ok::<(), freedesktop_entry_parser::errors::InvalidSocketAddress>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SocketAddress {
+    /// A port on all addresses, like `80`
+    Port(u16),
+    /// An IPv4 or IPv6 address with a port, like `127.0.0.1:80` or
+    /// `[::1]:80`
+    Inet(SocketAddr),
+    /// A Unix socket at a path, like `/run/foo.sock`
+    Unix(PathBuf),
+    /// An abstract Unix socket, written with a leading `@` that isn't part
+    /// of the name
+    Abstract(String),
+    /// An `AF_VSOCK` socket, like `vsock:2:1234`, without a CID for any
+    Vsock {
+        /// The context ID
+        cid: Option<u32>,
+        /// The port
+        port: u32,
+    },
+}
+
+impl SocketAddress {
+    /// Parse an address.
+    pub fn parse(s: &str) -> Result<Self, InvalidSocketAddress> {
+        let invalid = || InvalidSocketAddress(s.to_owned());
+        if s.starts_with('/') {
+            return Ok(SocketAddress::Unix(PathBuf::from(s)));
+        }
+        if let Some(name) = s.strip_prefix('@') {
+            if name.is_empty() {
+                return Err(invalid());
+            }
+            return Ok(SocketAddress::Abstract(name.to_owned()));
+        }
+        if let Some(vsock) = s.strip_prefix("vsock:") {
+            let colon = vsock.rfind(':').ok_or_else(invalid)?;
+            let cid = match &vsock[..colon] {
+                "" => None,
+                cid => Some(cid.parse().map_err(|_| invalid())?),
+            };
+            let port = vsock[colon + 1..].parse().map_err(|_| invalid())?;
+            return Ok(SocketAddress::Vsock { cid, port });
+        }
+        if !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit()) {
+            return match s.parse() {
+                Ok(port) if port > 0 => Ok(SocketAddress::Port(port)),
+                _ => Err(invalid()),
+            };
+        }
+        s.parse().map(SocketAddress::Inet).map_err(|_| invalid())
+    }
+}
+
+impl FromStr for SocketAddress {
+    type Err = InvalidSocketAddress;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SocketAddress::parse(s)
+    }
+}
+
+impl Display for SocketAddress {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SocketAddress::Port(port) => write!(f, "{}", port),
+            SocketAddress::Inet(address) => write!(f, "{}", address),
+            SocketAddress::Unix(path) => write!(f, "{}", path.display()),
+            SocketAddress::Abstract(name) => write!(f, "@{}", name),
+            SocketAddress::Vsock { cid, port } => match cid {
+                Some(cid) => write!(f, "vsock:{}:{}", cid, port),
+                None => write!(f, "vsock::{}", port),
+            },
+        }
+    }
+}
+
+impl<'a> SystemdUnit<'a> {
+    /// `ListenStream`, the addresses of the `SOCK_STREAM` sockets, like TCP.
+    /// Invalid addresses are skipped.
+    pub fn listen_stream(&self) -> Vec<SocketAddress> {
+        self.addresses("ListenStream")
+    }
+
+    /// `ListenDatagram`, the addresses of the `SOCK_DGRAM` sockets, like
+    /// UDP.  Invalid addresses are skipped.
+    pub fn listen_datagram(&self) -> Vec<SocketAddress> {
+        self.addresses("ListenDatagram")
+    }
+
+    /// `ListenSequentialPacket`, the addresses of the `SOCK_SEQPACKET`
+    /// sockets.  Invalid addresses are skipped.
+    pub fn listen_sequential_packet(&self) -> Vec<SocketAddress> {
+        self.addresses("ListenSequentialPacket")
+    }
+
+    /// `ListenFIFO`, the paths of the FIFOs
+    pub fn listen_fifo(&self) -> Vec<&'a str> {
+        self.values("Socket", "ListenFIFO")
+    }
+
+    /// `Accept`, whether a service instance is started for each connection
+    /// instead of one service for all of them.  Defaults to `false`.
+    pub fn accept(&self) -> bool {
+        self.value("Socket", "Accept")
+            .and_then(parse_bool)
+            .unwrap_or(false)
+    }
+
+    /// `SocketMode`, the permissions of Unix sockets and FIFOs.  Defaults to
+    /// `0o666`, and is `None` if it isn't a valid octal mode.
+    pub fn socket_mode(&self) -> Option<u32> {
+        match self.value("Socket", "SocketMode") {
+            Some(mode) => u32::from_str_radix(mode, 8)
+                .ok()
+                .filter(|mode| *mode <= 0o7777),
+            None => Some(0o666),
+        }
+    }
+
+    fn addresses(&self, key: &str) -> Vec<SocketAddress> {
+        self.values("Socket", key)
+            .into_iter()
+            .filter_map(|address| address.parse().ok())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Entry;
+
+    #[test]
+    fn addresses() {
+        let valid = [
+            ("22", SocketAddress::Port(22)),
+            (
+                "0.0.0.0:53",
+                SocketAddress::Inet("0.0.0.0:53".parse().unwrap()),
+            ),
+            (
+                "[fe80::1%2]:80",
+                SocketAddress::Inet("[fe80::1%2]:80".parse().unwrap()),
+            ),
+            ("/run/foo.sock", SocketAddress::Unix("/run/foo.sock".into())),
+            ("@foo", SocketAddress::Abstract("foo".to_owned())),
+            (
+                "vsock:2:1234",
+                SocketAddress::Vsock {
+                    cid: Some(2),
+                    port: 1234,
+                },
+            ),
+            (
+                "vsock::1234",
+                SocketAddress::Vsock {
+                    cid: None,
+                    port: 1234,
+                },
+            ),
+        ];
+        for (s, address) in &valid {
+            assert_eq!(SocketAddress::parse(s).as_ref(), Ok(address));
+            assert_eq!(address.to_string(), *s);
+        }
+        let invalid =
+            ["", "0", "65536", "::1:80", "localhost:80", "@", "vsock:x:1"];
+        for s in &invalid {
+            assert!(SocketAddress::parse(s).is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn socket_unit() {
+        let entry = Entry::parse(
+            &b"[Socket]\nListenStream=8080\nListenStream=[::1]:8080\n\
+               ListenStream=nope\nListenDatagram=/run/foo.dgram\n\
+               ListenFIFO=/run/foo.fifo\nAccept=yes\nSocketMode=0600"[..],
+        )
+        .unwrap();
+        let unit = entry.systemd_unit();
+        assert_eq!(
+            unit.listen_stream(),
+            [
+                SocketAddress::Port(8080),
+                SocketAddress::Inet("[::1]:8080".parse().unwrap())
+            ]
+        );
+        assert_eq!(
+            unit.listen_datagram(),
+            [SocketAddress::Unix("/run/foo.dgram".into())]
+        );
+        assert!(unit.listen_sequential_packet().is_empty());
+        assert_eq!(unit.listen_fifo(), ["/run/foo.fifo"]);
+        assert!(unit.accept());
+        assert_eq!(unit.socket_mode(), Some(0o600));
+
+        let entry = Entry::parse(&b"[Socket]\nSocketMode=999"[..]).unwrap();
+        let unit = entry.systemd_unit();
+        assert!(!unit.accept());
+        assert_eq!(unit.socket_mode(), None);
+    }
+}
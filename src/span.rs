@@ -0,0 +1,116 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+/// The location of a piece of the input.
+///
+/// All the byte slices in [`SectionBytes`](struct.SectionBytes.html) and
+/// [`AttrBytes`](struct.AttrBytes.html) point into the input, so their spans
+/// can be found with [`Span::of`](#method.of).
+///
+/// Example:
+/// ```
+/// use freedesktop_entry_parser::low_level::{parse_entry, Span};
+///
+/// let input = b"[Desktop Entry]\nName=Firefox";
+/// let section = parse_entry(input).next().unwrap()?;
+/// let span = Span::of(input, section.attrs[0].value).unwrap();
+/// assert_eq!((span.start, span.end), (21, 28));
+/// assert_eq!((span.line, span.column), (2, 6));
+/// # Ok::<(), freedesktop_entry_parser::ParseError>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Span {
+    /// Byte offset of the start
+    pub start: usize,
+    /// Byte offset one past the end
+    pub end: usize,
+    /// Line of the start, starting at 1
+    pub line: usize,
+    /// Column of the start in bytes, starting at 1
+    pub column: usize,
+}
+
+impl Span {
+    /// The span of `part` in `input`.  Returns `None` if `part` lies outside
+    /// of `input`.
+    ///
+    /// The span is found from where `part` is in memory, so `part` has to be
+    /// a slice of `input`.  An empty slice from somewhere else could happen
+    /// to point into `input` and would get a meaningless span.
+    pub fn of(input: &[u8], part: &[u8]) -> Option<Self> {
+        let start =
+            (part.as_ptr() as usize).checked_sub(input.as_ptr() as usize)?;
+        let end = start.checked_add(part.len())?;
+        if end > input.len() {
+            return None;
+        }
+        let (line, column) = line_col(input, start);
+        Some(Span {
+            start,
+            end,
+            line,
+            column,
+        })
+    }
+}
+
+/// Line and column of `offset` in `input`, both starting at 1.
+pub(crate) fn line_col(input: &[u8], offset: usize) -> (usize, usize) {
+    let before = &input[..offset];
+    let line = before.iter().filter(|c| **c == b'\n').count() + 1;
+    let line_start = before
+        .iter()
+        .rposition(|c| *c == b'\n')
+        .map_or(0, |i| i + 1);
+    (line, offset - line_start + 1)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        low_level::{parse_entry, parse_entry_with},
+        LeadingAttrs, ParserOptions,
+    };
+
+    #[test]
+    fn spans() {
+        let input = b"# Comment\n[Bar]\nA = 1\nB[de]=2\n";
+        let section = parse_entry(input).next().unwrap().unwrap();
+        assert_eq!(
+            Span::of(input, section.title),
+            Some(Span {
+                start: 11,
+                end: 14,
+                line: 2,
+                column: 2
+            })
+        );
+        let attr = &section.attrs[1];
+        assert_eq!(Span::of(input, attr.name).unwrap().line, 4);
+        let param = attr.param.as_ref().unwrap().param;
+        assert_eq!(Span::of(input, param).unwrap().column, 3);
+        assert_eq!(Span::of(input, section.attrs[0].value).unwrap().column, 5);
+    }
+
+    #[test]
+    fn not_in_input() {
+        assert_eq!(Span::of(b"abc", b"xyz"), None);
+        let input = b"abcdef";
+        assert_eq!(Span::of(&input[..3], &input[4..]), None);
+        assert_eq!(Span::of(&input[..3], &input[4..4]), None);
+        assert_eq!(Span::of(&input[2..], &input[..1]), None);
+    }
+
+    #[test]
+    fn global_section() {
+        let options =
+            ParserOptions::default().leading_attrs(LeadingAttrs::Global);
+        let input = b"# Comment\nName=Foo\n[Bar]\nBaz=1";
+        let section = parse_entry_with(input, &options).next().unwrap();
+        let span = Span::of(input, section.unwrap().title).unwrap();
+        assert_eq!((span.start, span.end), (10, 10));
+        assert_eq!((span.line, span.column), (2, 1));
+    }
+}
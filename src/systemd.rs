@@ -0,0 +1,1610 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Systemd [unit
+//! files](https://www.freedesktop.org/software/systemd/man/systemd.unit.html).
+//!
+//! Example:
+//! ```
+//! use freedesktop_entry_parser::{parse_entry, systemd::Restart};
+//!
+//! let entry = parse_entry("./test_data/sshd.service")?;
+//! let unit = entry.systemd_unit();
+//! assert_eq!(unit.description(), Some("OpenSSH Daemon"));
+//! assert_eq!(unit.exec_start(), ["/usr/bin/sshd -D"]);
+//! assert_eq!(unit.restart(), Restart::Always);
+//! assert_eq!(unit.wanted_by(), ["multi-user.target"]);
+//! # Ok::<(), freedesktop_entry_parser::ParseError>(())
+//! ```
+//!
+//! Keys can be assigned more than once, and sections repeated.  For list
+//! keys like `After` and `Environment` every assignment adds to the list,
+//! and an empty assignment resets it.  Other keys take their last value,
+//! with an empty one resetting them to the default.  The accessors of
+//! [`SystemdUnit`](struct.SystemdUnit.html) follow these rules, and
+//! [`value`](struct.SystemdUnit.html#method.value),
+//! [`values`](struct.SystemdUnit.html#method.values) and
+//! [`list`](struct.SystemdUnit.html#method.list) apply them to any key.
+//!
+//! Units are configured by their file and the drop-ins next to it, which
+//! [`load_unit`](fn.load_unit.html) merges into one entry.
+//!
+//! Values can refer to the unit's name and environment with specifiers,
+//! like `%i` for the instance or `%h` for the home directory, which
+//! [`Specifiers`](struct.Specifiers.html) expands.
+//!
+//! Whether a unit would start on a system can be checked with its
+//! [`conditions`](struct.SystemdUnit.html#method.conditions), which are
+//! evaluated against a [`Probe`](trait.Probe.html) of the system.
+use crate::{
+    errors::{InvalidUnitName, SpecifierError},
+    parser::{writable_name, writable_title},
+    AttrSelector, Entry, EntryRef, ParseError,
+};
+use std::{
+    collections::BTreeMap,
+    convert::{Infallible, TryFrom},
+    env,
+    fmt::{self, Display, Formatter},
+    fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+
+pub use crate::{
+    calendar::CalendarEvent,
+    condition::{
+        evaluate_conditions, Condition, ConditionKind, PathInfo, Probe,
+        SystemProbe,
+    },
+    socket::SocketAddress,
+};
+
+/// A typed view of a unit file, with accessors for the common keys of the
+/// `[Unit]`, `[Service]` and `[Install]` sections.
+///
+/// Created by [`EntryRef::systemd_unit`](../struct.EntryRef.html#method.systemd_unit)
+pub struct SystemdUnit<'a> {
+    pub(crate) entry: &'a EntryRef<'a>,
+}
+
+/// The `Type` of a service, how systemd knows it has started.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ServiceType {
+    /// Started as soon as the process was forked.  This is the default.
+    Simple,
+    /// Started once the program was executed
+    Exec,
+    /// Started once the process forked and the parent exited
+    Forking,
+    /// Started once the process exited
+    Oneshot,
+    /// Started once it took its `BusName` on D-Bus
+    Dbus,
+    /// Started once it sent a notification with `sd_notify`
+    Notify,
+    /// Like `Notify`, and also notifies about reloads
+    NotifyReload,
+    /// Like `Simple`, but delayed until the other jobs are done
+    Idle,
+    /// Any other type
+    Unknown(String),
+}
+
+/// The `Restart` policy of a service, when it's restarted after it exits.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Restart {
+    /// Never.  This is the default.
+    No,
+    /// When it exited cleanly
+    OnSuccess,
+    /// When it exited uncleanly, was killed by a signal or timed out
+    OnFailure,
+    /// When it was killed by a signal or timed out
+    OnAbnormal,
+    /// When its watchdog timed out
+    OnWatchdog,
+    /// When it was killed by a signal that wasn't handled
+    OnAbort,
+    /// Always
+    Always,
+    /// Any other policy
+    Unknown(String),
+}
+
+/// The name of a unit, like `sshd.service`, split into its parts.
+///
+/// Templates have an `@` without an instance, like `getty@.service`, and
+/// are instantiated by adding one, like `getty@tty1.service`.
+///
+/// Example:
+/// ```
+/// use freedesktop_entry_parser::systemd::UnitName;
+///
+/// let name: UnitName = "getty@.service".parse()?;
+/// assert!(name.is_template());
+/// let name = name.instantiate("tty1")?;
+/// assert_eq!(name.to_string(), "getty@tty1.service");
+/// assert_eq!(name.prefix(), "getty");
+/// assert_eq!(name.instance(), Some("tty1"));
+/// assert_eq!(name.suffix(), "service");
+/// # Ok::<(), freedesktop_entry_parser::errors::InvalidUnitName>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct UnitName {
+    prefix: String,
+    instance: Option<String>,
+    suffix: String,
+}
+
+/// Which service manager's units to look up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UnitScope {
+    /// The system's units
+    System,
+    /// The units of the user's service manager
+    User,
+}
+
+/// A unit loaded with its drop-ins by [`load_unit`](fn.load_unit.html).
+#[derive(Debug)]
+pub struct LoadedUnit {
+    /// The name the unit was loaded as
+    pub name: String,
+    /// The unit file, the template's for instances without their own
+    pub path: PathBuf,
+    /// The drop-ins that were merged, in the order they were applied
+    pub drop_ins: Vec<PathBuf>,
+    /// The unit file with the drop-ins merged into it
+    pub entry: Entry,
+}
+
+/// The symlinks that enable a unit, computed from its `[Install]` section
+/// by [`LoadedUnit::enable_plan`](struct.LoadedUnit.html#method.enable_plan).
+///
+/// Enabling a unit creates the symlinks and disabling it removes them,
+/// followed by a reload of the service manager.  The units in `also` are
+/// enabled or disabled with it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EnablePlan {
+    /// The symlinks to create
+    pub symlinks: Vec<Symlink>,
+    /// `Also`, other units to enable or disable with this one
+    pub also: Vec<String>,
+}
+
+/// A symlink of an [`EnablePlan`](struct.EnablePlan.html).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symlink {
+    /// Where the symlink is created
+    pub path: PathBuf,
+    /// The unit file it points to
+    pub target: PathBuf,
+}
+
+impl LoadedUnit {
+    /// Get a typed view of the merged unit.
+    pub fn unit(&self) -> SystemdUnit<'_> {
+        self.entry.systemd_unit()
+    }
+
+    /// The symlinks `systemctl enable` would create in `config_dir`, like
+    /// `/etc/systemd/system`, for this unit:
+    /// - `X.wants/name` for `WantedBy=X`, and `X.requires/name` and
+    ///   `X.upholds/name` for `RequiredBy` and `UpheldBy`
+    /// - `A` for `Alias=A`
+    ///
+    /// They all point to the unit file.  Templates are enabled as their
+    /// `DefaultInstance`, and only their `Also` units if they have none.
+    /// Specifiers in the values are expanded for the enabled name.
+    pub fn enable_plan(&self, config_dir: impl AsRef<Path>) -> EnablePlan {
+        let config_dir = config_dir.as_ref();
+        let unit = self.unit();
+        let mut plan = EnablePlan {
+            also: unit.also().into_iter().map(ToOwned::to_owned).collect(),
+            ..EnablePlan::default()
+        };
+        let name = match UnitName::parse(&self.name) {
+            Ok(name) if name.is_template() => {
+                let instance = unit.value("Install", "DefaultInstance");
+                match instance.and_then(|i| name.instantiate(i).ok()) {
+                    Some(name) => name.to_string(),
+                    None => return plan,
+                }
+            }
+            _ => self.name.clone(),
+        };
+        let specifiers = Specifiers::new(&name);
+        let expand = |value: &str| {
+            specifiers
+                .expand(value)
+                .unwrap_or_else(|_| value.to_owned())
+        };
+        let dependencies = [
+            ("WantedBy", "wants"),
+            ("RequiredBy", "requires"),
+            ("UpheldBy", "upholds"),
+        ];
+        for (key, suffix) in &dependencies {
+            for target in unit.list("Install", key) {
+                let dir = format!("{}.{}", expand(target), suffix);
+                plan.symlinks.push(Symlink {
+                    path: config_dir.join(dir).join(&name),
+                    target: self.path.clone(),
+                });
+            }
+        }
+        for alias in unit.alias() {
+            plan.symlinks.push(Symlink {
+                path: config_dir.join(expand(alias)),
+                target: self.path.clone(),
+            });
+        }
+        plan
+    }
+}
+
+impl UnitScope {
+    /// The directory `systemctl enable` creates symlinks in for this scope,
+    /// `None` for `User` if neither `XDG_CONFIG_HOME` nor `HOME` are set.
+    pub fn config_dir(self) -> Option<PathBuf> {
+        match self {
+            UnitScope::System => Some(PathBuf::from("/etc/systemd/system")),
+            UnitScope::User => xdg_dir("XDG_CONFIG_HOME", ".config")
+                .map(|dir| dir.join("systemd/user")),
+        }
+    }
+}
+
+/// The directories units are loaded from, from the most to the least
+/// important.
+pub fn unit_dirs(scope: UnitScope) -> Vec<PathBuf> {
+    match scope {
+        UnitScope::System => [
+            "/etc/systemd/system",
+            "/run/systemd/system",
+            "/usr/local/lib/systemd/system",
+            "/usr/lib/systemd/system",
+        ]
+        .iter()
+        .map(PathBuf::from)
+        .collect(),
+        UnitScope::User => {
+            let config = xdg_dir("XDG_CONFIG_HOME", ".config");
+            let data = xdg_dir("XDG_DATA_HOME", ".local/share");
+            let mut dirs: Vec<PathBuf> = config
+                .map(|dir| dir.join("systemd/user"))
+                .into_iter()
+                .collect();
+            dirs.push(PathBuf::from("/etc/systemd/user"));
+            dirs.push(PathBuf::from("/run/systemd/user"));
+            dirs.extend(data.map(|dir| dir.join("systemd/user")));
+            dirs.push(PathBuf::from("/usr/local/lib/systemd/user"));
+            dirs.push(PathBuf::from("/usr/lib/systemd/user"));
+            dirs
+        }
+    }
+}
+
+/// Load the unit `name`, like `sshd.service`, from the
+/// [`unit_dirs`](fn.unit_dirs.html) of `scope` and merge its drop-ins into
+/// it, the way systemd does.
+pub fn load_unit(
+    name: &str,
+    scope: UnitScope,
+) -> Result<LoadedUnit, ParseError> {
+    load_unit_in(&unit_dirs(scope), name)
+}
+
+/// Load the unit `name` from `dirs` and merge its drop-ins into it.
+///
+/// The unit file is taken from the first directory that has it.  Instances
+/// of templates without their own file use the template's, like
+/// `getty@.service` for `getty@tty1.service`.  Drop-ins are the `.conf`
+/// files in the `name.d` directories of all the `dirs`, and the template's
+/// for instances, where a drop-in hides those with the same file name in
+/// later directories.  They're applied sorted by file name, so their assignments
+/// come after the unit's own ones, and can add to lists or reset them.
+///
+/// Returns [`ParseError::Io`](errors/enum.ParseError.html#variant.Io) with
+/// `NotFound` if none of the `dirs` has the unit.
+pub fn load_unit_in(
+    dirs: &[impl AsRef<Path>],
+    name: &str,
+) -> Result<LoadedUnit, ParseError> {
+    let template = UnitName::parse(name)
+        .ok()
+        .and_then(|name| name.template())
+        .map(|template| template.to_string());
+    let names: Vec<&str> =
+        Some(name).into_iter().chain(template.as_deref()).collect();
+    let path = names
+        .iter()
+        .flat_map(|name| dirs.iter().map(move |dir| dir.as_ref().join(name)))
+        .find(|path| path.is_file())
+        .ok_or_else(|| {
+            ParseError::Io(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("Unit `{}` not found", name),
+            ))
+        })?;
+    let mut entry = Entry::parse_file(&path)?;
+
+    let mut drop_ins = BTreeMap::new();
+    let drop_in_dirs = dirs.iter().flat_map(|dir| {
+        names
+            .iter()
+            .map(move |name| dir.as_ref().join(format!("{}.d", name)))
+    });
+    for dir in drop_in_dirs {
+        let files = match fs::read_dir(&dir) {
+            Ok(files) => files,
+            Err(_) => continue,
+        };
+        for file in files.flatten() {
+            let file_name = match file.file_name().into_string() {
+                Ok(file_name) if file_name.ends_with(".conf") => file_name,
+                _ => continue,
+            };
+            drop_ins.entry(file_name).or_insert_with(|| file.path());
+        }
+    }
+    for drop_in in drop_ins.values() {
+        let drop_in = Entry::parse_file(drop_in)?;
+        merge(&mut entry, &drop_in);
+    }
+
+    Ok(LoadedUnit {
+        name: name.to_owned(),
+        path,
+        drop_ins: drop_ins.into_values().collect(),
+        entry,
+    })
+}
+
+/// Append every assignment of `drop_in` to `entry`.  Names that can't be
+/// set, which systemd wouldn't accept either, are skipped.
+fn merge(entry: &mut Entry, drop_in: &EntryRef) {
+    for section in drop_in.sections() {
+        if !writable_title(section.name()) {
+            continue;
+        }
+        entry.add_section(section.name());
+        let mut target = entry.section_mut(section.name());
+        for attr in section.attrs() {
+            if attr.name.is_empty() || !writable_name(attr.name) {
+                continue;
+            }
+            for (param, value) in attr.values() {
+                match param {
+                    Some(param) if !writable_name(param) => {}
+                    Some(param) => {
+                        target.set_attr_with_param(attr.name, param, value)
+                    }
+                    None => target.add_attr(attr.name, value),
+                }
+            }
+        }
+    }
+}
+
+/// The values of specifiers for a unit, used to expand them.
+///
+/// The specifiers from the unit's name are set by [`new`](#method.new), the
+/// others have to be set with the setters, or taken from the current
+/// environment with [`current`](#method.current).  `%%` always expands to
+/// `%`.
+///
+/// Example:
+/// ```
+/// use freedesktop_entry_parser::systemd::Specifiers;
+///
+/// let specifiers = Specifiers::new("backup@docs.service").home("/home/me");
+/// assert_eq!(
+///     specifiers.expand("%h/bin/backup %i").unwrap(),
+///     "/home/me/bin/backup docs"
+/// );
+/// assert!(specifiers.expand("%H").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Specifiers {
+    values: BTreeMap<char, String>,
+}
+
+impl Specifiers {
+    /// The specifiers of the unit `name`: `%n` for the full name, `%N` for
+    /// it without the type suffix, `%p` for the prefix before the `@`, `%i`
+    /// for the instance after it, and `%j` for the last `-` separated part
+    /// of the prefix.  `%P`, `%I` and `%J` are their unescaped versions,
+    /// and `%f` the unescaped instance, or prefix if there's none, as a
+    /// path.  `%T` and `%V` are set to `/tmp` and `/var/tmp`.
+    pub fn new(name: &str) -> Self {
+        let without_suffix = match name.rfind('.') {
+            Some(dot) => &name[..dot],
+            None => name,
+        };
+        let (prefix, instance, _) = split_name(name);
+        let instance = instance.unwrap_or_default();
+        let last = prefix.rsplit('-').next().unwrap_or(prefix);
+        let unescape =
+            |s: &str| unescape_name(s).unwrap_or_else(|| s.to_owned());
+        let path = if instance.is_empty() {
+            prefix
+        } else {
+            instance
+        };
+        let path = unescape_path(path).unwrap_or_else(|| path.to_owned());
+        Specifiers {
+            values: BTreeMap::new(),
+        }
+        .set('n', name)
+        .set('N', without_suffix)
+        .set('p', prefix)
+        .set('P', unescape(prefix))
+        .set('i', instance)
+        .set('I', unescape(instance))
+        .set('j', last)
+        .set('J', unescape(last))
+        .set('f', path)
+        .set('T', "/tmp")
+        .set('V', "/var/tmp")
+    }
+
+    /// The specifiers of the unit `name` in the current environment, with
+    /// the user, home directory, host name, machine ID and the directories
+    /// of the service manager of `scope` set where they're known.
+    pub fn current(name: &str, scope: UnitScope) -> Self {
+        let mut specifiers = Specifiers::new(name);
+        let var =
+            |name: &str| env::var(name).ok().filter(|value| !value.is_empty());
+        let file = |path: &str| {
+            fs::read_to_string(path)
+                .ok()
+                .map(|contents| contents.trim().to_owned())
+                .filter(|contents| !contents.is_empty())
+        };
+        if let Some(user) = var("USER") {
+            specifiers = specifiers.user(user);
+        }
+        if let Some(home) = var("HOME") {
+            specifiers = specifiers.home(home);
+        }
+        if let Some(host) = file("/etc/hostname").or_else(|| var("HOSTNAME")) {
+            specifiers = specifiers.host(host);
+        }
+        if let Some(machine_id) = file("/etc/machine-id") {
+            specifiers = specifiers.machine_id(machine_id);
+        }
+        let dirs = match scope {
+            UnitScope::System => [
+                Some("/run".into()),
+                Some("/var/lib".into()),
+                Some("/var/cache".into()),
+                Some("/var/log".into()),
+                Some("/etc".into()),
+            ],
+            UnitScope::User => {
+                let state = xdg_dir("XDG_STATE_HOME", ".local/state");
+                [
+                    var("XDG_RUNTIME_DIR").map(PathBuf::from),
+                    state.clone(),
+                    xdg_dir("XDG_CACHE_HOME", ".cache"),
+                    state.map(|state| state.join("log")),
+                    xdg_dir("XDG_CONFIG_HOME", ".config"),
+                ]
+            }
+        };
+        for (specifier, dir) in ['t', 'S', 'C', 'L', 'E'].iter().zip(dirs) {
+            if let Some(dir) = dir.as_ref().and_then(|dir| dir.to_str()) {
+                specifiers = specifiers.set(*specifier, dir);
+            }
+        }
+        specifiers
+    }
+
+    /// Set the value of `specifier`, like `'m'` for `%m`.
+    pub fn set(mut self, specifier: char, value: impl Into<String>) -> Self {
+        self.values.insert(specifier, value.into());
+        self
+    }
+
+    /// `%u`, the name of the user the unit runs as
+    pub fn user(self, user: impl Into<String>) -> Self {
+        self.set('u', user)
+    }
+
+    /// `%U`, the ID of the user the unit runs as
+    pub fn uid(self, uid: u32) -> Self {
+        self.set('U', uid.to_string())
+    }
+
+    /// `%h`, the home directory of the user the unit runs as
+    pub fn home(self, home: impl Into<String>) -> Self {
+        self.set('h', home)
+    }
+
+    /// `%H`, the host name.  Also sets `%l`, the host name up to the first
+    /// `.`.
+    pub fn host(self, host: impl Into<String>) -> Self {
+        let host = host.into();
+        let short = host.split('.').next().unwrap_or_default().to_owned();
+        self.set('H', host).set('l', short)
+    }
+
+    /// `%m`, the machine ID
+    pub fn machine_id(self, machine_id: impl Into<String>) -> Self {
+        self.set('m', machine_id)
+    }
+
+    /// The value of `specifier`, `None` if it isn't set
+    pub fn get(&self, specifier: char) -> Option<&str> {
+        self.values.get(&specifier).map(String::as_str)
+    }
+
+    /// Replace the specifiers in `value` with their values.
+    pub fn expand(&self, value: &str) -> Result<String, SpecifierError> {
+        let mut expanded = String::with_capacity(value.len());
+        let mut chars = value.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                expanded.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('%') => expanded.push('%'),
+                Some(specifier) => expanded.push_str(
+                    self.get(specifier)
+                        .ok_or(SpecifierError::Unknown(specifier))?,
+                ),
+                None => {
+                    return Err(SpecifierError::Unterminated {
+                        value: value.to_owned(),
+                    })
+                }
+            }
+        }
+        Ok(expanded)
+    }
+}
+
+impl UnitName {
+    /// The unit types, which are the suffixes names can have
+    pub const SUFFIXES: &'static [&'static str] = &[
+        "service",
+        "socket",
+        "device",
+        "mount",
+        "automount",
+        "swap",
+        "target",
+        "path",
+        "timer",
+        "slice",
+        "scope",
+    ];
+
+    /// Parse a unit name.  It must have one of the
+    /// [`SUFFIXES`](#associatedconstant.SUFFIXES), a non-empty prefix, at
+    /// most one `@`, and only ASCII letters, digits and `:-_.\\` otherwise.
+    pub fn parse(name: &str) -> Result<Self, InvalidUnitName> {
+        let (prefix, instance, suffix) = split_name(name);
+        let valid = |part: &str| {
+            part.chars()
+                .all(|c| c.is_ascii_alphanumeric() || ":-_.\\".contains(c))
+        };
+        let valid_instance = match instance {
+            Some(instance) => valid(instance),
+            None => true,
+        };
+        if name.len() > 255
+            || prefix.is_empty()
+            || !valid(prefix)
+            || !valid_instance
+            || !Self::SUFFIXES.contains(&suffix)
+        {
+            return Err(InvalidUnitName(name.to_owned()));
+        }
+        Ok(UnitName {
+            prefix: prefix.to_owned(),
+            instance: instance.map(ToOwned::to_owned),
+            suffix: suffix.to_owned(),
+        })
+    }
+
+    /// The part before the `@`, or before the suffix if there's no `@`
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// The part between the `@` and the suffix.  `None` if there's no `@`,
+    /// and empty for templates.
+    pub fn instance(&self) -> Option<&str> {
+        self.instance.as_deref()
+    }
+
+    /// The unit type, like `service`, without the `.`
+    pub fn suffix(&self) -> &str {
+        &self.suffix
+    }
+
+    /// Whether this is a template, like `getty@.service`
+    pub fn is_template(&self) -> bool {
+        self.instance.as_deref() == Some("")
+    }
+
+    /// Whether this is an instance of a template, like
+    /// `getty@tty1.service`
+    pub fn is_instance(&self) -> bool {
+        !self.instance.as_deref().unwrap_or_default().is_empty()
+    }
+
+    /// The template this is an instance of, `None` if it isn't an
+    /// instance.
+    pub fn template(&self) -> Option<UnitName> {
+        if !self.is_instance() {
+            return None;
+        }
+        Some(UnitName {
+            instance: Some(String::new()),
+            ..self.clone()
+        })
+    }
+
+    /// The instance of this template with `instance`.  Fails if this isn't
+    /// a template or the instance has invalid characters.
+    pub fn instantiate(
+        &self,
+        instance: &str,
+    ) -> Result<UnitName, InvalidUnitName> {
+        let name = format!("{}@{}.{}", self.prefix, instance, self.suffix);
+        if !self.is_template() || instance.is_empty() {
+            return Err(InvalidUnitName(name));
+        }
+        UnitName::parse(&name)
+    }
+
+    /// The name of the unit of type `suffix` for `path`, like `home.mount`
+    /// for `/home`.
+    pub fn from_path(
+        path: &str,
+        suffix: &str,
+    ) -> Result<UnitName, InvalidUnitName> {
+        UnitName::parse(&format!("{}.{}", escape_path(path), suffix))
+    }
+}
+
+impl FromStr for UnitName {
+    type Err = InvalidUnitName;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        UnitName::parse(s)
+    }
+}
+
+impl Display for UnitName {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.prefix)?;
+        if let Some(instance) = &self.instance {
+            write!(f, "@{}", instance)?;
+        }
+        write!(f, ".{}", self.suffix)
+    }
+}
+
+/// Escape `s` for use in a unit name, like `systemd-escape` does: `/`
+/// becomes `-`, and characters other than ASCII letters, digits, `:`, `_`
+/// and `.` are written as `\\xNN` for each of their bytes.  A leading `.`
+/// is escaped too.
+///
+/// Example:
+/// ```
+/// use freedesktop_entry_parser::systemd::{escape_name, unescape_name};
+///
+/// assert_eq!(escape_name("a-b/c d"), "a\\x2db-c\\x20d");
+/// assert_eq!(unescape_name("a\\x2db-c\\x20d").as_deref(), Some("a-b/c d"));
+/// ```
+pub fn escape_name(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for (i, b) in s.bytes().enumerate() {
+        match b {
+            b'/' => escaped.push('-'),
+            b'.' if i == 0 => escaped.push_str("\\x2e"),
+            b if b.is_ascii_alphanumeric() || b":_.".contains(&b) => {
+                escaped.push(b as char)
+            }
+            b => escaped.push_str(&format!("\\x{:02x}", b)),
+        }
+    }
+    escaped
+}
+
+/// Escape the path `path` for use in a unit name, like `systemd-escape
+/// --path` does.  Repeated, leading and trailing `/` are removed first, and
+/// the root directory becomes `-`.
+///
+/// Example:
+/// ```
+/// use freedesktop_entry_parser::systemd::escape_path;
+///
+/// assert_eq!(escape_path("/home/me/"), "home-me");
+/// assert_eq!(escape_path("/"), "-");
+/// ```
+pub fn escape_path(path: &str) -> String {
+    let path = path
+        .split('/')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("/");
+    if path.is_empty() {
+        "-".to_owned()
+    } else {
+        escape_name(&path)
+    }
+}
+
+/// The reverse of [`escape_name`](fn.escape_name.html).  Returns `None` if
+/// there's an invalid escape sequence or the result isn't valid UTF-8.
+pub fn unescape_name(s: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut rest = s.as_bytes();
+    while let Some((&b, tail)) = rest.split_first() {
+        rest = tail;
+        match b {
+            b'-' => bytes.push(b'/'),
+            b'\\' => {
+                let hex = rest.get(..3).filter(|hex| hex[0] == b'x')?;
+                let hex = std::str::from_utf8(&hex[1..]).ok()?;
+                bytes.push(u8::from_str_radix(hex, 16).ok()?);
+                rest = &rest[3..];
+            }
+            b => bytes.push(b),
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// The reverse of [`escape_path`](fn.escape_path.html), an absolute path.
+pub fn unescape_path(s: &str) -> Option<String> {
+    if s == "-" {
+        return Some("/".to_owned());
+    }
+    unescape_name(s).map(|path| format!("/{}", path))
+}
+
+/// Split a unit name into its prefix, instance and suffix, without
+/// checking them.
+fn split_name(name: &str) -> (&str, Option<&str>, &str) {
+    let (name, suffix) = match name.rfind('.') {
+        Some(dot) => (&name[..dot], &name[dot + 1..]),
+        None => (name, ""),
+    };
+    match name.find('@') {
+        Some(at) => (&name[..at], Some(&name[at + 1..]), suffix),
+        None => (name, None, suffix),
+    }
+}
+
+impl<'a> EntryRef<'a> {
+    /// Get a typed view of a systemd unit file.
+    pub fn systemd_unit(&self) -> SystemdUnit<'_> {
+        SystemdUnit { entry: self }
+    }
+}
+
+impl<'a> SystemdUnit<'a> {
+    /// The `[Unit]` section
+    pub fn unit(&self) -> AttrSelector<'a, &'static str> {
+        self.entry.section("Unit")
+    }
+
+    /// The `[Service]` section
+    pub fn service(&self) -> AttrSelector<'a, &'static str> {
+        self.entry.section("Service")
+    }
+
+    /// The `[Install]` section
+    pub fn install(&self) -> AttrSelector<'a, &'static str> {
+        self.entry.section("Install")
+    }
+
+    /// The last value of `key` in `section`.  `None` if it's missing or
+    /// was reset with an empty value.
+    pub fn value(&self, section: &str, key: &str) -> Option<&'a str> {
+        self.values(section, key).pop()
+    }
+
+    /// Every value of `key` in `section`, from all the sections with that
+    /// name, starting over after each empty value.
+    pub fn values(&self, section: &str, key: &str) -> Vec<&'a str> {
+        let mut values = Vec::new();
+        for section in self.entry.sections_named(section) {
+            for value in section.attr_values(key) {
+                if value.is_empty() {
+                    values.clear();
+                } else {
+                    values.push(value);
+                }
+            }
+        }
+        values
+    }
+
+    /// The items of the space separated list `key` in `section`, from all
+    /// its [`values`](#method.values).
+    pub fn list(&self, section: &str, key: &str) -> Vec<&'a str> {
+        self.values(section, key)
+            .into_iter()
+            .flat_map(str::split_whitespace)
+            .collect()
+    }
+
+    /// `Description` of the unit
+    pub fn description(&self) -> Option<&'a str> {
+        self.value("Unit", "Description")
+    }
+
+    /// `Documentation`, URIs of the unit's documentation
+    pub fn documentation(&self) -> Vec<&'a str> {
+        self.list("Unit", "Documentation")
+    }
+
+    /// `Requires`, units that have to be started with this one
+    pub fn requires(&self) -> Vec<&'a str> {
+        self.list("Unit", "Requires")
+    }
+
+    /// `Wants`, units that should be started with this one
+    pub fn wants(&self) -> Vec<&'a str> {
+        self.list("Unit", "Wants")
+    }
+
+    /// `After`, units this one is started after
+    pub fn after(&self) -> Vec<&'a str> {
+        self.list("Unit", "After")
+    }
+
+    /// `Before`, units this one is started before
+    pub fn before(&self) -> Vec<&'a str> {
+        self.list("Unit", "Before")
+    }
+
+    /// `Conflicts`, units that are stopped when this one is started
+    pub fn conflicts(&self) -> Vec<&'a str> {
+        self.list("Unit", "Conflicts")
+    }
+
+    /// `Type` of the service.  Defaults to `Simple`.
+    pub fn service_type(&self) -> ServiceType {
+        match self.value("Service", "Type") {
+            Some(ty) => match ty.parse() {
+                Ok(ty) => ty,
+                Err(e) => match e {},
+            },
+            None => ServiceType::Simple,
+        }
+    }
+
+    /// `ExecStart`, the command lines that start the service.  Only
+    /// `Oneshot` services can have more than one.
+    pub fn exec_start(&self) -> Vec<&'a str> {
+        self.values("Service", "ExecStart")
+    }
+
+    /// `ExecStop`, the command lines that stop the service
+    pub fn exec_stop(&self) -> Vec<&'a str> {
+        self.values("Service", "ExecStop")
+    }
+
+    /// `ExecReload`, the command lines that reload the service's
+    /// configuration
+    pub fn exec_reload(&self) -> Vec<&'a str> {
+        self.values("Service", "ExecReload")
+    }
+
+    /// `Restart` policy of the service.  Defaults to `No`.
+    pub fn restart(&self) -> Restart {
+        match self.value("Service", "Restart") {
+            Some(restart) => match restart.parse() {
+                Ok(restart) => restart,
+                Err(e) => match e {},
+            },
+            None => Restart::No,
+        }
+    }
+
+    /// `User` the service runs as
+    pub fn user(&self) -> Option<&'a str> {
+        self.value("Service", "User")
+    }
+
+    /// `Environment` variables of the service, as names and values.  A
+    /// variable assigned again keeps its place and takes the new value.
+    pub fn environment(&self) -> Vec<(String, String)> {
+        let mut vars: Vec<(String, String)> = Vec::new();
+        let assignments = self
+            .values("Service", "Environment")
+            .into_iter()
+            .flat_map(split_quoted);
+        for assignment in assignments {
+            let mut parts = assignment.splitn(2, '=');
+            let (name, value) = match (parts.next(), parts.next()) {
+                (Some(name), Some(value)) if !name.is_empty() => (name, value),
+                _ => continue,
+            };
+            match vars.iter_mut().find(|(var, _)| var == name) {
+                Some(var) => var.1 = value.to_owned(),
+                None => vars.push((name.to_owned(), value.to_owned())),
+            }
+        }
+        vars
+    }
+
+    /// `WorkingDirectory` of the service
+    pub fn working_directory(&self) -> Option<&'a str> {
+        self.value("Service", "WorkingDirectory")
+    }
+
+    /// The value of `key` in `section` as a
+    /// [time span](fn.parse_time_span.html).  `None` if it's missing or
+    /// invalid.
+    pub fn time_span(&self, section: &str, key: &str) -> Option<Duration> {
+        parse_time_span(self.value(section, key)?)
+    }
+
+    /// `RestartSec`, how long to wait before restarting the service
+    pub fn restart_sec(&self) -> Option<Duration> {
+        self.time_span("Service", "RestartSec")
+    }
+
+    /// `TimeoutStartSec`, how long the service may take to start, or
+    /// `TimeoutSec` if it isn't set
+    pub fn timeout_start_sec(&self) -> Option<Duration> {
+        self.time_span("Service", "TimeoutStartSec")
+            .or_else(|| self.time_span("Service", "TimeoutSec"))
+    }
+
+    /// `TimeoutStopSec`, how long the service may take to stop, or
+    /// `TimeoutSec` if it isn't set
+    pub fn timeout_stop_sec(&self) -> Option<Duration> {
+        self.time_span("Service", "TimeoutStopSec")
+            .or_else(|| self.time_span("Service", "TimeoutSec"))
+    }
+
+    /// The value of `key` in `section` as a [size](fn.parse_size.html) in
+    /// bytes.  `None` if it's missing or invalid.
+    pub fn size(&self, section: &str, key: &str) -> Option<u64> {
+        parse_size(self.value(section, key)?)
+    }
+
+    /// `MemoryMax`, the most memory the service may use in bytes.
+    /// Percentages of the physical memory aren't supported and return
+    /// `None`.
+    pub fn memory_max(&self) -> Option<u64> {
+        self.size("Service", "MemoryMax")
+    }
+
+    /// `OnCalendar`, the calendar events that trigger a timer.  Invalid
+    /// events are skipped.
+    pub fn on_calendar(&self) -> Vec<CalendarEvent> {
+        self.values("Timer", "OnCalendar")
+            .into_iter()
+            .filter_map(|event| event.parse().ok())
+            .collect()
+    }
+
+    /// `WantedBy`, the targets that want this unit when it's enabled
+    pub fn wanted_by(&self) -> Vec<&'a str> {
+        self.list("Install", "WantedBy")
+    }
+
+    /// `RequiredBy`, the targets that require this unit when it's enabled
+    pub fn required_by(&self) -> Vec<&'a str> {
+        self.list("Install", "RequiredBy")
+    }
+
+    /// `Alias`, other names the unit is linked as when it's enabled
+    pub fn alias(&self) -> Vec<&'a str> {
+        self.list("Install", "Alias")
+    }
+
+    /// `Also`, units that are enabled with this one
+    pub fn also(&self) -> Vec<&'a str> {
+        self.list("Install", "Also")
+    }
+}
+
+impl ServiceType {
+    /// The type as it's written in the `Type` key
+    pub fn as_str(&self) -> &str {
+        match self {
+            ServiceType::Simple => "simple",
+            ServiceType::Exec => "exec",
+            ServiceType::Forking => "forking",
+            ServiceType::Oneshot => "oneshot",
+            ServiceType::Dbus => "dbus",
+            ServiceType::Notify => "notify",
+            ServiceType::NotifyReload => "notify-reload",
+            ServiceType::Idle => "idle",
+            ServiceType::Unknown(ty) => ty,
+        }
+    }
+}
+
+impl FromStr for ServiceType {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "simple" => ServiceType::Simple,
+            "exec" => ServiceType::Exec,
+            "forking" => ServiceType::Forking,
+            "oneshot" => ServiceType::Oneshot,
+            "dbus" => ServiceType::Dbus,
+            "notify" => ServiceType::Notify,
+            "notify-reload" => ServiceType::NotifyReload,
+            "idle" => ServiceType::Idle,
+            ty => ServiceType::Unknown(ty.to_owned()),
+        })
+    }
+}
+
+impl Display for ServiceType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Restart {
+    /// The policy as it's written in the `Restart` key
+    pub fn as_str(&self) -> &str {
+        match self {
+            Restart::No => "no",
+            Restart::OnSuccess => "on-success",
+            Restart::OnFailure => "on-failure",
+            Restart::OnAbnormal => "on-abnormal",
+            Restart::OnWatchdog => "on-watchdog",
+            Restart::OnAbort => "on-abort",
+            Restart::Always => "always",
+            Restart::Unknown(restart) => restart,
+        }
+    }
+}
+
+impl FromStr for Restart {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "no" => Restart::No,
+            "on-success" => Restart::OnSuccess,
+            "on-failure" => Restart::OnFailure,
+            "on-abnormal" => Restart::OnAbnormal,
+            "on-watchdog" => Restart::OnWatchdog,
+            "on-abort" => Restart::OnAbort,
+            "always" => Restart::Always,
+            restart => Restart::Unknown(restart.to_owned()),
+        })
+    }
+}
+
+impl Display for Restart {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Parse a time span like `5min 20s`, `1h30m` or `100ms`.  Each number can
+/// have a fraction and is followed by a unit, with or without spaces in
+/// between; a number without a unit is in seconds.  `infinity` is returned
+/// as [`Duration::MAX`](https://doc.rust-lang.org/std/time/struct.Duration.html#associatedconstant.MAX).
+///
+/// The units are `ns`, `us`, `ms`, `s`, `min` (or `m`), `h`, `d`, `w`,
+/// `M` for months of 30.44 days and `y` for years of 365.25 days, as well
+/// as the other spellings systemd accepts, like `sec` or `hours`.
+///
+/// Example:
+/// ```
+/// use freedesktop_entry_parser::systemd::parse_time_span;
+/// use std::time::Duration;
+///
+/// assert_eq!(parse_time_span("5min 20s"), Some(Duration::from_secs(320)));
+/// assert_eq!(parse_time_span("1.5h"), Some(Duration::from_secs(5400)));
+/// assert_eq!(parse_time_span("90"), Some(Duration::from_secs(90)));
+/// assert_eq!(parse_time_span("infinity"), Some(Duration::MAX));
+/// assert_eq!(parse_time_span("5 parsecs"), None);
+/// ```
+pub fn parse_time_span(s: &str) -> Option<Duration> {
+    const SEC: u128 = 1_000_000_000;
+    let s = s.trim();
+    if s == "infinity" {
+        return Some(Duration::MAX);
+    } else if s.is_empty() {
+        return None;
+    }
+    let mut rest = s;
+    let mut total: u128 = 0;
+    while !rest.is_empty() {
+        let number_len = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(rest.len());
+        let (number, tail) = rest.split_at(number_len);
+        let tail = tail.trim_start();
+        let unit_len = tail
+            .find(|c: char| !c.is_alphabetic())
+            .unwrap_or(tail.len());
+        let (unit, tail) = tail.split_at(unit_len);
+        rest = tail.trim_start();
+
+        let nanos = match unit {
+            "ns" | "nsec" => 1,
+            "us" | "usec" | "µs" | "μs" => 1_000,
+            "ms" | "msec" => 1_000_000,
+            "" | "s" | "sec" | "second" | "seconds" => SEC,
+            "m" | "min" | "minute" | "minutes" => 60 * SEC,
+            "h" | "hr" | "hour" | "hours" => 3_600 * SEC,
+            "d" | "day" | "days" => 86_400 * SEC,
+            "w" | "week" | "weeks" => 604_800 * SEC,
+            "M" | "month" | "months" => 2_629_800 * SEC,
+            "y" | "year" | "years" => 31_557_600 * SEC,
+            _ => return None,
+        };
+        total = total.checked_add(scale(number, nanos)?)?;
+    }
+    let secs = u64::try_from(total / SEC).ok()?;
+    Some(Duration::new(secs, (total % SEC) as u32))
+}
+
+/// Parse a size like `2G`, `100M` or `1.5K` into bytes.  The suffixes `K`,
+/// `M`, `G`, `T`, `P` and `E` are powers of 1024, and a number without one,
+/// or with `B`, is in bytes.  Several sizes are added up, like `1G 512M`.
+/// `infinity` is returned as `u64::MAX`.
+///
+/// Example:
+/// ```
+/// use freedesktop_entry_parser::systemd::parse_size;
+///
+/// assert_eq!(parse_size("2G"), Some(2 * 1024 * 1024 * 1024));
+/// assert_eq!(parse_size("1.5K"), Some(1536));
+/// assert_eq!(parse_size("100"), Some(100));
+/// assert_eq!(parse_size("infinity"), Some(u64::MAX));
+/// assert_eq!(parse_size("2GB"), None);
+/// ```
+pub fn parse_size(s: &str) -> Option<u64> {
+    let s = s.trim();
+    if s == "infinity" {
+        return Some(u64::MAX);
+    } else if s.is_empty() {
+        return None;
+    }
+    let mut rest = s;
+    let mut total: u64 = 0;
+    while !rest.is_empty() {
+        let number_len = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(rest.len());
+        let (number, tail) = rest.split_at(number_len);
+        let tail = tail.trim_start();
+        let (factor, tail) = match tail.chars().next() {
+            Some(suffix) if suffix.is_alphabetic() => {
+                let exponent = "BKMGTPE".find(suffix)?;
+                (1u64 << (10 * exponent), &tail[suffix.len_utf8()..])
+            }
+            _ => (1, tail),
+        };
+        rest = tail.trim_start();
+
+        let bytes = u64::try_from(scale(number, u128::from(factor))?).ok()?;
+        total = total.checked_add(bytes)?;
+    }
+    Some(total)
+}
+
+/// `number`, which can have a fraction, times `factor`.  `None` if it isn't
+/// a valid number or overflows.
+fn scale(number: &str, factor: u128) -> Option<u128> {
+    let mut parts = number.splitn(2, '.');
+    let whole = parts.next().unwrap_or_default();
+    let fraction = parts.next().unwrap_or_default();
+    if whole.is_empty() && fraction.is_empty() || fraction.contains('.') {
+        return None;
+    }
+    let whole: u128 = if whole.is_empty() {
+        0
+    } else {
+        whole.parse().ok()?
+    };
+    let fraction = &fraction[..fraction.len().min(18)];
+    let fraction = if fraction.is_empty() {
+        0
+    } else {
+        factor.checked_mul(fraction.parse().ok()?)?
+            / 10u128.pow(fraction.len() as u32)
+    };
+    whole.checked_mul(factor)?.checked_add(fraction)
+}
+
+/// The XDG base directory in the environment variable `var`, defaulting to
+/// `default` in the home directory.
+fn xdg_dir(var: &str, default: &str) -> Option<PathBuf> {
+    env::var_os(var)
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            env::var_os("HOME").map(|home| Path::new(&home).join(default))
+        })
+}
+
+/// Split `s` at whitespace outside of single or double quotes, removing
+/// the quotes and backslashes before escaped characters.
+fn split_quoted(s: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = None;
+    let mut quote = None;
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match (c, quote) {
+            ('\\', _) => {
+                let word = word.get_or_insert_with(String::new);
+                word.extend(chars.next());
+            }
+            (c, Some(q)) if c == q => quote = None,
+            (c, Some(_)) => word.get_or_insert_with(String::new).push(c),
+            ('"', None) | ('\'', None) => {
+                quote = Some(c);
+                word.get_or_insert_with(String::new);
+            }
+            (c, None) if c.is_whitespace() => words.extend(word.take()),
+            (c, None) => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word);
+    words
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Entry;
+
+    #[test]
+    fn typed() {
+        let entry = Entry::parse(
+            &b"[Unit]\nDescription=Foo\nRequires=a.service b.service\n\
+               [Service]\nType=oneshot\nExecStart=/bin/foo\nRestart=weekly\n\
+               User=foo\n[Install]\nAlias=bar.service"[..],
+        )
+        .unwrap();
+        let unit = entry.systemd_unit();
+        assert_eq!(unit.description(), Some("Foo"));
+        assert_eq!(unit.requires(), ["a.service", "b.service"]);
+        assert!(unit.wants().is_empty());
+        assert_eq!(unit.service_type(), ServiceType::Oneshot);
+        assert_eq!(unit.exec_start(), ["/bin/foo"]);
+        assert_eq!(unit.restart(), Restart::Unknown("weekly".to_owned()));
+        assert_eq!(unit.user(), Some("foo"));
+        assert_eq!(unit.alias(), ["bar.service"]);
+
+        let entry = Entry::parse(&b"[Unit]\nDescription=Foo"[..]).unwrap();
+        let unit = entry.systemd_unit();
+        assert_eq!(unit.service_type(), ServiceType::Simple);
+        assert_eq!(unit.restart(), Restart::No);
+        assert_eq!(ServiceType::NotifyReload.to_string(), "notify-reload");
+    }
+
+    #[test]
+    fn multiple_assignments() {
+        let entry = Entry::parse(
+            &b"[Unit]\nAfter=a.service\nAfter=b.service c.service\n\
+               Description=Foo\nDescription=\n\
+               [Service]\nExecStart=/bin/a\nExecStart=\nExecStart=/bin/b\n\
+               Environment=\"A=1 2\" B=3\n\
+               [Unit]\nAfter=d.service\nBefore=e.service\nBefore=\n\
+               [Service]\nEnvironment=A=4 'C=\\'5\\''"[..],
+        )
+        .unwrap();
+        let unit = entry.systemd_unit();
+        assert_eq!(
+            unit.after(),
+            ["a.service", "b.service", "c.service", "d.service"]
+        );
+        assert!(unit.before().is_empty());
+        assert_eq!(unit.description(), None);
+        assert_eq!(unit.exec_start(), ["/bin/b"]);
+        let var = |name: &str, value: &str| (name.to_owned(), value.to_owned());
+        assert_eq!(
+            unit.environment(),
+            [var("A", "4"), var("B", "3"), var("C", "'5'")]
+        );
+    }
+
+    #[test]
+    fn dirs() {
+        assert_eq!(
+            unit_dirs(UnitScope::System),
+            [
+                Path::new("/etc/systemd/system"),
+                Path::new("/run/systemd/system"),
+                Path::new("/usr/local/lib/systemd/system"),
+                Path::new("/usr/lib/systemd/system"),
+            ]
+        );
+        let dirs = unit_dirs(UnitScope::User);
+        let position =
+            |dir: &str| dirs.iter().position(|d| d == Path::new(dir));
+        assert!(position("/etc/systemd/user") < position("/run/systemd/user"));
+        assert!(
+            position("/run/systemd/user")
+                < position("/usr/local/lib/systemd/user")
+        );
+        if let Some(data) = xdg_dir("XDG_DATA_HOME", ".local/share") {
+            let data =
+                dirs.iter().position(|d| *d == data.join("systemd/user"));
+            assert!(position("/run/systemd/user") < data);
+            assert!(data < position("/usr/local/lib/systemd/user"));
+        }
+    }
+
+    #[test]
+    fn drop_ins() {
+        let dirs = ["./test_data/units/etc", "./test_data/units/lib"];
+        let loaded = load_unit_in(&dirs, "foo.service").unwrap();
+        assert_eq!(loaded.path, Path::new("./test_data/units/lib/foo.service"));
+        assert_eq!(
+            loaded.drop_ins,
+            [
+                Path::new("./test_data/units/etc/foo.service.d/10-exec.conf"),
+                Path::new("./test_data/units/lib/foo.service.d/20-order.conf"),
+                Path::new("./test_data/units/etc/foo.service.d/override.conf"),
+            ]
+        );
+        let unit = loaded.unit();
+        assert_eq!(unit.description(), Some("Foo"));
+        assert_eq!(unit.after(), ["network.target", "dbus.service"]);
+        assert_eq!(unit.exec_start(), ["/usr/bin/foo --verbose"]);
+        assert_eq!(unit.restart(), Restart::OnFailure);
+        assert_eq!(unit.environment().len(), 2);
+
+        match load_unit_in(&dirs, "bar.service") {
+            Err(ParseError::Io(e)) => {
+                assert_eq!(e.kind(), io::ErrorKind::NotFound)
+            }
+            _ => panic!("bar.service shouldn't exist"),
+        }
+    }
+
+    #[test]
+    fn unit_names() {
+        let name = UnitName::parse("sshd.service").unwrap();
+        assert_eq!(name.prefix(), "sshd");
+        assert_eq!(name.instance(), None);
+        assert!(!name.is_template() && !name.is_instance());
+        assert_eq!(name.template(), None);
+        assert!(name.instantiate("x").is_err());
+
+        let name = UnitName::parse("foo@bar\\x2dbaz.socket").unwrap();
+        assert!(name.is_instance());
+        assert_eq!(name.instance(), Some("bar\\x2dbaz"));
+        let template = name.template().unwrap();
+        assert_eq!(template.to_string(), "foo@.socket");
+        assert!(template.instantiate("").is_err());
+        assert!(template.instantiate("a/b").is_err());
+
+        let invalid = ["foo", "foo.bar", "@x.service", "a@b@c.service"];
+        for name in &invalid {
+            assert!(UnitName::parse(name).is_err(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn template_drop_ins() {
+        let dirs = ["./test_data/units/etc", "./test_data/units/lib"];
+        let loaded = load_unit_in(&dirs, "worker@1.service").unwrap();
+        assert_eq!(
+            loaded.path,
+            Path::new("./test_data/units/lib/worker@.service")
+        );
+        assert_eq!(loaded.drop_ins.len(), 2);
+        let unit = loaded.unit();
+        assert_eq!(unit.exec_start(), ["/usr/bin/worker --id %i"]);
+        assert_eq!(unit.user(), Some("worker1"));
+    }
+
+    #[test]
+    fn escaping() {
+        let cases = [
+            ("foo", "foo"),
+            ("a-b/c", "a\\x2db-c"),
+            (".hidden/x.y", "\\x2ehidden-x.y"),
+            ("ä \\", "\\xc3\\xa4\\x20\\x5c"),
+        ];
+        for (s, escaped) in &cases {
+            assert_eq!(escape_name(s), *escaped);
+            assert_eq!(unescape_name(escaped).as_deref(), Some(*s));
+        }
+        assert_eq!(unescape_name("a\\x2"), None);
+        assert_eq!(unescape_name("a\\y20"), None);
+        assert_eq!(unescape_name("\\xff"), None);
+
+        assert_eq!(escape_path("//dev//sda1/"), "dev-sda1");
+        assert_eq!(unescape_path("dev-sda1").as_deref(), Some("/dev/sda1"));
+        assert_eq!(unescape_path("-").as_deref(), Some("/"));
+        assert_eq!(
+            UnitName::from_path("/var/lib/my-data", "mount")
+                .unwrap()
+                .to_string(),
+            "var-lib-my\\x2ddata.mount"
+        );
+        assert_eq!(
+            UnitName::from_path("/", "mount").unwrap().to_string(),
+            "-.mount"
+        );
+
+        let specifiers = Specifiers::new("fsck@dev-disk-by\\x2dlabel.service");
+        assert_eq!(specifiers.get('I'), Some("dev/disk/by-label"));
+        assert_eq!(specifiers.get('f'), Some("/dev/disk/by-label"));
+        assert_eq!(specifiers.get('P'), Some("fsck"));
+        let specifiers = Specifiers::new("home-my\\x2ddata.mount");
+        assert_eq!(specifiers.get('J'), Some("my-data"));
+        assert_eq!(specifiers.get('f'), Some("/home/my-data"));
+    }
+
+    #[test]
+    fn time_spans() {
+        let ms = Duration::from_millis;
+        let cases = [
+            ("100ms", ms(100)),
+            ("1h30m", ms(5_400_000)),
+            ("2 min 3.5s", ms(123_500)),
+            ("  1d ", ms(86_400_000)),
+            ("1w 1y", ms(604_800_000 + 31_557_600_000)),
+            ("0.5", ms(500)),
+            ("1.25us", Duration::from_nanos(1_250)),
+            ("3 hours 2 minutes", ms(10_920_000)),
+        ];
+        for (s, duration) in &cases {
+            assert_eq!(parse_time_span(s), Some(*duration), "{}", s);
+        }
+        for s in &["", "s", "1x", "-5s", "1..5s", "5min 20 xs"] {
+            assert_eq!(parse_time_span(s), None, "{}", s);
+        }
+
+        let entry = Entry::parse(
+            &b"[Service]\nRestartSec=5\nTimeoutSec=infinity\n\
+               TimeoutStopSec=1min"[..],
+        )
+        .unwrap();
+        let unit = entry.systemd_unit();
+        assert_eq!(unit.restart_sec(), Some(Duration::from_secs(5)));
+        assert_eq!(unit.timeout_start_sec(), Some(Duration::MAX));
+        assert_eq!(unit.timeout_stop_sec(), Some(Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn sizes() {
+        let cases = [
+            ("0", 0),
+            ("512B", 512),
+            ("4K", 4096),
+            ("100M", 100 << 20),
+            ("1G 512M", 3 << 29),
+            (" 2 T ", 2 << 40),
+            ("0.5E", 1 << 59),
+        ];
+        for (s, size) in &cases {
+            assert_eq!(parse_size(s), Some(*size), "{}", s);
+        }
+        for s in &["", "K", "1k", "1KB", "-1", "20E", "50%", "1.2.3M"] {
+            assert_eq!(parse_size(s), None, "{}", s);
+        }
+
+        let entry =
+            Entry::parse(&b"[Service]\nMemoryMax=2G\nLimitFSIZE=100M"[..])
+                .unwrap();
+        let unit = entry.systemd_unit();
+        assert_eq!(unit.memory_max(), Some(2 << 30));
+        assert_eq!(unit.size("Service", "LimitFSIZE"), Some(100 << 20));
+    }
+
+    #[test]
+    fn enable_plan() {
+        let dirs = ["./test_data/units/etc", "./test_data/units/lib"];
+        let config = Path::new("/etc/systemd/system");
+        let link = |path: &str, target: &str| Symlink {
+            path: config.join(path),
+            target: PathBuf::from(target),
+        };
+
+        let plan = load_unit_in(&dirs, "foo.service")
+            .unwrap()
+            .enable_plan(config);
+        assert_eq!(
+            plan.symlinks,
+            [
+                link(
+                    "multi-user.target.wants/foo.service",
+                    "./test_data/units/lib/foo.service"
+                ),
+                link("bar.service", "./test_data/units/lib/foo.service"),
+            ]
+        );
+        assert_eq!(plan.also, ["foo.socket"]);
+
+        let worker = "./test_data/units/lib/worker@.service";
+        let plan = load_unit_in(&dirs, "worker@.service")
+            .unwrap()
+            .enable_plan(config);
+        assert_eq!(
+            plan.symlinks,
+            [
+                link("workers@main.target.wants/worker@main.service", worker),
+                link("worker-main.service", worker),
+            ]
+        );
+        let plan = load_unit_in(&dirs, "worker@2.service")
+            .unwrap()
+            .enable_plan(config);
+        assert_eq!(
+            plan.symlinks[0],
+            link("workers@2.target.wants/worker@2.service", worker)
+        );
+    }
+
+    #[test]
+    fn timer() {
+        let entry = Entry::parse(
+            &b"[Timer]\nOnCalendar=hourly\nOnCalendar=\n\
+               OnCalendar=Mon 10:00\nOnCalendar=never\nOnCalendar=daily"[..],
+        )
+        .unwrap();
+        assert_eq!(
+            entry.systemd_unit().on_calendar(),
+            [
+                CalendarEvent::parse("Mon 10:00").unwrap(),
+                CalendarEvent::parse("daily").unwrap()
+            ]
+        );
+    }
+
+    #[test]
+    fn specifiers() {
+        let specifiers = Specifiers::new("getty-serial@ttyS0.service")
+            .user("root")
+            .uid(0)
+            .host("box.example.com");
+        let expand = |value: &str| specifiers.expand(value);
+        assert_eq!(
+            expand("%n %N %p %i %j").unwrap(),
+            "getty-serial@ttyS0.service getty-serial@ttyS0 getty-serial \
+             ttyS0 serial"
+        );
+        assert_eq!(
+            expand("%u:%U@%H (%l) 100%%").unwrap(),
+            "root:0@box.example.com (box) 100%"
+        );
+        assert_eq!(expand("%h"), Err(SpecifierError::Unknown('h')));
+        assert_eq!(
+            expand("50%"),
+            Err(SpecifierError::Unterminated {
+                value: "50%".to_owned()
+            })
+        );
+
+        let specifiers = Specifiers::new("sshd.service");
+        assert_eq!(specifiers.get('p'), Some("sshd"));
+        assert_eq!(specifiers.get('i'), Some(""));
+        assert_eq!(
+            Specifiers::current("sshd.service", UnitScope::System).get('S'),
+            Some("/var/lib")
+        );
+    }
+
+    #[test]
+    fn quoted() {
+        assert_eq!(
+            split_quoted(r#" a "b c"d  e\ f '' "#),
+            ["a", "b cd", "e f", ""]
+        );
+    }
+}
@@ -0,0 +1,435 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Validation of desktop entries against the [Desktop Entry
+//! spec](https://specifications.freedesktop.org/desktop-entry-spec/desktop-entry-spec-latest.html).
+//!
+//! The checks are similar to the ones done by `desktop-file-validate`.
+//!
+//! Example:
+//! ```
+//! use freedesktop_entry_parser::{validate::validate, Entry, Severity};
+//!
+//! let entry = Entry::parse(&b"[Desktop Entry]\nType=Application\nTerminal=yes"[..])?;
+//! let findings = validate(&entry);
+//! assert!(findings.iter().any(|f| f.severity == Severity::Error));
+//! for finding in findings {
+//!     println!("{}", finding);
+//! }
+//! # Ok::<(), freedesktop_entry_parser::ParseError>(())
+//! ```
+use crate::{AttrSelector, EntryRef, Severity};
+use std::fmt::{self, Display, Formatter};
+
+/// A problem found by [`validate`](fn.validate.html).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    /// How serious the problem is
+    pub severity: Severity,
+    /// Section the problem is in
+    pub section: String,
+    /// Attribute the problem is with, if it's about a single attribute
+    pub attr: Option<String>,
+    /// Description of the problem
+    pub message: String,
+}
+
+impl Display for Finding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}]", self.section)?;
+        if let Some(attr) = &self.attr {
+            write!(f, " {}", attr)?;
+        }
+        write!(f, ": {}: {}", self.severity, self.message)
+    }
+}
+
+/// Types of values from the spec.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Kind {
+    String,
+    LocaleString,
+    IconString,
+    Boolean,
+    Strings,
+    LocaleStrings,
+}
+
+pub(crate) const ENTRY_KEYS: &[(&str, Kind)] = &[
+    ("Type", Kind::String),
+    ("Version", Kind::String),
+    ("Name", Kind::LocaleString),
+    ("GenericName", Kind::LocaleString),
+    ("NoDisplay", Kind::Boolean),
+    ("Comment", Kind::LocaleString),
+    ("Icon", Kind::IconString),
+    ("Hidden", Kind::Boolean),
+    ("OnlyShowIn", Kind::Strings),
+    ("NotShowIn", Kind::Strings),
+    ("DBusActivatable", Kind::Boolean),
+    ("TryExec", Kind::String),
+    ("Exec", Kind::String),
+    ("Path", Kind::String),
+    ("Terminal", Kind::Boolean),
+    ("Actions", Kind::Strings),
+    ("MimeType", Kind::Strings),
+    ("Categories", Kind::Strings),
+    ("Implements", Kind::Strings),
+    ("Keywords", Kind::LocaleStrings),
+    ("StartupNotify", Kind::Boolean),
+    ("StartupWMClass", Kind::String),
+    ("URL", Kind::String),
+    ("PrefersNonDefaultGPU", Kind::Boolean),
+    ("SingleMainWindow", Kind::Boolean),
+];
+
+pub(crate) const ACTION_KEYS: &[(&str, Kind)] = &[
+    ("Name", Kind::LocaleString),
+    ("Icon", Kind::IconString),
+    ("Exec", Kind::String),
+];
+
+pub(crate) const DEPRECATED_KEYS: &[&str] = &[
+    "Encoding",
+    "MiniIcon",
+    "TerminalOptions",
+    "Protocols",
+    "Extensions",
+    "BinaryPattern",
+    "MapNotify",
+    "SwallowTitle",
+    "SwallowExec",
+    "SortOrder",
+    "FilePattern",
+    "Patterns",
+    "DefaultApp",
+    "Dev",
+    "FSType",
+    "MountPoint",
+    "ReadOnly",
+    "UnmountIcon",
+];
+
+const TYPES: &[&str] = &["Application", "Link", "Directory"];
+
+/// Pairs of keys that can't be used together
+const EXCLUSIVE_KEYS: &[(&str, &str)] = &[("OnlyShowIn", "NotShowIn")];
+
+/// Check an entry against the Desktop Entry spec.
+///
+/// Returns everything that was wrong, errors are things the spec requires
+/// and warnings are things it recommends or deprecates.
+pub fn validate(entry: &EntryRef<'_>) -> Vec<Finding> {
+    let mut validator = Validator {
+        findings: Vec::new(),
+    };
+    validator.validate(entry);
+    validator.findings
+}
+
+struct Validator {
+    findings: Vec<Finding>,
+}
+
+impl Validator {
+    fn validate(&mut self, entry: &EntryRef<'_>) {
+        match entry.sections().next() {
+            None => {
+                return self.push(
+                    Severity::Error,
+                    "Desktop Entry",
+                    None,
+                    "Missing `Desktop Entry` section",
+                )
+            }
+            Some(first) if first.name() != "Desktop Entry" => self.push(
+                Severity::Error,
+                first.name(),
+                None,
+                "The first section must be `Desktop Entry`",
+            ),
+            Some(_) => {}
+        }
+        let main = entry.section("Desktop Entry");
+        if entry.has_section("Desktop Entry") {
+            self.main_section(&main);
+        }
+        let actions: Vec<&str> = main
+            .attr("Actions")
+            .map(|actions| split_list(actions).collect())
+            .unwrap_or_default();
+
+        for section in entry.sections() {
+            let name = section.name();
+            if name == "Desktop Entry" || name.starts_with("X-") {
+                continue;
+            }
+            match name.strip_prefix("Desktop Action ") {
+                Some(action) => {
+                    if !actions.contains(&action) {
+                        self.push(
+                            Severity::Warning,
+                            name,
+                            None,
+                            "Action isn't listed in `Actions`",
+                        );
+                    }
+                    self.keys(&section, ACTION_KEYS);
+                    if !section.has_attr("Name") {
+                        self.missing(name, "Name");
+                    }
+                }
+                None => self.push(
+                    Severity::Error,
+                    name,
+                    None,
+                    "Unknown section, sections extending the format must \
+                     start with `X-`",
+                ),
+            }
+        }
+        for action in actions {
+            if !entry.has_section(format!("Desktop Action {}", action)) {
+                self.push(
+                    Severity::Error,
+                    "Desktop Entry",
+                    Some("Actions"),
+                    format!("Action `{}` has no section", action),
+                );
+            }
+        }
+    }
+
+    fn main_section(&mut self, section: &AttrSelector<'_, &str>) {
+        let name = section.name();
+        self.keys(section, ENTRY_KEYS);
+        for key in &["Type", "Name"] {
+            if !section.has_attr(key) {
+                self.missing(name, key);
+            }
+        }
+        match section.attr("Type") {
+            Some("Link") if !section.has_attr("URL") => {
+                self.missing(name, "URL")
+            }
+            Some("Application")
+                if !section.has_attr("Exec")
+                    && section.attr("DBusActivatable") != Some("true") =>
+            {
+                self.missing(name, "Exec")
+            }
+            Some(ty) if !TYPES.contains(&ty) && !ty.starts_with("X-") => self
+                .push(
+                    Severity::Error,
+                    name,
+                    Some("Type"),
+                    format!("Unknown type `{}`", ty),
+                ),
+            _ => {}
+        }
+        for (a, b) in EXCLUSIVE_KEYS {
+            if section.has_attr(a) && section.has_attr(b) {
+                self.push(
+                    Severity::Error,
+                    name,
+                    Some(b),
+                    format!("`{}` and `{}` can't be used together", a, b),
+                );
+            }
+        }
+    }
+
+    /// Check the names and values of every attribute in `section`.
+    fn keys(
+        &mut self,
+        section: &AttrSelector<'_, &str>,
+        known: &[(&str, Kind)],
+    ) {
+        let name = section.name();
+        for attr in section.attrs() {
+            if attr.name.starts_with("X-") {
+                continue;
+            }
+            if DEPRECATED_KEYS.contains(&attr.name) {
+                self.push(
+                    Severity::Warning,
+                    name,
+                    Some(attr.name),
+                    "Key is deprecated",
+                );
+                continue;
+            }
+            let kind = match known.iter().find(|(key, _)| *key == attr.name) {
+                Some((_, kind)) => *kind,
+                None => {
+                    self.push(
+                        Severity::Error,
+                        name,
+                        Some(attr.name),
+                        "Unknown key, keys extending the format must start \
+                         with `X-`",
+                    );
+                    continue;
+                }
+            };
+            if let Some(value) = attr.value {
+                self.value(name, attr.name, kind, value);
+            }
+            let localized = matches!(
+                kind,
+                Kind::LocaleString | Kind::IconString | Kind::LocaleStrings
+            );
+            if attr.has_params() && !localized {
+                self.push(
+                    Severity::Error,
+                    name,
+                    Some(attr.name),
+                    "Key can't be localized",
+                );
+            }
+        }
+    }
+
+    fn value(&mut self, section: &str, attr: &str, kind: Kind, value: &str) {
+        match kind {
+            Kind::Boolean => match value {
+                "true" | "false" => {}
+                "0" | "1" => self.push(
+                    Severity::Warning,
+                    section,
+                    Some(attr),
+                    format!(
+                        "Boolean value `{}` is deprecated, use `true` or \
+                         `false`",
+                        value
+                    ),
+                ),
+                _ => self.push(
+                    Severity::Error,
+                    section,
+                    Some(attr),
+                    format!("`{}` isn't a boolean", value),
+                ),
+            },
+            Kind::String | Kind::Strings => {
+                if value.chars().any(|c| !c.is_ascii() || c.is_ascii_control())
+                {
+                    self.push(
+                        Severity::Error,
+                        section,
+                        Some(attr),
+                        "Value must be printable ASCII",
+                    );
+                }
+            }
+            Kind::LocaleString | Kind::IconString | Kind::LocaleStrings => {}
+        }
+    }
+
+    fn missing(&mut self, section: &str, attr: &str) {
+        self.push(
+            Severity::Error,
+            section,
+            Some(attr),
+            format!("Required key `{}` is missing", attr),
+        );
+    }
+
+    fn push(
+        &mut self,
+        severity: Severity,
+        section: &str,
+        attr: Option<&str>,
+        message: impl Into<String>,
+    ) {
+        self.findings.push(Finding {
+            severity,
+            section: section.to_owned(),
+            attr: attr.map(str::to_owned),
+            message: message.into(),
+        });
+    }
+}
+
+/// Split a `;` separated list, ignoring the trailing `;`
+fn split_list(list: &str) -> impl Iterator<Item = &str> {
+    list.split(';').filter(|item| !item.is_empty())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Entry;
+
+    fn errors(input: &str) -> Vec<(Option<String>, Severity)> {
+        let entry = Entry::parse_str(input).unwrap();
+        validate(&entry)
+            .into_iter()
+            .map(|f| (f.attr, f.severity))
+            .collect()
+    }
+
+    #[test]
+    fn valid() {
+        let entry = Entry::parse_file("./test_data/firefox.desktop").unwrap();
+        let findings = validate(&entry);
+        assert!(
+            findings.iter().all(|f| f.severity != Severity::Error),
+            "{:?}",
+            findings
+        );
+    }
+
+    #[test]
+    fn required() {
+        assert_eq!(
+            errors("[Desktop Entry]\nType=Application\nName=Foo"),
+            [(Some("Exec".to_owned()), Severity::Error)]
+        );
+        assert_eq!(
+            errors("[Desktop Entry]\nType=Link\nName=Foo"),
+            [(Some("URL".to_owned()), Severity::Error)]
+        );
+    }
+
+    #[test]
+    fn values() {
+        let input = "[Desktop Entry]\nType=Directory\nName=Foo\n\
+                     Terminal=yes\nHidden=0\nEncoding=UTF-8\nFoo=bar\n\
+                     X-Foo=bar\nExec[de]=foo\nOnlyShowIn=A;\nNotShowIn=B;";
+        let found = errors(input);
+        let attr = |s: &str| Some(s.to_owned());
+        assert_eq!(
+            found,
+            [
+                (attr("Terminal"), Severity::Error),
+                (attr("Hidden"), Severity::Warning),
+                (attr("Encoding"), Severity::Warning),
+                (attr("Foo"), Severity::Error),
+                (attr("Exec"), Severity::Error),
+                (attr("NotShowIn"), Severity::Error),
+            ]
+        );
+    }
+
+    #[test]
+    fn sections() {
+        let input = "[Desktop Entry]\nType=Directory\nName=Foo\nActions=a;b;\n\
+                     [Desktop Action a]\nName=A\n[Desktop Action c]\nName=C\n\
+                     [X-Foo]\nBar=1\n[Foo]";
+        let entry = Entry::parse_str(input).unwrap();
+        let sections: Vec<_> = validate(&entry)
+            .into_iter()
+            .map(|f| (f.section, f.severity))
+            .collect();
+        assert_eq!(
+            sections,
+            [
+                ("Desktop Action c".to_owned(), Severity::Warning),
+                ("Foo".to_owned(), Severity::Error),
+                ("Desktop Entry".to_owned(), Severity::Error),
+            ]
+        );
+    }
+}
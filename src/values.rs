@@ -0,0 +1,324 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::{errors::EntryError, AttrSelector, Locale};
+use std::{borrow::Cow, error::Error, str::FromStr};
+
+impl<'a, T: AsRef<str>> AttrSelector<'a, T> {
+    /// Get the value of the attribute `name` as a boolean.
+    ///
+    /// Only `true` and `false` are accepted, as the spec requires.  Returns
+    /// `None` if the attribute is missing or isn't a boolean.
+    pub fn attr_bool(&self, name: impl AsRef<str>) -> Option<bool> {
+        parse_bool(self.attr(name)?)
+    }
+
+    /// Get the value of the attribute `name` as an integer, like the `Size`
+    /// of an icon theme directory.  Returns `None` if the attribute is
+    /// missing or isn't an integer.
+    pub fn attr_int(&self, name: impl AsRef<str>) -> Option<i64> {
+        self.attr(name)?.parse().ok()
+    }
+
+    /// Get the value of the attribute `name` as a number.  Returns `None` if
+    /// the attribute is missing or isn't a number.
+    pub fn attr_float(&self, name: impl AsRef<str>) -> Option<f64> {
+        self.attr(name)?.parse().ok()
+    }
+
+    /// Get the value of the attribute `name` parsed as `V`.
+    ///
+    /// Returns `Ok(None)` if the attribute is missing and an error naming
+    /// the section and attribute if the value couldn't be parsed.
+    ///
+    /// Example:
+    /// ```
+    /// use freedesktop_entry_parser::Entry;
+    ///
+    /// let entry = Entry::parse(&b"[Icon Theme]\nSize=48"[..])?;
+    /// let size: Option<u32> = entry.section("Icon Theme").attr_parse("Size")?;
+    /// assert_eq!(size, Some(48));
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn attr_parse<V>(
+        &self,
+        name: impl AsRef<str>,
+    ) -> Result<Option<V>, EntryError>
+    where
+        V: FromStr,
+        V::Err: Error + Send + Sync + 'static,
+    {
+        let name = name.as_ref();
+        let value = match self.attr(name) {
+            Some(value) => value,
+            None => return Ok(None),
+        };
+        value
+            .parse()
+            .map(Some)
+            .map_err(|e| EntryError::InvalidValue {
+                section: self.name().to_owned(),
+                attr: name.to_owned(),
+                value: value.to_owned(),
+                source: Box::new(e),
+            })
+    }
+
+    /// Get the value of the attribute `name` with escape sequences decoded.
+    /// See [`unescape`](fn.unescape.html).
+    pub fn attr_unescaped(
+        &self,
+        name: impl AsRef<str>,
+    ) -> Option<Cow<'a, str>> {
+        self.attr(name).map(unescape)
+    }
+
+    /// Get the items of a `;` separated list like `Categories` or
+    /// `MimeType`.
+    ///
+    /// A `;` can be escaped as `\;` to be part of an item.  The trailing
+    /// `;` is optional.  Escape sequences in the items are decoded, see
+    /// [`unescape`](fn.unescape.html).  Returns an empty iterator if the attribute is
+    /// missing.
+    pub fn attr_list(&self, name: impl AsRef<str>) -> ListIter<'a> {
+        ListIter {
+            rem: self.attr(name).unwrap_or(""),
+        }
+    }
+
+    /// Get the items of the localized list `name` that best matches
+    /// `locale`, like `Keywords[de]`.  The value is found as in
+    /// [`attr_localized`](struct.AttrSelector.html#method.attr_localized)
+    /// and split as in [`attr_list`](#method.attr_list).
+    ///
+    /// Example:
+    /// ```
+    /// use freedesktop_entry_parser::Entry;
+    ///
+    /// let entry = Entry::parse(&b"[A]\nKeywords=Files;\nKeywords[de]=Dateien;Ordner;"[..])?;
+    /// let keywords: Vec<_> = entry
+    ///     .section("A")
+    ///     .attr_list_localized("Keywords", &"de_DE".parse()?)
+    ///     .collect();
+    /// assert_eq!(keywords, ["Dateien", "Ordner"]);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn attr_list_localized(
+        &self,
+        name: impl AsRef<str>,
+        locale: &Locale,
+    ) -> ListIter<'a> {
+        ListIter {
+            rem: self.attr_localized(name, locale).unwrap_or(""),
+        }
+    }
+}
+
+/// Iterator over the items of a list value.
+///
+/// Created by [`AttrSelector::attr_list`](struct.AttrSelector.html#method.attr_list)
+pub struct ListIter<'a> {
+    rem: &'a str,
+}
+
+impl<'a> ListIter<'a> {
+    /// Iterator over the items of `value`
+    pub(crate) fn new(value: &'a str) -> Self {
+        ListIter { rem: value }
+    }
+}
+
+impl<'a> Iterator for ListIter<'a> {
+    type Item = Cow<'a, str>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rem.is_empty() {
+            return None;
+        }
+        let bytes = self.rem.as_bytes();
+        let mut end = 0;
+        while end < bytes.len() && bytes[end] != b';' {
+            // Skip the escaped character
+            if bytes[end] == b'\\' {
+                end += 1;
+            }
+            end += 1;
+        }
+        let end = end.min(bytes.len());
+        let item = &self.rem[..end];
+        self.rem = self.rem.get(end + 1..).unwrap_or("");
+        Some(unescape_with(item, true))
+    }
+}
+
+/// Decode the escape sequences `\s`, `\n`, `\t`, `\r` and `\\` in a value.
+///
+/// Unknown escape sequences and `\;`, which is only an escape in lists, are
+/// kept as is.  Use
+/// [`AttrSelector::attr_list`](struct.AttrSelector.html#method.attr_list)
+/// to get the items of a list.
+///
+/// Example:
+/// ```
+/// use freedesktop_entry_parser::unescape;
+///
+/// assert_eq!(unescape(r"Hello\sWorld\n"), "Hello World\n");
+/// ```
+pub fn unescape(value: &str) -> Cow<'_, str> {
+    unescape_with(value, false)
+}
+
+/// Escape a value so it can be written to a file.  The reverse of
+/// [`unescape`](fn.unescape.html).
+///
+/// Leading spaces are escaped as `\s`, otherwise they'd be dropped when
+/// parsing.
+pub fn escape(value: &str) -> Cow<'_, str> {
+    let leading = value.len() - value.trim_start_matches(' ').len();
+    if leading == 0 && !value.contains(&['\\', '\n', '\t', '\r'][..]) {
+        return Cow::Borrowed(value);
+    }
+    let mut escaped = String::with_capacity(value.len() + 2);
+    for (i, c) in value.char_indices() {
+        match c {
+            ' ' if i < leading => escaped.push_str("\\s"),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            '\r' => escaped.push_str("\\r"),
+            c => escaped.push(c),
+        }
+    }
+    Cow::Owned(escaped)
+}
+
+/// Escape the line breaks in a value that's already written with escape
+/// sequences, so writing it out can't start a new line.
+pub(crate) fn escape_line_breaks(value: &str) -> Cow<'_, str> {
+    if !value.contains(&['\n', '\r'][..]) {
+        return Cow::Borrowed(value);
+    }
+    Cow::Owned(value.replace('\n', "\\n").replace('\r', "\\r"))
+}
+
+/// Decode escape sequences, including `\;` if `list` is set.
+fn unescape_with(value: &str, list: bool) -> Cow<'_, str> {
+    if !value.contains('\\') {
+        return Cow::Borrowed(value);
+    }
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('s') => unescaped.push(' '),
+            Some('n') => unescaped.push('\n'),
+            Some('t') => unescaped.push('\t'),
+            Some('r') => unescaped.push('\r'),
+            Some('\\') => unescaped.push('\\'),
+            Some(';') if list => unescaped.push(';'),
+            Some(c) => {
+                unescaped.push('\\');
+                unescaped.push(c);
+            }
+            None => unescaped.push('\\'),
+        }
+    }
+    Cow::Owned(unescaped)
+}
+
+pub(crate) fn parse_bool(value: &str) -> Option<bool> {
+    match value {
+        "true" => Some(true),
+        "false" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Entry;
+
+    #[test]
+    fn parse() {
+        let entry = Entry::parse(&b"[A]\nSize=48\nScale=x"[..]).unwrap();
+        let section = entry.section("A");
+        assert_eq!(section.attr_parse::<u8>("Size").unwrap(), Some(48));
+        assert_eq!(section.attr_parse::<u8>("Missing").unwrap(), None);
+        match section.attr_parse::<u8>("Scale") {
+            Err(EntryError::InvalidValue {
+                section,
+                attr,
+                value,
+                ..
+            }) => assert_eq!((&*section, &*attr, &*value), ("A", "Scale", "x")),
+            _ => panic!("Expected an invalid value error"),
+        }
+    }
+
+    #[test]
+    fn escapes() {
+        assert_eq!(unescape(r"a\sb\\n\n\x\;\"), "a b\\n\n\\x\\;\\");
+        assert!(matches!(unescape("plain"), Cow::Borrowed(_)));
+        let value = "  two\tlines\nC:\\ ";
+        assert_eq!(escape(value), r"\s\stwo\tlines\nC:\\ ");
+        assert_eq!(unescape(&escape(value)), value);
+        assert!(matches!(escape("plain value"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn list() {
+        let entry = Entry::parse(
+            &b"[A]\nCategories=GTK;Network;\nKeywords=a\\;b;c\\sd\nEmpty="[..],
+        )
+        .unwrap();
+        let section = entry.section("A");
+        let categories: Vec<_> = section.attr_list("Categories").collect();
+        assert_eq!(categories, ["GTK", "Network"]);
+        let keywords: Vec<_> = section.attr_list("Keywords").collect();
+        assert_eq!(keywords, ["a;b", "c d"]);
+        assert_eq!(section.attr_list("Empty").count(), 0);
+        assert_eq!(section.attr_list("Missing").count(), 0);
+    }
+
+    #[test]
+    fn localized_list() {
+        let entry =
+            Entry::parse(&b"[A]\nKeywords=a;b\nKeywords[de]=c\\;d;e\\s;"[..])
+                .unwrap();
+        let section = entry.section("A");
+        let list = |locale: &str| {
+            section
+                .attr_list_localized("Keywords", &locale.parse().unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(list("de_AT"), ["c;d", "e "]);
+        assert_eq!(list("fr"), ["a", "b"]);
+        let locale = "de".parse().unwrap();
+        assert_eq!(section.attr_list_localized("Missing", &locale).count(), 0);
+    }
+
+    #[test]
+    fn typed() {
+        let entry = Entry::parse(
+            &b"[A]\nTerminal=true\nHidden=1\nSize=48\nScale=-2\nVersion=1.5\n\
+               Bad=1.5.1"[..],
+        )
+        .unwrap();
+        let section = entry.section("A");
+        assert_eq!(section.attr_bool("Terminal"), Some(true));
+        assert_eq!(section.attr_bool("Hidden"), None);
+        assert_eq!(section.attr_bool("Missing"), None);
+        assert_eq!(section.attr_int("Size"), Some(48));
+        assert_eq!(section.attr_int("Scale"), Some(-2));
+        assert_eq!(section.attr_int("Version"), None);
+        assert_eq!(section.attr_float("Version"), Some(1.5));
+        assert_eq!(section.attr_float("Size"), Some(48.0));
+        assert_eq!(section.attr_float("Bad"), None);
+    }
+}
@@ -0,0 +1,187 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::{
+    internal::AttrMap, values::escape_line_breaks, AttrSelector, Entry,
+    EntryRef, Section,
+};
+use std::{
+    fmt::{self, Display, Formatter},
+    io::{self, Write},
+};
+
+impl<'a> EntryRef<'a> {
+    /// Write the entry out in the keyfile format.
+    ///
+    /// Each section is written as a `[NAME]` header followed by its
+    /// attributes.  Attributes with params are written as `name[param]=value`
+    /// right after the attribute's value without a param.  Sections are
+    /// separated by an empty line.  The `""` section of attributes before the
+    /// first header is always the first section and is written without a
+    /// header.
+    ///
+    /// Values are written as they're stored, with their escape sequences,
+    /// except that line breaks are escaped as `\n` and `\r` so a value can't
+    /// add lines to the file.
+    pub fn write_to(&self, mut writer: impl Write) -> io::Result<()> {
+        write!(writer, "{}", self)
+    }
+}
+
+impl Display for Entry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl<'a> Display for EntryRef<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (i, section) in self.0.sections().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            Display::fmt(section, f)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Display for Section<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write_section(f, self.name(), Some(self.attrs()))
+    }
+}
+
+impl<'a, T: AsRef<str>> Display for AttrSelector<'a, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write_section(f, self.name(), self.section)
+    }
+}
+
+fn write_section(
+    f: &mut Formatter<'_>,
+    name: &str,
+    attrs: Option<&AttrMap<'_>>,
+) -> fmt::Result {
+    // Attributes from before the first header
+    if !name.is_empty() {
+        writeln!(f, "[{}]", name)?;
+    }
+    let attrs = match attrs {
+        Some(attrs) => attrs,
+        None => return Ok(()),
+    };
+    for attr_name in attrs.attr_names_iter() {
+        let attr = match attrs.get_attr(attr_name) {
+            Some(attr) => attr,
+            None => continue,
+        };
+        for value in attr.get_values() {
+            writeln!(f, "{}={}", attr_name, escape_line_breaks(value))?;
+        }
+        let params = match attr.get_params() {
+            Some(params) => params,
+            None => continue,
+        };
+        for param_name in params.param_names_iter() {
+            if let Some(value) = params.get_param(param_name) {
+                writeln!(
+                    f,
+                    "{}[{}]={}",
+                    attr_name,
+                    param_name,
+                    escape_line_breaks(value)
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Entry, LeadingAttrs, ParserOptions};
+
+    #[test]
+    fn round_trip() {
+        let entry = Entry::parse_file("./test_data/firefox.desktop").unwrap();
+        let reparsed = Entry::parse(entry.to_string()).unwrap();
+        for section in entry.sections() {
+            for attr in section.attrs() {
+                assert_eq!(
+                    reparsed.section(section.name()).attr(attr.name),
+                    attr.value
+                );
+                for param in attr.params() {
+                    assert_eq!(
+                        reparsed
+                            .section(section.name())
+                            .attr_with_param(attr.name, param.param_val),
+                        Some(param.value)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn multi_line_value() {
+        let mut entry = Entry::default();
+        let value = "foo\n[Evil]\r\nExec=rm";
+        entry.section_mut("Desktop Entry").set_attr("Name", value);
+        entry
+            .section_mut("Desktop Entry")
+            .set_attr_with_param("Name", "de", value);
+        let written = entry.to_string();
+        assert_eq!(
+            written,
+            "[Desktop Entry]\nName=foo\\n[Evil]\\r\\nExec=rm\n\
+             Name[de]=foo\\n[Evil]\\r\\nExec=rm\n"
+        );
+        let reparsed = Entry::parse(written).unwrap();
+        assert!(!reparsed.has_section("Evil"));
+        let section = reparsed.section("Desktop Entry");
+        assert!(!section.has_attr("Exec"));
+        assert_eq!(section.attr_unescaped("Name").as_deref(), Some(value));
+        assert_eq!(
+            section.attr_with_param("Name", "de").map(crate::unescape),
+            Some(value.into())
+        );
+    }
+
+    #[test]
+    fn global_section() {
+        let mut entry = Entry::parse(&b"[A]\nX=1"[..]).unwrap();
+        entry.section_mut("").set_attr("G", "1");
+        let written = entry.to_string();
+        assert_eq!(written, "G=1\n\n[A]\nX=1\n");
+        let options =
+            ParserOptions::default().leading_attrs(LeadingAttrs::Global);
+        let reparsed = Entry::parse_with(written, &options).unwrap();
+        assert_eq!(reparsed.section("").attr("G"), Some("1"));
+        assert_eq!(reparsed.section("A").attr("X"), Some("1"));
+        assert_eq!(reparsed.section("A").attr("G"), None);
+    }
+
+    #[test]
+    fn display_section() {
+        let entry = Entry::parse(&b"[A]\nB=1\n[C]\nD=2\n"[..]).unwrap();
+        assert_eq!(entry.section("C").to_string(), "[C]\nD=2\n");
+        assert_eq!(entry["A"].to_string(), "[A]\nB=1\n");
+        assert_eq!(entry.section("E").to_string(), "[E]\n");
+    }
+
+    #[test]
+    fn write_params() {
+        let entry =
+            Entry::parse(&b"[Desktop Entry]\nName[de]=Hallo\nName=Hello\n"[..])
+                .unwrap();
+        let mut buf = Vec::new();
+        entry.write_to(&mut buf).unwrap();
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            "[Desktop Entry]\nName=Hello\nName[de]=Hallo\n"
+        );
+    }
+}